@@ -0,0 +1,129 @@
+use std::error::Error;
+use std::fs::{ self, File };
+use std::io::{ BufWriter, Write };
+use std::path::{ Path, PathBuf };
+use std::process;
+
+use clap::ValueEnum;
+
+use crate::error::ClodError;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum BackupPolicy {
+	/// Don't keep a copy of the original file
+	None,
+	/// Keep one .bak copy next to the file, replacing any older one
+	#[default]
+	Single,
+	/// Keep every copy next to the file, as .bak1, .bak2, ...
+	Numbered,
+	/// Keep one copy in a separate backup folder
+	Dir
+}
+
+// how to keep a copy of a file before a command overwrites it
+#[derive(Clone, Default)]
+pub struct Backup {
+	pub policy: BackupPolicy,
+	// folder for BackupPolicy::Dir; defaults to a "backup" folder next to the file
+	pub dir: Option<PathBuf>
+}
+
+impl Backup {
+	// copies the file as the policy says, returning where the copy went
+	pub fn backup(&self, path: &Path) -> Result<Option<PathBuf>, Box<dyn Error>> {
+		let file_name = path.file_name().ok_or(format!("{} is not a file", path.to_string_lossy()))?.to_string_lossy();
+		let backup_path = match self.policy {
+			BackupPolicy::None => return Ok(None),
+			BackupPolicy::Single => path.with_file_name(format!("{file_name}.bak")),
+			BackupPolicy::Numbered => {
+				let mut i = 1;
+				while path.with_file_name(format!("{file_name}.bak{i}")).exists() {
+					i += 1;
+				}
+				path.with_file_name(format!("{file_name}.bak{i}"))
+			}
+			BackupPolicy::Dir => {
+				let dir = self.dir.clone().unwrap_or(path.with_file_name("backup"));
+				fs::create_dir_all(&dir).map_err(|err| ClodError::io(&dir, err))?;
+				dir.join(file_name.as_ref())
+			}
+		};
+		fs::copy(path, &backup_path).map_err(|err| ClodError::io(&backup_path, err))?;
+		Ok(Some(backup_path))
+	}
+}
+
+// writes to a temporary file next to the destination and renames it into place,
+// so an interrupted write never leaves a truncated file behind
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+	write_atomic_with(path, |file| Ok(file.write_all(bytes)?))
+}
+
+// like write_atomic, but streams the contents into the temporary file
+pub fn write_atomic_with(path: &Path, write: impl FnOnce(&mut BufWriter<File>) -> Result<(), Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+	let file_name = path.file_name().ok_or(format!("{} is not a file", path.to_string_lossy()))?.to_string_lossy();
+	let temp_path = path.with_file_name(format!(".{file_name}.{}.tmp", process::id()));
+
+	let result = File::create(&temp_path)
+		.map_err(|err| ClodError::io(path, err).into())
+		.and_then(|file| {
+			let mut writer = BufWriter::new(file);
+			write(&mut writer)?;
+			let file = writer.into_inner().map_err(|err| ClodError::io(path, err.into_error()))?;
+			file.sync_all().map_err(|err| ClodError::io(path, err))?;
+			fs::rename(&temp_path, path).map_err(|err| ClodError::io(path, err).into())
+		});
+
+	if result.is_err() {
+		let _ = fs::remove_file(&temp_path);
+	}
+	result
+}
+
+// backs up a file that's being modified in place, then replaces it atomically
+pub fn replace_file(path: &Path, bytes: &[u8], backup: &Backup) -> Result<(), Box<dyn Error>> {
+	backup.backup(path)?;
+	write_atomic(path, bytes)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::io::Write;
+
+	use crate::backup::{ Backup, BackupPolicy, replace_file, write_atomic_with };
+	use crate::test_support::test_dir;
+
+	#[test]
+	fn failed_writes_leave_the_target_alone() {
+		let dir = test_dir("backup_failed_write");
+		let target = dir.join("target.package");
+		fs::write(&target, b"original").unwrap();
+
+		let result = write_atomic_with(&target, |file| {
+			file.write_all(b"partial")?;
+			Err("write failed".into())
+		});
+		assert!(result.is_err());
+		assert_eq!(fs::read(&target).unwrap(), b"original");
+		// only the target is left, without the temporary file
+		assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+		fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn numbered_backups_are_all_kept() {
+		let dir = test_dir("backup_numbered");
+		let target = dir.join("target.package");
+		let backup = Backup { policy: BackupPolicy::Numbered, dir: None };
+		fs::write(&target, b"first").unwrap();
+		replace_file(&target, b"second", &backup).unwrap();
+		replace_file(&target, b"third", &backup).unwrap();
+
+		assert_eq!(fs::read(&target).unwrap(), b"third");
+		assert_eq!(fs::read(dir.join("target.package.bak1")).unwrap(), b"first");
+		assert_eq!(fs::read(dir.join("target.package.bak2")).unwrap(), b"second");
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
@@ -1,20 +1,17 @@
 use std::error::Error;
-use std::fs;
 use std::io::{ self, Write };
 use std::path::PathBuf;
 
+use crate::backup::Backup;
 use crate::dbpf::Dbpf;
 use crate::dbpf::resource::DecodedResource;
 
-pub fn edit_gzps(files: Vec<PathBuf>, property: &str, value: &str) -> Result<(), Box<dyn Error>> {
+pub fn edit_gzps(files: Vec<PathBuf>, property: &str, value: &str, preserve: bool, backup: &Backup) -> Result<(), Box<dyn Error>> {
 	for file in files {
 		if file.is_file() && file.extension().is_some_and(|e| e == "package") {
 			print!("Editing {}...", file.to_string_lossy());
 			io::stdout().flush()?;
 
-			// make backup copy
-			fs::copy(&file, file.with_extension("package.bak"))?;
-
 			// read package file
 			let mut package = Dbpf::read_from_file(&file, "")?;
 
@@ -26,7 +23,8 @@ pub fn edit_gzps(files: Vec<PathBuf>, property: &str, value: &str) -> Result<(),
 			}
 
 			// save package file
-			package.write_to_file(&file)?;
+			backup.backup(&file)?;
+			package.write_to_file(&file, preserve)?;
 
 			println!(" DONE");
 		}
@@ -0,0 +1,181 @@
+use std::collections::{ HashMap, HashSet };
+use std::error::Error;
+use std::path::PathBuf;
+
+use crate::conflicts::find_packages;
+use crate::dbpf::{ Identifier, TypeId };
+use crate::dbpf::index::DbpfIndex;
+use crate::dbpf::resource_types::idr::Idr;
+use crate::dbpf::resource_types::shpe::Shpe;
+use crate::dbpf::resource_types::gmnd::Gmnd;
+
+pub fn check_links(dir: PathBuf, game: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+	let mut files = find_packages(&dir)?;
+	let num_downloads = files.len();
+	if let Some(game) = &game {
+		files.extend(find_packages(game)?);
+	}
+
+	let Links { mut unresolved, unused_meshes } = find_broken_links(&files, num_downloads);
+
+	println!("Checked {} packages ({} from the game)", files.len(), files.len() - num_downloads);
+
+	unresolved.sort_by_key(|(file_index, _)| *file_index);
+	if !unresolved.is_empty() {
+		println!();
+		println!("Unresolved references:");
+		let mut last_file = None;
+		for (file_index, problem) in &unresolved {
+			if last_file != Some(file_index) {
+				println!("{}", files[*file_index].to_string_lossy());
+				last_file = Some(file_index);
+			}
+			println!("    {problem}");
+		}
+	}
+
+	if !unused_meshes.is_empty() {
+		println!();
+		println!("Meshes without recolors:");
+		for (file_index, shpe) in &unused_meshes {
+			println!("{}: {} ({})", files[*file_index].to_string_lossy(), shpe.id, shpe.block.file_name);
+		}
+	}
+
+	println!();
+	println!("{} unresolved references, {} meshes without recolors", unresolved.len(), unused_meshes.len());
+
+	Ok(())
+}
+
+struct Links {
+	// file index -> problems
+	unresolved: Vec<(usize, String)>,
+	unused_meshes: Vec<(usize, Shpe)>
+}
+
+// the first num_downloads files are checked, the rest are only searched for what they reference
+fn find_broken_links(files: &[PathBuf], num_downloads: usize) -> Links {
+	// TGIR -> index of the first file containing it
+	let mut ids: HashMap<Identifier, usize> = HashMap::new();
+	let mut idrs: Vec<(usize, Idr)> = Vec::new();
+	let mut shpes: Vec<(usize, Shpe)> = Vec::new();
+	let mut gmnds: HashMap<Identifier, Gmnd> = HashMap::new();
+	let mut gzps_ids: Vec<(usize, Identifier)> = Vec::new();
+	let mut object_files: HashSet<usize> = HashSet::new();
+
+	for (file_index, file) in files.iter().enumerate() {
+		let is_download = file_index < num_downloads;
+		let mut index = match DbpfIndex::open(file) {
+			Ok(index) => index,
+			Err(err) => {
+				println!("WARNING: skipping {}: {err}", file.to_string_lossy());
+				continue;
+			}
+		};
+
+		for entry in &index.index_entries {
+			ids.entry(entry.id.clone()).or_insert(file_index);
+		}
+
+		// only the small resources that hold references are decoded; meshes and textures just need to exist.
+		// game files are only searched for 3IDRs, since anything they reference is already there
+		let index_entries = index.index_entries.clone();
+		for entry in index_entries {
+			let result = match entry.id.type_id {
+				TypeId::Idr => index.read_entry(&entry).and_then(|r| Idr::new(&r)).map(|idr| idrs.push((file_index, idr))),
+				TypeId::Shpe if is_download => index.read_entry(&entry).and_then(|r| Shpe::new(&r)).map(|shpe| shpes.push((file_index, shpe))),
+				TypeId::Gmnd if is_download => index.read_entry(&entry).and_then(|r| Gmnd::new(&r)).map(|gmnd| { gmnds.insert(gmnd.id.clone(), gmnd); }),
+				TypeId::Gzps if is_download => {
+					gzps_ids.push((file_index, entry.id.clone()));
+					Ok(())
+				}
+				TypeId::Mmat if is_download => {
+					object_files.insert(file_index);
+					Ok(())
+				}
+				_ => Ok(())
+			};
+			if let Err(err) = result {
+				println!("WARNING: failed to decode {} in {}: {err}", entry.id, file.to_string_lossy());
+			}
+		}
+	}
+
+	// file index -> problems
+	let mut unresolved: Vec<(usize, String)> = Vec::new();
+	let mut missing = |file_index: usize, from: &Identifier, id: &Identifier| {
+		if !ids.contains_key(id) {
+			unresolved.push((file_index, format!("{from}: missing {id}")));
+		}
+	};
+
+	for (file_index, gzps_id) in &gzps_ids {
+		missing(*file_index, gzps_id, &Identifier { type_id: TypeId::Idr, ..gzps_id.clone() });
+	}
+
+	let mut shpes_by_id: HashMap<&Identifier, &Shpe> = HashMap::new();
+	for (_, shpe) in &shpes {
+		shpes_by_id.entry(&shpe.id).or_insert(shpe);
+	}
+
+	// same chain as Outfit::from_resources: 3IDR -> CRES, SHPE -> GMND -> GMDC, TXMTs
+	for (file_index, idr) in idrs.iter().filter(|(file_index, _)| *file_index < num_downloads) {
+		if let Some(cres_ref) = &idr.cres_ref {
+			missing(*file_index, &idr.id, cres_ref);
+		}
+		if let Some(shpe_ref) = &idr.shpe_ref {
+			missing(*file_index, &idr.id, shpe_ref);
+			if let Some(gmnd_ref) = shpes_by_id.get(shpe_ref).and_then(|shpe| shpe.gmnd_ref.as_ref()) {
+				missing(*file_index, shpe_ref, gmnd_ref);
+				if let Some(gmnd) = gmnds.get(gmnd_ref) {
+					missing(*file_index, gmnd_ref, &gmnd.gmdc_ref);
+				}
+			}
+		}
+		for txmt_ref in &idr.txmt_refs {
+			missing(*file_index, &idr.id, txmt_ref);
+		}
+	}
+
+	// object meshes are recolored through MMATs rather than 3IDRs, so they're left out
+	let recolored = idrs.iter().filter_map(|(_, idr)| idr.shpe_ref.as_ref()).collect::<HashSet<&Identifier>>();
+	let unused_meshes = shpes.iter()
+		.filter(|(file_index, shpe)| !object_files.contains(file_index) && !recolored.contains(&shpe.id))
+		.cloned()
+		.collect::<Vec<(usize, Shpe)>>();
+
+	Links {
+		unresolved,
+		unused_meshes
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use crate::check_links::find_broken_links;
+	use crate::dbpf::{ Identifier, TypeId };
+	use crate::dbpf::resource::Resource;
+	use crate::test_support::{ test_dir, write_package };
+	use crate::test_support::fixtures::{ gzps_resource, idr_resource };
+
+	#[test]
+	fn missing_references_are_reported() {
+		let dir = test_dir("check_links");
+		let file = dir.join("outfit.package");
+		// the 3IDR references a CRES, a SHPE and two TXMTs, and only the CRES is there
+		let reference = |type_id, instance_id| Identifier::new(u32::from(type_id), 0x1C050000, 0xABCD0000 + instance_id, instance_id);
+		let cres = Resource { id: reference(TypeId::Cres, 1), data: Vec::new() };
+		write_package(&file, vec![(gzps_resource(), false), (idr_resource(), false), (cres, false)]);
+
+		let links = find_broken_links(&[file], 1);
+		let idr_id = idr_resource().id;
+		let expected = [reference(TypeId::Shpe, 2), reference(TypeId::Txmt, 3), reference(TypeId::Txmt, 4)]
+			.map(|id| (0, format!("{idr_id}: missing {id}")));
+		assert_eq!(links.unresolved, expected);
+		assert!(links.unused_meshes.is_empty());
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
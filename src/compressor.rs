@@ -1,31 +1,74 @@
 use std::error::Error;
-use std::fs::{ self, File };
-use std::io::{ self, Cursor, Write };
-use std::path::PathBuf;
+use std::fs;
+use std::io::{ self, Write };
+use std::path::{ Path, PathBuf };
 
+use crate::backup::{ Backup, write_atomic_with };
 use crate::dbpf::Dbpf;
+use crate::dbpf::compression::CompressionPolicy;
+use crate::dbpf::format::PackageFormat;
+use crate::dbpf::index::DbpfIndex;
+use crate::dbpf::resource::Resource;
+use crate::error::ClodError;
 
-pub fn compress_packages(files: Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+pub fn compress_packages(files: Vec<PathBuf>, policy: CompressionPolicy, backup: &Backup) -> Result<(), Box<dyn Error>> {
 	for file in files {
 		if file.is_file() && file.extension().is_some_and(|e| e == "package") {
-			print!("Compressing {}...", file.to_string_lossy());
-			io::stdout().flush()?;
-
-			// make backup copy
-			fs::copy(&file, file.with_extension("package.bak"))?;
-
-			// read package file
-			let bytes = fs::read(&file)?;
-			let (resources, header, _) = Dbpf::read_resources(&bytes)?;
+			rewrite_package(&file, "Compressing", backup, None, |resource, _| policy.allows(resource))?;
+		}
+	}
+	Ok(())
+}
 
-			// save package file with compression
-			let mut cur = Cursor::new(Vec::new());
-			Dbpf::write_resources(resources, header, &mut cur, true)?;
-			let mut new_file = File::create(&file)?;
-			new_file.write_all(&cur.into_inner())?;
+pub fn decompress_packages(files: Vec<PathBuf>, backup: &Backup) -> Result<(), Box<dyn Error>> {
+	for file in files {
+		if file.is_file() && file.extension().is_some_and(|e| e == "package") {
+			rewrite_package(&file, "Decompressing", backup, None, |_, _| false)?;
+		}
+	}
+	Ok(())
+}
 
-			println!(" DONE");
+// rewrites packages for another game version, keeping each resource compressed if it was
+pub fn convert_packages(files: Vec<PathBuf>, format: PackageFormat, backup: &Backup) -> Result<(), Box<dyn Error>> {
+	for file in files {
+		if file.is_file() && file.extension().is_some_and(|e| e == "package") {
+			rewrite_package(&file, "Converting", backup, Some(format), |_, was_compressed| was_compressed)?;
 		}
 	}
 	Ok(())
 }
+
+// streams the resources of the package into a new one next to it, which then replaces it.
+// compress is called with each resource and whether it was compressed in the original
+fn rewrite_package(
+		file: &Path,
+		action: &str,
+		backup: &Backup,
+		format: Option<PackageFormat>,
+		compress: impl Fn(&Resource, bool) -> bool + Sync
+	) -> Result<(), Box<dyn Error>> {
+
+	print!("{action} {}...", file.to_string_lossy());
+	io::stdout().flush()?;
+
+	let old_size = fs::metadata(file).map_err(|err| ClodError::io(file, err))?.len();
+	let mut index = DbpfIndex::open(file)?;
+	let header = index.header.clone();
+	let index_entries = index.index_entries.clone();
+	let dir = index.dir.clone();
+
+	backup.backup(file)?;
+	// the original is only closed once the new package is complete
+	write_atomic_with(file, move |writer| {
+		let resources = index_entries.iter().map(|index_entry| index.read_entry(index_entry));
+		let was_compressed = |resource: &Resource| dir.as_ref().is_some_and(|dir| dir.uncompressed_size(&resource.id).is_some());
+		Dbpf::write_resources_with(resources, header, writer, |resource| compress(resource, was_compressed(resource)), format)
+	})?;
+
+	let new_size = fs::metadata(file).map_err(|err| ClodError::io(file, err))?.len();
+	let change = (new_size as f64 / old_size.max(1) as f64 - 1.0) * 100.0;
+	println!(" DONE ({old_size} -> {new_size} bytes, {change:+.1}%)");
+
+	Ok(())
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{ Path, PathBuf };
+
+use crate::dbpf::{ Identifier, TypeId };
+use crate::dbpf::index::DbpfIndex;
+
+// every package file under a folder, in the order the game loads them
+pub fn find_packages(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+	let mut files = Vec::new();
+	let mut dirs = vec![dir.to_path_buf()];
+	while let Some(dir) = dirs.pop() {
+		for entry in (fs::read_dir(&dir)?).flatten() {
+			let entry_path = entry.path();
+			if entry_path.is_dir() {
+				dirs.push(entry_path);
+			} else if entry_path.is_file() && entry_path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("package")) {
+				files.push(entry_path);
+			}
+		}
+	}
+	files.sort_by_key(|file| file.to_string_lossy().to_lowercase());
+	Ok(files)
+}
+
+fn is_default_type(type_id: TypeId) -> bool {
+	matches!(type_id, TypeId::Gzps | TypeId::Idr | TypeId::Xtol)
+}
+
+pub fn scan_conflicts(dir: PathBuf, all_types: bool) -> Result<(), Box<dyn Error>> {
+	let files = find_packages(&dir)?;
+	let conflicts = find_conflicts(&files, all_types);
+
+	println!("Scanned {} packages ({} resources)", files.len(), conflicts.num_resources);
+
+	if !conflicts.resources.is_empty() {
+		println!();
+		println!("Conflicting resources:");
+		for (id, file_indices) in &conflicts.resources {
+			println!("{id}");
+			for (i, file_index) in file_indices.iter().enumerate() {
+				let last = if i == file_indices.len() - 1 { " (loaded last)" } else { "" };
+				println!("    {}{last}", files[*file_index].to_string_lossy());
+			}
+		}
+	}
+
+	if !conflicts.groups.is_empty() {
+		println!();
+		println!("Shared GZPS group IDs:");
+		for (group_id, gzps_ids) in &conflicts.groups {
+			println!("0x{group_id:08X}");
+			for (file_index, id) in gzps_ids {
+				println!("    {}: {id}", files[*file_index].to_string_lossy());
+			}
+		}
+	}
+
+	println!();
+	println!("{} conflicting resources, {} shared GZPS group IDs", conflicts.resources.len(), conflicts.groups.len());
+
+	Ok(())
+}
+
+struct Conflicts {
+	num_resources: usize,
+	// TGIRs in more than one file, with the indices of the files containing them
+	resources: Vec<(Identifier, Vec<usize>)>,
+	// recolors sharing a group id with a different GZPS from another package, as (file index, GZPS id)
+	groups: Vec<(u32, Vec<(usize, Identifier)>)>
+}
+
+fn find_conflicts(files: &[PathBuf], all_types: bool) -> Conflicts {
+	// TGIR -> indices of the files containing it
+	let mut tgir_files: BTreeMap<Identifier, Vec<usize>> = BTreeMap::new();
+	// GZPS group id -> (file index, GZPS id)
+	let mut gzps_groups: BTreeMap<u32, Vec<(usize, Identifier)>> = BTreeMap::new();
+	let mut num_resources = 0;
+
+	for (file_index, file) in files.iter().enumerate() {
+		let index = match DbpfIndex::open(file) {
+			Ok(index) => index,
+			Err(err) => {
+				println!("WARNING: skipping {}: {err}", file.to_string_lossy());
+				continue;
+			}
+		};
+		for entry in &index.index_entries {
+			let id = &entry.id;
+			num_resources += 1;
+
+			let file_indices = tgir_files.entry(id.clone()).or_default();
+			if !file_indices.contains(&file_index) {
+				file_indices.push(file_index);
+			}
+
+			// the local group is resolved per package, so it can't clash
+			if id.type_id == TypeId::Gzps && id.group_id != 0xFFFFFFFF {
+				gzps_groups.entry(id.group_id).or_default().push((file_index, id.clone()));
+			}
+		}
+	}
+
+	let resources = tgir_files.into_iter()
+		.filter(|(id, file_indices)| file_indices.len() > 1 && (all_types || is_default_type(id.type_id)))
+		.collect::<Vec<(Identifier, Vec<usize>)>>();
+
+	let groups = gzps_groups.into_iter()
+		.filter(|(_, gzps_ids)| gzps_ids.iter().any(|(file_index, id)|
+			gzps_ids.iter().any(|(other_index, other_id)| other_index != file_index && other_id != id)))
+		.collect::<Vec<(u32, Vec<(usize, Identifier)>)>>();
+
+	Conflicts {
+		num_resources,
+		resources,
+		groups
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use crate::conflicts::{ find_conflicts, find_packages };
+	use crate::dbpf::{ Identifier, TypeId };
+	use crate::test_support::{ test_dir, write_package };
+	use crate::test_support::fixtures::{ binx_resource, gzps_resource };
+
+	#[test]
+	fn shared_resources_and_groups_are_conflicts() {
+		let dir = test_dir("conflicts");
+		fs::create_dir_all(dir.join("sub")).unwrap();
+		let mut recolor = gzps_resource();
+		recolor.id = Identifier { instance_id: 1, ..recolor.id };
+		write_package(&dir.join("a.package"), vec![(gzps_resource(), false), (binx_resource(), false)]);
+		write_package(&dir.join("sub/b.package"), vec![(gzps_resource(), true), (binx_resource(), false), (recolor.clone(), false)]);
+
+		let files = find_packages(&dir).unwrap();
+		assert_eq!(files, [dir.join("a.package"), dir.join("sub/b.package")]);
+
+		let conflicts = find_conflicts(&files, false);
+		assert_eq!(conflicts.num_resources, 5);
+		assert_eq!(conflicts.resources, [(gzps_resource().id, vec![0, 1])]);
+		assert_eq!(conflicts.groups, [(0x1C050000, vec![(0, gzps_resource().id), (1, gzps_resource().id), (1, recolor.id)])]);
+
+		let types = find_conflicts(&files, true).resources.into_iter().map(|(id, _)| id.type_id).collect::<Vec<TypeId>>();
+		assert!(types.contains(&TypeId::Gzps) && types.contains(&TypeId::Binx) && types.len() == 2);
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
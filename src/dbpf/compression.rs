@@ -0,0 +1,49 @@
+use crate::dbpf::TypeId;
+use crate::dbpf::resource::Resource;
+
+// Decides which resources are worth compressing when a package is written,
+// for use with Dbpf::write_resources_with.
+#[derive(Clone, Default)]
+pub struct CompressionPolicy {
+	pub skip_types: Vec<TypeId>,
+	// resources smaller than this (uncompressed) are stored as-is
+	pub min_size: usize
+}
+
+impl CompressionPolicy {
+	pub fn allows(&self, resource: &Resource) -> bool {
+		!self.skip_types.contains(&resource.id.type_id) && resource.data.len() >= self.min_size
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+
+	use crate::dbpf::{ Dbpf, TypeId };
+	use crate::dbpf::compression::CompressionPolicy;
+	use crate::dbpf::header::Header;
+	use crate::dbpf::index::DbpfIndex;
+	use crate::test_support::resource;
+	use crate::test_support::fixtures::gzps_resource;
+
+	#[test]
+	fn skipped_and_small_resources_are_stored_uncompressed() {
+		let policy = CompressionPolicy { skip_types: vec![TypeId::Txtr], min_size: 100 };
+		let skipped = resource(TypeId::Txtr, b"repeated ".repeat(64));
+		let small = resource(TypeId::TextList, b"repeated ".repeat(8));
+		// both would get smaller if they were compressed
+		assert!(skipped.clone().compress().unwrap() && small.clone().compress().unwrap());
+		let resources = vec![gzps_resource(), skipped.clone(), small.clone()];
+
+		let mut cur = Cursor::new(Vec::new());
+		Dbpf::write_resources_with(resources.into_iter().map(Ok), Header::default(), &mut cur, |r| policy.allows(r), None).unwrap();
+
+		let index = DbpfIndex::new(Cursor::new(cur.into_inner())).unwrap();
+		let dir_ids = index.dir.as_ref().unwrap().items.iter().map(|item| item.id.clone()).collect::<Vec<_>>();
+		assert_eq!(dir_ids, [gzps_resource().id]);
+		for uncompressed in [skipped, small] {
+			assert_eq!(index.find(&uncompressed.id).unwrap().resource_size, uncompressed.data.len() as u32);
+		}
+	}
+}
@@ -0,0 +1,209 @@
+use std::error::Error;
+use std::io::Cursor;
+
+use binrw::{ BinRead, BinWrite };
+use clap::ValueEnum;
+
+use crate::dbpf::{ Identifier, TypeId };
+use crate::dbpf::header::Header;
+use crate::dbpf::resource::Resource;
+
+// RCOL resources that aren't decoded, but still have a link table to convert:
+// ANIM, CINE, the four LGHT types and LIFO
+const OTHER_RCOL_TYPES: [u32; 7] = [0xFB00791E, 0x4D51F042, 0xC9C81B9B, 0xC9C81BA3, 0xC9C81BA9, 0xC9C81BAD, 0xED534136];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum PackageFormat {
+	/// Header 1.0 and index 7.0, without resource ids (base game)
+	#[value(name = "7.0")]
+	Index70,
+	/// Header 1.1 and index 7.1, without resource ids
+	#[value(name = "7.1")]
+	Index71,
+	/// Header 1.1 and index 7.2, with resource ids (University and later)
+	#[value(name = "7.2")]
+	Index72
+}
+
+impl PackageFormat {
+	pub fn from_header(header: &Header) -> Self {
+		match (header.minor_version, header.index_minor_version) {
+			(_, 2..) => Self::Index72,
+			(0, _) => Self::Index70,
+			_ => Self::Index71
+		}
+	}
+
+	// whether references include a resource id (TGIR) or not (TGI)
+	pub fn use_tgir(self) -> bool {
+		self == Self::Index72
+	}
+
+	pub fn apply_to_header(self, header: &mut Header) {
+		header.major_version = 1;
+		header.index_major_version = 7;
+		(header.minor_version, header.index_minor_version) = match self {
+			Self::Index70 => (0, 0),
+			Self::Index71 => (1, 1),
+			Self::Index72 => (1, 2)
+		};
+	}
+
+	// rewrites the references stored inside a resource (RCOL links and 3IDR entries) in this format.
+	// resource ids are 0 when going to TGIR. going to TGI fails if there's a resource id that would be lost,
+	// since resources that only differ in it would then collide
+	pub fn convert(self, resource: Resource) -> Result<Resource, Box<dyn Error>> {
+		self.check_resource_id(&resource.id, &resource.id)?;
+		let data = match resource.id.type_id {
+			TypeId::Gmdc | TypeId::Gmnd | TypeId::Shpe | TypeId::Cres | TypeId::Txmt | TypeId::Txtr => self.convert_rcol(&resource)?,
+			TypeId::Other(type_id) if OTHER_RCOL_TYPES.contains(&type_id) => self.convert_rcol(&resource)?,
+			TypeId::Idr => self.convert_idr(&resource)?,
+			_ => return Ok(resource)
+		};
+		Ok(Resource { id: resource.id, data })
+	}
+
+	fn check_resource_id(self, from: &Identifier, id: &Identifier) -> Result<(), Box<dyn Error>> {
+		if !self.use_tgir() && id.resource_id != 0 {
+			let reference = if from == id { String::new() } else { format!(" (referenced by {from})") };
+			return Err(format!("{id}{reference} has a resource id, which packages without resource ids can't store").into());
+		}
+		Ok(())
+	}
+
+	fn convert_rcol(self, resource: &Resource) -> Result<Vec<u8>, Box<dyn Error>> {
+		let data = resource.data.as_slice();
+		let mut cur = Cursor::new(data);
+		let first = u32::read_le(&mut cur)?;
+		let use_tgir = first == 0xFFFF0001;
+		let num_links = if use_tgir { u32::read_le(&mut cur)? } else { first };
+		let mut links = Vec::new();
+		for _ in 0..num_links {
+			let group_id = u32::read_le(&mut cur)?;
+			let instance_id = u32::read_le(&mut cur)?;
+			let resource_id = if use_tgir { u32::read_le(&mut cur)? } else { 0 };
+			let type_id = u32::read_le(&mut cur)?;
+			links.push(Identifier::new(type_id, group_id, resource_id, instance_id));
+		}
+		if use_tgir == self.use_tgir() {
+			return Ok(data.to_vec());
+		}
+
+		let mut writer = Cursor::new(Vec::new());
+		if self.use_tgir() {
+			0xFFFF0001u32.write_le(&mut writer)?;
+		}
+		(links.len() as u32).write_le(&mut writer)?;
+		for link in &links {
+			self.check_resource_id(&resource.id, link)?;
+			link.group_id.write_le(&mut writer)?;
+			link.instance_id.write_le(&mut writer)?;
+			if self.use_tgir() {
+				link.resource_id.write_le(&mut writer)?;
+			}
+			u32::from(link.type_id).write_le(&mut writer)?;
+		}
+		// the blocks don't depend on the link format
+		writer.get_mut().extend_from_slice(&data[cur.position() as usize..]);
+		Ok(writer.into_inner())
+	}
+
+	fn convert_idr(self, resource: &Resource) -> Result<Vec<u8>, Box<dyn Error>> {
+		let data = resource.data.as_slice();
+		let mut cur = Cursor::new(data);
+		let magic = u32::read_le(&mut cur)?;
+		let use_tgir = u32::read_le(&mut cur)? == 2;
+		if use_tgir == self.use_tgir() {
+			return Ok(data.to_vec());
+		}
+
+		let num_entries = u32::read_le(&mut cur)?;
+		let mut writer = Cursor::new(Vec::new());
+		magic.write_le(&mut writer)?;
+		(if self.use_tgir() { 2u32 } else { 1u32 }).write_le(&mut writer)?;
+		num_entries.write_le(&mut writer)?;
+		for _ in 0..num_entries {
+			let entry = Identifier::read(&mut cur, use_tgir)?;
+			self.check_resource_id(&resource.id, &entry)?;
+			entry.write(&mut writer, self.use_tgir())?;
+		}
+		writer.get_mut().extend_from_slice(&data[cur.position() as usize..]);
+		Ok(writer.into_inner())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+	
+	use crate::dbpf::{ Identifier, TypeId };
+	use crate::dbpf::format::PackageFormat;
+	use crate::dbpf::resource::{ Resource, DecodedResource };
+	use crate::dbpf::resource_types::rcol::Rcol;
+	use crate::test_support::{ assert_round_trip, rcol };
+	use crate::test_support::fixtures::{ gmnd_resource, idr_resource };
+
+	// the fixtures with every resource id set to 0, so they can go to a format without them
+	fn without_resource_ids(resource: &Resource) -> Resource {
+		if let DecodedResource::Idr(mut idr) = assert_round_trip(resource) {
+			idr.id.resource_id = 0;
+			for id in idr.txmt_refs.iter_mut().chain(&mut idr.cres_ref).chain(&mut idr.shpe_ref).chain(&mut idr.gzps_ref) {
+				id.resource_id = 0;
+			}
+			return DecodedResource::Idr(idr).to_resource().unwrap();
+		}
+		let mut rcol = Rcol::read(&resource.data).unwrap();
+		for link in &mut rcol.links {
+			link.resource_id = 0;
+		}
+		let mut data = Cursor::new(Vec::new());
+		rcol.write(&mut data).unwrap();
+		let mut id = resource.id.clone();
+		id.resource_id = 0;
+		Resource { id, data: data.into_inner() }
+	}
+
+	#[test]
+	fn format_conversion_keeps_references() {
+		for resource in [idr_resource(), gmnd_resource()] {
+			let resource = without_resource_ids(&resource);
+			let tgi = PackageFormat::Index71.convert(resource.clone()).unwrap();
+			let tgir = PackageFormat::Index72.convert(tgi.clone()).unwrap();
+			let references = |decoded: DecodedResource| match decoded {
+				DecodedResource::Idr(idr) => idr.txmt_refs.iter().chain(&idr.shpe_ref).map(|id| (id.type_id, id.instance_id)).collect::<Vec<_>>(),
+				DecodedResource::Gmnd(gmnd) => vec![(gmnd.gmdc_ref.type_id, gmnd.gmdc_ref.instance_id)],
+				_ => unreachable!()
+			};
+			let original = references(assert_round_trip(&resource));
+			assert_eq!(references(assert_round_trip(&tgi)), original);
+			assert_eq!(references(assert_round_trip(&tgir)), original);
+			assert!(tgi.data.len() < resource.data.len());
+		}
+	}
+
+	#[test]
+	fn format_conversion_refuses_to_drop_resource_ids() {
+		for resource in [idr_resource(), gmnd_resource()] {
+			assert!(PackageFormat::Index70.convert(resource.clone()).is_err());
+
+			// only a reference has a resource id
+			let mut referencing = resource.clone();
+			referencing.id.resource_id = 0;
+			let err = PackageFormat::Index71.convert(referencing).err().unwrap();
+			assert!(err.to_string().contains("referenced by"), "{err}");
+		}
+	}
+
+	#[test]
+	fn format_conversion_covers_undecoded_rcol_types() {
+		let link = Identifier::new(u32::from(TypeId::Txmt), 0x1C050000, 0, 0x22222222);
+		let anim = Resource {
+			id: Identifier::new(0xFB00791E, 0x1C050000, 0, 0x9ABCDEF0),
+			data: rcol(&[link], TypeId::Other(0xFB00791E), &[1, 2, 3, 4])
+		};
+		let tgi = PackageFormat::Index71.convert(anim.clone()).unwrap();
+		assert_eq!(tgi.data.len(), anim.data.len() - 8);
+		assert_eq!(u32::from_le_bytes(tgi.data[..4].try_into().unwrap()), 1);
+		assert_eq!(PackageFormat::Index72.convert(tgi).unwrap().data, anim.data);
+	}
+}
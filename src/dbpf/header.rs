@@ -1,5 +1,5 @@
 use std::error::Error;
-use std::io::Cursor;
+use std::io::{ Cursor, Seek, Write };
 
 use binrw::{ BinRead, BinWrite };
 
@@ -7,11 +7,18 @@ use binrw::{ BinRead, BinWrite };
 pub struct Header {
 	pub major_version: u32,
 	pub minor_version: u32,
+	pub unknown: [u32; 3],
+	pub date_created: u32,
+	pub date_modified: u32,
 	pub index_major_version: u32,
 	pub index_minor_version: u32,
 	pub index_entry_count: u32,
 	pub index_offset: u32,
-	pub index_size: u32
+	pub index_size: u32,
+	pub hole_entry_count: u32,
+	pub hole_offset: u32,
+	pub hole_size: u32,
+	pub reserved: [u8; 32]
 }
 
 impl Default for Header {
@@ -19,11 +26,18 @@ impl Default for Header {
 		Self {
 			major_version: 1,
 			minor_version: 1,
+			unknown: [0; 3],
+			date_created: 0,
+			date_modified: 0,
 			index_major_version: 7,
 			index_minor_version: 2,
 			index_entry_count: 0,
 			index_offset: 0,
-			index_size: 0
+			index_size: 0,
+			hole_entry_count: 0,
+			hole_offset: 0,
+			hole_size: 0,
+			reserved: [0; 32]
 		}
 	}
 }
@@ -41,55 +55,58 @@ impl Header {
 			return Err("Not a Sims 2 DBPF file.".into());
 		}
 
-		let _unknown1 = u32::read_le(cur)?;
-		let _unknown2 = u32::read_le(cur)?;
-		let _unknown3 = u32::read_le(cur)?;
-		let _date_created = u32::read_le(cur)?;
-		let _date_modified = u32::read_le(cur)?;
+		let unknown = <[u32; 3]>::read_le(cur)?;
+		let date_created = u32::read_le(cur)?;
+		let date_modified = u32::read_le(cur)?;
 
 		let index_major_version = u32::read_le(cur)?;
 		let index_entry_count = u32::read_le(cur)?;
 		let index_offset = u32::read_le(cur)?;
 		let index_size = u32::read_le(cur)?;
 
-		let _hole_entry_count = u32::read_le(cur)?;
-		let _hole_offset = u32::read_le(cur)?;
-		let _hole_size = u32::read_le(cur)?;
+		let hole_entry_count = u32::read_le(cur)?;
+		let hole_offset = u32::read_le(cur)?;
+		let hole_size = u32::read_le(cur)?;
 
-		let index_minor_version = if minor_version >= 1 { u32::read_le(cur)? } else { 0 };
+		// reserved in 1.0 headers, where it's always 0 (index 7.0)
+		let index_minor_version = u32::read_le(cur)?;
 
-		let _unknown4 = u32::read_le(cur)?;
-		let _unknown5 = u32::read_le(cur)?;
+		let reserved = <[u8; 32]>::read_le(cur)?;
 
 		Ok(Header {
 			major_version,
 			minor_version,
+			unknown,
+			date_created,
+			date_modified,
 			index_major_version,
 			index_entry_count,
 			index_offset,
 			index_size,
-			index_minor_version
+			hole_entry_count,
+			hole_offset,
+			hole_size,
+			index_minor_version,
+			reserved
 		})
 	}
 
-	pub fn write(&self, writer: &mut Cursor<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+	pub fn write<W: Write + Seek>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
 		"DBPF".as_bytes().write(writer)?;
 		self.major_version.write_le(writer)?;
 		self.minor_version.write_le(writer)?;
-		[0u8; 12].write(writer)?;
-		0u32.write_le(writer)?; // date_created
-		0u32.write_le(writer)?; // date_modified
+		self.unknown.write_le(writer)?;
+		self.date_created.write_le(writer)?;
+		self.date_modified.write_le(writer)?;
 		self.index_major_version.write_le(writer)?;
 		self.index_entry_count.write_le(writer)?;
 		self.index_offset.write_le(writer)?;
 		self.index_size.write_le(writer)?;
-		0u32.write_le(writer)?; // hole_entry_count
-		0u32.write_le(writer)?; // hole_offset
-		0u32.write_le(writer)?; // hole_size
-		if self.minor_version >= 1 {
-			self.index_minor_version.write_le(writer)?;
-		}
-		[0u8; 32].write(writer)?;
+		self.hole_entry_count.write_le(writer)?;
+		self.hole_offset.write_le(writer)?;
+		self.hole_size.write_le(writer)?;
+		self.index_minor_version.write_le(writer)?;
+		self.reserved.write(writer)?;
 		Ok(())
 	}
 }
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{ BufReader, Cursor, Read, Seek, SeekFrom };
+use std::path::{ Path, PathBuf };
+
+use crate::dbpf::{ Dbpf, Identifier, TypeId };
+use crate::dbpf::header::Header;
+use crate::dbpf::index_entry::IndexEntry;
+use crate::dbpf::layout::{ Chunk, Layout };
+use crate::dbpf::resource::{ Resource, DecodedResource };
+use crate::dbpf::resource_types::dir::Dir;
+use crate::error::ClodError;
+use crate::parallel::try_par_map;
+
+// Reads only the header and index of a package up front; resources are read
+// from the underlying reader (and decompressed/decoded) one at a time, on request.
+pub struct DbpfIndex<R: Read + Seek> {
+	pub header: Header,
+	// the package file, when opened from one, for error messages
+	pub path: Option<PathBuf>,
+	pub index_entries: Vec<IndexEntry>,
+	pub dir_entry: Option<IndexEntry>,
+	pub dir: Option<Dir>,
+	dir_position: usize,
+	// position in index_entries by id, so lookups don't scan the index
+	positions: HashMap<Identifier, usize>,
+	reader: R
+}
+
+impl DbpfIndex<BufReader<File>> {
+	pub fn open(path: &Path) -> Result<Self, Box<dyn Error>> {
+		let file = File::open(path).map_err(|err| ClodError::io(path, err))?;
+		let mut index = Self::new(BufReader::new(file)).map_err(|err| ClodError::locate(err, Some(path), None))?;
+		index.path = Some(path.to_path_buf());
+		Ok(index)
+	}
+}
+
+impl<R: Read + Seek> DbpfIndex<R> {
+	pub fn new(mut reader: R) -> Result<Self, Box<dyn Error>> {
+		reader.seek(SeekFrom::Start(0))?;
+		let mut header_bytes = Vec::new();
+		reader.by_ref().take(96).read_to_end(&mut header_bytes)?;
+		let header = Header::read(&mut Cursor::new(&header_bytes[..]))
+			.map_err(|err| ClodError::parse(0, format!("invalid header: {err}")))?;
+
+		let index_offset = header.index_offset as u64;
+		reader.seek(SeekFrom::Start(index_offset))?;
+		let mut index_bytes = vec![0u8; header.index_entry_count as usize * IndexEntry::size(&header)];
+		reader.read_exact(&mut index_bytes)
+			.map_err(|err| ClodError::parse(index_offset, format!("index of {} entries is out of bounds: {err}", header.index_entry_count)))?;
+		let mut index_entries = IndexEntry::read_all(&mut Cursor::new(&index_bytes[..]), &header)
+			.map_err(|err| ClodError::parse(index_offset, format!("invalid index: {err}")))?;
+		let dir_position = index_entries.iter().position(|e| e.id.type_id == TypeId::Dir);
+		let dir_entry = dir_position.map(|i| index_entries.remove(i));
+
+		// the DIR decides which resources get decompressed, so it's read up front
+		let dir = match &dir_entry {
+			Some(dir_entry) => {
+				let dir_offset = dir_entry.resource_offset as u64;
+				let resource = Resource::read(&mut reader, dir_entry, None)
+					.map_err(|err| ClodError::parse(dir_offset, format!("failed to read DIR: {err}")))?;
+				Some(Dir::read(&resource, header.index_minor_version >= 2)
+					.map_err(|err| ClodError::parse(dir_offset, format!("invalid DIR: {err}")))?)
+			}
+			None => None
+		};
+
+		let mut positions = HashMap::new();
+		for (i, index_entry) in index_entries.iter().enumerate() {
+			positions.entry(index_entry.id.clone()).or_insert(i);
+		}
+
+		Ok(Self {
+			header,
+			path: None,
+			index_entries,
+			dir_entry,
+			dir,
+			dir_position: dir_position.unwrap_or(0),
+			positions,
+			reader
+		})
+	}
+
+	pub fn is_compressed(&self) -> bool {
+		self.dir_entry.is_some()
+	}
+
+	// every entry in the index, including the DIR, in the order they were stored
+	pub fn all_entries(&self) -> Vec<&IndexEntry> {
+		let mut entries = self.index_entries.iter().collect::<Vec<&IndexEntry>>();
+		if let Some(dir_entry) = &self.dir_entry {
+			entries.insert(self.dir_position.min(entries.len()), dir_entry);
+		}
+		entries
+	}
+
+	pub fn find(&self, id: &Identifier) -> Option<&IndexEntry> {
+		self.positions.get(id).map(|&i| &self.index_entries[i])
+	}
+
+	pub fn find_by_type(&self, type_id: TypeId) -> Vec<&IndexEntry> {
+		self.index_entries.iter().filter(|index_entry| index_entry.id.type_id == type_id).collect()
+	}
+
+	pub fn read_entry(&mut self, index_entry: &IndexEntry) -> Result<Resource, Box<dyn Error>> {
+		Resource::read(&mut self.reader, index_entry, self.dir.as_ref()).map_err(|err| self.read_error(index_entry, err))
+	}
+
+	fn read_error(&self, index_entry: &IndexEntry, err: Box<dyn Error>) -> Box<dyn Error> {
+		read_error(self.path.as_deref(), index_entry, err)
+	}
+
+	// decodes a resource read from this package, adding the package path and offset to any error
+	pub fn decode_entry(&mut self, index_entry: &IndexEntry, title: &str) -> Result<DecodedResource, Box<dyn Error>> {
+		self.read_entry(index_entry)?.decode(title)
+			.map_err(|err| ClodError::locate(err, self.path.as_deref(), Some(index_entry.resource_offset)))
+	}
+
+	pub fn read_resource(&mut self, id: &Identifier) -> Result<Resource, Box<dyn Error>> {
+		let index_entry = self.find(id).ok_or(format!("{id} not found in package"))?.clone();
+		self.read_entry(&index_entry)
+	}
+
+	pub fn decode_resource(&mut self, id: &Identifier, title: &str) -> Result<DecodedResource, Box<dyn Error>> {
+		let index_entry = self.find(id).ok_or(format!("{id} not found in package"))?.clone();
+		self.decode_entry(&index_entry, title)
+	}
+
+	pub fn read_all(&mut self) -> Result<Vec<Resource>, Box<dyn Error>> {
+		let index_entries = self.index_entries.clone();
+		index_entries.iter().map(|index_entry| self.read_entry(index_entry)).collect()
+	}
+
+	pub fn read_holes(&mut self) -> Result<Vec<u8>, Box<dyn Error>> {
+		let mut holes = vec![0u8; self.header.hole_entry_count as usize * 8];
+		if !holes.is_empty() {
+			self.reader.seek(SeekFrom::Start(self.header.hole_offset as u64))?;
+			self.reader.read_exact(&mut holes)?;
+		}
+		Ok(holes)
+	}
+
+	// the bytes not covered by the header, any resource, the index or the hole index
+	pub fn read_gaps(&mut self) -> Result<Vec<Chunk>, Box<dyn Error>> {
+		let mut covered = vec![
+			(0, 96),
+			(self.header.index_offset as u64, self.header.index_entry_count as u64 * IndexEntry::size(&self.header) as u64),
+			(self.header.hole_offset as u64, self.header.hole_entry_count as u64 * 8)
+		];
+		covered.extend(self.all_entries().iter().map(|e| (e.resource_offset as u64, e.resource_size as u64)));
+		covered.sort();
+
+		let end = self.reader.seek(SeekFrom::End(0))?;
+		let mut gaps = Vec::new();
+		let mut position = 0;
+		for (offset, size) in covered.into_iter().chain([(end, 0)]) {
+			if offset.min(end) > position {
+				let mut gap = vec![0u8; (offset.min(end) - position) as usize];
+				self.reader.seek(SeekFrom::Start(position))?;
+				self.reader.read_exact(&mut gap)?;
+				gaps.push((position as u32, gap));
+			}
+			position = position.max(offset + size);
+		}
+		Ok(gaps)
+	}
+
+	pub fn decode_all(&mut self, title: &str, lenient: bool) -> Result<Dbpf, Box<dyn Error>> {
+		let mut layout = Layout {
+			ids: self.index_entries.iter().map(|e| e.id.clone()).collect(),
+			dir: None,
+			compressed: HashMap::new(),
+			entries: self.all_entries().into_iter().cloned().collect(),
+			index_offset: self.header.index_offset,
+			hole_offset: self.header.hole_offset,
+			holes: self.read_holes()?,
+			gaps: self.read_gaps()?
+		};
+
+		if let Some(dir_entry) = &self.dir_entry {
+			let dir = Resource {
+				id: dir_entry.id.clone(),
+				data: Resource::read_stored(&mut self.reader, dir_entry).map_err(|err| self.read_error(dir_entry, err))?
+			};
+			layout.dir = Some((self.dir_position, dir));
+		}
+
+		// reading is sequential, decompressing and decoding is spread over the worker threads
+		let mut stored = Vec::new();
+		for index_entry in &self.index_entries {
+			let stored_data = Resource::read_stored(&mut self.reader, index_entry).map_err(|err| self.read_error(index_entry, err))?;
+			stored.push((index_entry, stored_data));
+		}
+
+		let dir = self.dir.as_ref();
+		let path = self.path.as_deref();
+		let decoded = try_par_map(stored, |(index_entry, stored_data)| {
+			let (data, compressed_data) = match dir.and_then(|dir| dir.uncompressed_size(&index_entry.id)) {
+				Some(uncompressed_size) => match Resource::decompress(&stored_data, uncompressed_size) {
+					Ok(data) => (data, Some(stored_data)),
+					Err(err) => {
+						let err = read_error(path, index_entry, err);
+						if !lenient {
+							return Err(err);
+						}
+						// corrupt data is kept as it was stored, rather than handed to the decoders
+						let resource = Resource { id: index_entry.id.clone(), data: stored_data };
+						return Ok((DecodedResource::Other(resource), None, Some(err.to_string())));
+					}
+				},
+				None => (stored_data, None)
+			};
+			let resource = Resource {
+				id: index_entry.id.clone(),
+				data
+			};
+			let (decoded, warning) = match resource.decode(title) {
+				Ok(decoded) => (decoded, None),
+				Err(err) => {
+					let err = ClodError::locate(err, path, Some(index_entry.resource_offset));
+					if !lenient {
+						return Err(err);
+					}
+					(DecodedResource::Other(resource.clone()), Some(err.to_string()))
+				}
+			};
+			// keep the original bytes of compressed resources so they can be written back untouched
+			let compressed = compressed_data.map(|data| Resource { id: resource.id, data });
+			Ok((decoded, compressed, warning))
+		})?;
+
+		let mut resources = Vec::new();
+		let mut warnings = Vec::new();
+		for (decoded, compressed, warning) in decoded {
+			resources.push(decoded);
+			layout.compressed.extend(compressed.map(|resource| (resource.id.clone(), resource)));
+			warnings.extend(warning);
+		}
+
+		Ok(Dbpf {
+			header: self.header.clone(),
+			resources,
+			is_compressed: self.is_compressed(),
+			layout: Some(layout),
+			warnings
+		})
+	}
+}
+
+fn read_error(path: Option<&Path>, index_entry: &IndexEntry, err: Box<dyn Error>) -> Box<dyn Error> {
+	let err = ClodError::parse(index_entry.resource_offset as u64, format!("failed to read {}: {err}", index_entry.id));
+	ClodError::locate(err.into(), path, None)
+}
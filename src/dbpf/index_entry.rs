@@ -1,5 +1,5 @@
 use std::error::Error;
-use std::io::Cursor;
+use std::io::{ Cursor, Seek, Write };
 
 use binrw::{ BinRead, BinWrite };
 
@@ -23,18 +23,19 @@ impl IndexEntry {
 		}
 	}
 
-	pub fn read_all(cur: &mut Cursor<&[u8]>, header: &Header) -> Result<(Vec<IndexEntry>, bool), Box<dyn Error>> {
+	pub fn read_all(cur: &mut Cursor<&[u8]>, header: &Header) -> Result<Vec<IndexEntry>, Box<dyn Error>> {
 		let mut index_entries = Vec::new();
-		let mut has_dir = false;
 		for _ in 0..header.index_entry_count as usize {
 			let index_entry = Self::read(cur, header.index_minor_version)?;
-			if index_entry.id.type_id == TypeId::Dir {
-				has_dir = true;
-			} else if index_entry.id.type_id != TypeId::Unknown {
+			if index_entry.id.type_id != TypeId::Unknown {
 				index_entries.push(index_entry);
 			}
 		}
-		Ok((index_entries, has_dir))
+		Ok(index_entries)
+	}
+
+	pub fn size(header: &Header) -> usize {
+		if header.index_minor_version >= 2 { 24 } else { 20 }
 	}
 
 	pub fn read(cur: &mut Cursor<&[u8]>, version: u32) -> Result<Self, Box<dyn Error>> {
@@ -48,7 +49,7 @@ impl IndexEntry {
 		})
 	}
 
-	pub fn write(&self, writer: &mut Cursor<Vec<u8>>, use_tgir: bool) -> Result<(), Box<dyn Error>> {
+	pub fn write<W: Write + Seek>(&self, writer: &mut W, use_tgir: bool) -> Result<(), Box<dyn Error>> {
 		self.id.write(writer, use_tgir)?;
 		self.resource_offset.write_le(writer)?;
 		self.resource_size.write_le(writer)?;
@@ -0,0 +1,204 @@
+use std::collections::{ HashMap, HashSet };
+use std::error::Error;
+use std::io::{ Cursor, Seek, SeekFrom, Write };
+
+use crate::dbpf::{ Dbpf, Identifier };
+use crate::dbpf::header::Header;
+use crate::dbpf::index_entry::IndexEntry;
+use crate::dbpf::resource::Resource;
+use crate::dbpf::resource_types::dir::{ Dir, DirItem };
+
+// bytes at an offset from the start of the package
+pub type Chunk = (u32, Vec<u8>);
+
+// How a package was stored on disk, beyond what the decoded resources capture.
+// Used to write an untouched package back out byte-for-byte.
+#[derive(Clone, Default)]
+pub struct Layout {
+	pub ids: HashSet<Identifier>,
+	pub dir: Option<(usize, Resource)>,
+	pub compressed: HashMap<Identifier, Resource>,
+	// every index entry in its original order, including the DIR
+	pub entries: Vec<IndexEntry>,
+	pub index_offset: u32,
+	pub hole_offset: u32,
+	pub holes: Vec<u8>,
+	// bytes that belong to no resource, index or header, by offset
+	pub gaps: Vec<Chunk>
+}
+
+impl Layout {
+	pub fn write<W: Write + Seek>(&self, resources: Vec<Resource>, header: Header, writer: &mut W, compress: bool) -> Result<(), Box<dyn Error>> {
+		let mut dir_items = Vec::new();
+		let mut stored_resources = Vec::new();
+		for mut resource in resources {
+			let uncompressed_size = resource.data.len() as u32;
+			let is_compressed = match self.compressed.get(&resource.id) {
+				// reuse the original compressed bytes if the resource hasn't changed
+				Some(original) if Resource::decompress(&original.data, uncompressed_size).is_ok_and(|data| data == resource.data) => {
+					resource.data = original.data.clone();
+					true
+				}
+				Some(_) => resource.compress()?,
+				// resources that were stored uncompressed stay that way
+				None if self.ids.contains(&resource.id) => false,
+				None => compress && resource.compress()?
+			};
+			if is_compressed {
+				dir_items.push(DirItem { id: resource.id.clone(), uncompressed_size });
+			}
+			stored_resources.push(resource);
+		}
+
+		if !dir_items.is_empty() {
+			let use_tgir = header.index_minor_version >= 2;
+			let (position, dir) = match &self.dir {
+				Some((position, original)) if Self::same_items(&Dir::read(original, use_tgir)?.items, &dir_items) => {
+					(*position, original.clone())
+				}
+				dir => {
+					let new_dir = Dir::new(dir_items);
+					let position = dir.as_ref().map_or(0, |(position, _)| *position);
+					(position, Resource { id: new_dir.id.clone(), data: new_dir.to_bytes(use_tgir)? })
+				}
+			};
+			stored_resources.insert(position.min(stored_resources.len()), dir);
+		}
+
+		match self.chunks(&stored_resources, &header)? {
+			Some(chunks) => Self::write_chunks(chunks, writer),
+			// the resources no longer fit where they were, so they're packed and the holes are dropped
+			None => Dbpf::write_entries(&stored_resources, Dbpf::fresh_header(header), writer)
+		}
+	}
+
+	// everything in the package at its original offset, or None if the stored
+	// resources or header no longer match the original index
+	fn chunks(&self, resources: &[Resource], header: &Header) -> Result<Option<Vec<Chunk>>, Box<dyn Error>> {
+		let by_id = resources.iter().map(|resource| (&resource.id, resource)).collect::<HashMap<&Identifier, &Resource>>();
+		if by_id.len() != resources.len()
+			|| resources.len() != self.entries.len()
+			|| header.index_entry_count as usize != self.entries.len()
+			|| header.index_offset != self.index_offset
+			|| header.hole_offset != self.hole_offset
+			|| header.hole_entry_count as usize * 8 != self.holes.len() {
+			return Ok(None);
+		}
+
+		let mut header_bytes = Cursor::new(Vec::new());
+		header.write(&mut header_bytes)?;
+		let mut chunks = vec![(0, header_bytes.into_inner())];
+
+		let mut index = Cursor::new(Vec::new());
+		for entry in &self.entries {
+			match by_id.get(&entry.id) {
+				Some(resource) if resource.data.len() == entry.resource_size as usize => {
+					chunks.push((entry.resource_offset, resource.data.clone()));
+				}
+				_ => return Ok(None)
+			}
+			entry.write(&mut index, header.index_minor_version >= 2)?;
+		}
+		chunks.push((self.index_offset, index.into_inner()));
+		chunks.push((self.hole_offset, self.holes.clone()));
+		chunks.extend(self.gaps.iter().cloned());
+
+		chunks.retain(|(_, data)| !data.is_empty());
+		chunks.sort_by_key(|(offset, _)| *offset);
+		let overlapping = chunks.windows(2).any(|pair| pair[0].0 as usize + pair[0].1.len() > pair[1].0 as usize);
+		Ok((!overlapping).then_some(chunks))
+	}
+
+	fn write_chunks<W: Write + Seek>(chunks: Vec<Chunk>, writer: &mut W) -> Result<(), Box<dyn Error>> {
+		let start = writer.stream_position()?;
+		for (offset, data) in chunks {
+			writer.seek(SeekFrom::Start(start + offset as u64))?;
+			writer.write_all(&data)?;
+		}
+		writer.flush()?;
+		Ok(())
+	}
+
+	fn same_items(a: &[DirItem], b: &[DirItem]) -> bool {
+		let sizes = a.iter().map(|item| (&item.id, item.uncompressed_size)).collect::<HashMap<&Identifier, u32>>();
+		a.len() == b.len() && b.iter().all(|item| sizes.get(&item.id) == Some(&item.uncompressed_size))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::{ Cursor, Seek, SeekFrom, Write };
+
+	use crate::dbpf::{ Dbpf, PascalString };
+	use crate::dbpf::header::Header;
+	use crate::dbpf::index_entry::IndexEntry;
+	use crate::dbpf::resource::{ Resource, DecodedResource };
+	use crate::dbpf::resource_types::dir::{ Dir, DirItem };
+	use crate::test_support::fixtures::{ binx_resource, gzps_resource, idr_resource };
+
+	// a package with a DIR in the middle of the index, resources stored back to front,
+	// and a freed block between each of them that's listed in the hole index
+	fn scattered_package() -> Vec<u8> {
+		let mut gzps = gzps_resource();
+		let uncompressed_size = gzps.data.len() as u32;
+		assert!(gzps.compress().unwrap());
+		let dir = Dir::new(vec![DirItem { id: gzps.id.clone(), uncompressed_size }]);
+		let dir = Resource { id: dir.id.clone(), data: dir.to_bytes(true).unwrap() };
+		let resources = [idr_resource(), dir, gzps, binx_resource()];
+
+		let mut cur = Cursor::new(vec![0u8; 96]);
+		cur.seek(SeekFrom::End(0)).unwrap();
+		let mut entries = Vec::new();
+		let mut holes = Vec::new();
+		for resource in resources.iter().rev() {
+			entries.push(IndexEntry::from_resource(resource, cur.position() as u32));
+			cur.write_all(&resource.data).unwrap();
+			holes.extend((cur.position() as u32).to_le_bytes());
+			holes.extend(16u32.to_le_bytes());
+			cur.write_all(&[0xAB; 16]).unwrap();
+		}
+		entries.reverse();
+
+		let header = Header {
+			index_entry_count: entries.len() as u32,
+			index_offset: cur.position() as u32,
+			index_size: entries.len() as u32 * 24,
+			hole_entry_count: entries.len() as u32,
+			hole_offset: cur.position() as u32 + entries.len() as u32 * 24,
+			hole_size: holes.len() as u32,
+			..Header::default()
+		};
+		for entry in &entries {
+			entry.write(&mut cur, true).unwrap();
+		}
+		cur.write_all(&holes).unwrap();
+		cur.seek(SeekFrom::Start(0)).unwrap();
+		header.write(&mut cur).unwrap();
+		cur.into_inner()
+	}
+
+	#[test]
+	fn untouched_packages_are_written_back_byte_for_byte() {
+		let bytes = scattered_package();
+		let package = Dbpf::read(&bytes, "test").unwrap();
+		let mut written = Cursor::new(Vec::new());
+		package.write(&mut written, None, true).unwrap();
+		assert!(written.into_inner() == bytes);
+	}
+
+	#[test]
+	fn changed_packages_drop_the_hole_index() {
+		let mut package = Dbpf::read(&scattered_package(), "test").unwrap();
+		let binx = package.resources.iter_mut().find(|r| matches!(r, DecodedResource::Binx(_))).unwrap();
+		let DecodedResource::Binx(binx) = binx else { unreachable!() };
+		binx.creator_id = PascalString::new("a longer creator id than before");
+		let mut written = Cursor::new(Vec::new());
+		package.write(&mut written, None, true).unwrap();
+
+		let reread = Dbpf::read(&written.into_inner(), "test").unwrap();
+		assert_eq!((reread.header.hole_entry_count, reread.header.hole_offset, reread.header.hole_size), (0, 0, 0));
+		let ids = |package: &Dbpf| package.resources.iter().map(|r| r.get_id()).collect::<Vec<_>>();
+		assert_eq!(ids(&reread), ids(&package));
+		assert!(reread.layout.unwrap().compressed.contains_key(&gzps_resource().id));
+	}
+}
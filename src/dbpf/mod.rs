@@ -1,27 +1,41 @@
 use std::error::Error;
-use std::io::{ Cursor, Read, Write };
+use std::io::{ Cursor, Read, Seek, Write };
+use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{ Hash, Hasher };
 use std::convert::From;
-use std::fs::{ self, File };
 use std::path::Path;
+use std::str::FromStr;
 
 use binrw::{ BinRead, BinWrite };
 
+use crate::backup::write_atomic_with;
+use crate::dbpf::format::PackageFormat;
 use crate::dbpf::header::Header;
-use crate::dbpf::index_entry::IndexEntry;
+use crate::dbpf::index::DbpfIndex;
+use crate::dbpf::layout::Layout;
 use crate::dbpf::resource::{ Resource, DecodedResource };
-use crate::dbpf::resource_types::dir::{ Dir, DirItem };
+use crate::dbpf::writer::PackageWriter;
 
 pub mod header;
+pub mod index;
 pub mod index_entry;
+pub mod layout;
+pub mod format;
+pub mod compression;
 pub mod resource;
+pub mod resource_index;
 pub mod resource_types;
+pub mod writer;
 
 #[derive(Clone)]
 pub struct Dbpf {
 	pub header: Header,
 	pub resources: Vec<DecodedResource>,
-	pub is_compressed: bool
+	pub is_compressed: bool,
+	pub layout: Option<Layout>,
+	// resources that failed to decode in lenient mode, and were kept as Other
+	pub warnings: Vec<String>
 }
 
 impl Dbpf {
@@ -29,113 +43,107 @@ impl Dbpf {
 		Ok(Self {
 			header: Header::default(),
 			resources,
-			is_compressed: false
+			is_compressed: false,
+			layout: None,
+			warnings: Vec::new()
 		})
 	}
 
 	pub fn read(bytes: &[u8], title: &str) -> Result<Dbpf, Box<dyn Error>> {
-		let (resources, header, is_compressed) = Self::read_resources(bytes)?;
-		let decoded_resources = resources
-			.iter()
-			.map(|r| -> Result<DecodedResource, Box<dyn Error>> { r.decode(title) })
-			.collect::<Result<Vec<DecodedResource>, Box<dyn Error>>>()?;
-
-		Ok(Dbpf {
-			header,
-			resources: decoded_resources,
-			is_compressed
-		})
+		DbpfIndex::new(Cursor::new(bytes))?.decode_all(title, false)
 	}
 
 	pub fn read_resources(bytes: &[u8]) -> Result<(Vec<Resource>, Header, bool), Box<dyn Error>> {
-		let mut cur = Cursor::new(bytes);
-		let header = Header::read(&mut cur)?;
-		cur.set_position(header.index_offset as u64);
-		let (index_entries, is_compressed) = IndexEntry::read_all(&mut cur, &header)?;
-		let resources = Resource::read_all(&mut cur, &index_entries)?;
-		Ok((resources, header, is_compressed))
+		let mut index = DbpfIndex::new(Cursor::new(bytes))?;
+		let resources = index.read_all()?;
+		Ok((resources, index.header.clone(), index.is_compressed()))
 	}
 
 	pub fn read_from_file(path: &Path, title: &str) -> Result<Dbpf, Box<dyn Error>> {
-		let bytes = fs::read(path)?;
-		Dbpf::read(&bytes, title)
+		DbpfIndex::open(path)?.decode_all(title, false)
 	}
 
-	pub fn write(&self, writer: &mut Cursor<Vec<u8>>, compress: Option<bool>) -> Result<(), Box<dyn Error>> {
-		let resources = self.resources
-			.iter()
-			.map(|r| -> Result<Resource, Box<dyn Error>> { r.to_resource() })
-			.collect::<Result<Vec<Resource>, Box<dyn Error>>>()?;
-
-		let compress = compress.is_some_and(|c| c) || self.is_compressed;
-		Self::write_resources(resources, self.header.clone(), writer, compress)
+	// like read_from_file, but resources that fail to decode are kept undecoded and listed in warnings
+	pub fn read_from_file_lenient(path: &Path, title: &str) -> Result<Dbpf, Box<dyn Error>> {
+		DbpfIndex::open(path)?.decode_all(title, true)
 	}
 
-	pub fn write_resources(mut resources: Vec<Resource>, mut header: Header, writer: &mut Cursor<Vec<u8>>, compress: bool) -> Result<(), Box<dyn Error>> {
-		if compress {
-			let mut dir_items = Vec::new();
-			for resource in resources.iter_mut() {
-				let uncompressed_size = resource.data.len() as u32;
-				if resource.compress()? {
-					dir_items.push(DirItem{ id: resource.id.clone(), uncompressed_size })
-				}
+	pub fn write<W: Write + Seek>(&self, writer: &mut W, compress: Option<bool>, preserve: bool) -> Result<(), Box<dyn Error>> {
+		let compress = compress.is_some_and(|c| c) || self.is_compressed;
+		match &self.layout {
+			Some(layout) if preserve => {
+				let resources = self.resources
+					.iter()
+					.map(|r| -> Result<Resource, Box<dyn Error>> { r.to_resource() })
+					.collect::<Result<Vec<Resource>, Box<dyn Error>>>()?;
+				layout.write(resources, self.header.clone(), writer, compress)
 			}
-			if !dir_items.is_empty() {
-				let dir = Dir::new(dir_items);
-				resources.insert(0, Resource{ id: dir.id.clone(), data: dir.to_bytes()? });
+			_ => {
+				// resources are encoded one at a time as they're written
+				let mut package_writer = PackageWriter::new(writer, Self::fresh_header(self.header.clone()))?;
+				package_writer.add_all(self.resources.iter().map(|r| r.to_resource()), |_| compress)?;
+				package_writer.finish()?;
+				Ok(())
 			}
 		}
+	}
 
-		header.index_entry_count = resources.len() as u32;
-
-		let mut index_entries = Vec::new();
-		let mut offset = if header.minor_version >= 1 { 96 } else { 92 };
-		for resource in &resources {
-			let index_entry = IndexEntry::from_resource(resource, offset);
-			index_entries.push(index_entry);
-			offset += resource.data.len() as u32;
-		}
-
-		header.index_offset = offset;
-		let index_entry_size = if header.index_minor_version >= 2 { 24 } else { 20 };
-		header.index_size = (index_entries.len() * index_entry_size) as u32;
-
-		header.write(writer)?;
+	pub fn write_resources<W: Write + Seek>(resources: Vec<Resource>, header: Header, writer: &mut W, compress: bool) -> Result<(), Box<dyn Error>> {
+		Self::write_resources_with(resources.into_iter().map(Ok), header, writer, |_| compress, None)
+	}
 
-		for resource in &resources {
-			resource.write(writer)?;
-		}
+	// like write_resources, but decides per resource whether to try compressing it,
+	// and can convert the package to another format. resources are read from the iterator
+	// as they're written, so they don't all have to be in memory
+	pub fn write_resources_with<W: Write + Seek>(
+			resources: impl IntoIterator<Item = Result<Resource, Box<dyn Error>>>,
+			header: Header,
+			writer: &mut W,
+			compress: impl Fn(&Resource) -> bool + Sync,
+			format: Option<PackageFormat>
+		) -> Result<(), Box<dyn Error>> {
+
+		let mut package_writer = PackageWriter::new(writer, Self::fresh_header(header))?.with_format(format);
+		package_writer.add_all(resources, compress)?;
+		package_writer.finish()?;
+		Ok(())
+	}
 
-		writer.set_position(offset as u64);
-		for index_entry in &index_entries {
-			index_entry.write(writer, header.index_minor_version >= 2)?;
+	pub fn write_entries<W: Write + Seek>(resources: &[Resource], header: Header, writer: &mut W) -> Result<(), Box<dyn Error>> {
+		let mut package_writer = PackageWriter::new(writer, header)?;
+		for resource in resources {
+			package_writer.write_stored(resource)?;
 		}
-
+		package_writer.finish()?;
 		Ok(())
 	}
 
+	// a header for a package that's written from scratch, without the original hole index
+	fn fresh_header(mut header: Header) -> Header {
+		header.hole_entry_count = 0;
+		header.hole_offset = 0;
+		header.hole_size = 0;
+		header
+	}
+
 	pub fn clean_up_resources(&mut self) {
 		self.resources.sort_by_key(|res| res.get_id().to_string());
 		self.resources.dedup_by_key(|res| res.get_id().to_string());
 	}
 
-	pub fn write_to_file(&self, path: &Path) -> Result<(), Box<dyn Error>> {
-		let mut cur = Cursor::new(Vec::new());
-		self.write(&mut cur, None)?;
-		let mut new_file = File::create(path)?;
-		new_file.write_all(&cur.into_inner())?;
-		Ok(())
+	pub fn write_to_file(&self, path: &Path, preserve: bool) -> Result<(), Box<dyn Error>> {
+		write_atomic_with(path, |file| self.write(file, None, preserve))
 	}
 
 	pub fn write_package_file(resources: &[DecodedResource], path: &Path, compress: bool) -> Result<(), Box<dyn Error>> {
 		let mut new_dbpf = Dbpf::new(resources.to_vec())?;
 		new_dbpf.clean_up_resources();
 		new_dbpf.is_compressed = compress;
-		new_dbpf.write_to_file(path)
+		new_dbpf.write_to_file(path, false)
 	}
 }
 
-#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, Default)]
 pub enum TypeId {
 	#[default]
 	Unknown,
@@ -249,7 +257,52 @@ impl fmt::Display for TypeId {
 	}
 }
 
-#[derive(Clone, Default, PartialEq, Eq)]
+// type ids compare, hash and sort by their numeric value
+impl PartialEq for TypeId {
+	fn eq(&self, other: &Self) -> bool {
+		u32::from(*self) == u32::from(*other)
+	}
+}
+
+impl Eq for TypeId {}
+
+impl Hash for TypeId {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		u32::from(*self).hash(state);
+	}
+}
+
+impl PartialOrd for TypeId {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for TypeId {
+	fn cmp(&self, other: &Self) -> Ordering {
+		u32::from(*self).cmp(&u32::from(*other))
+	}
+}
+
+// accepts the names TypeId is displayed with (case-insensitive), or a hex id like 0x1C4A276C
+impl FromStr for TypeId {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if let Some(hex) = s.strip_prefix("0x").or(s.strip_prefix("0X")) {
+			return u32::from_str_radix(hex, 16).map(Self::from).map_err(|err| format!("Invalid type id \"{s}\": {err}"));
+		}
+		[Self::Dir, Self::Gmdc, Self::Gmnd, Self::Shpe, Self::Cres, Self::Mmat, Self::Txmt, Self::Txtr,
+			Self::Gzps, Self::Idr, Self::Binx, Self::Xtol, Self::Xhtn, Self::Ui, Self::Coll, Self::TextList,
+			Self::DataList, Self::BoneData, Self::Transform, Self::ShapeRef]
+			.into_iter()
+			.find(|type_id| type_id.to_string().eq_ignore_ascii_case(s))
+			.ok_or(format!("Unknown resource type \"{s}\""))
+	}
+}
+
+// sorts by type, group, resource and then instance id
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Identifier {
 	pub type_id: TypeId,
 	pub group_id: u32,
@@ -275,7 +328,7 @@ impl Identifier {
 		Ok(Self::new(type_id, group_id, resource_id, instance_id))
 	}
 
-	pub fn write(&self, writer: &mut Cursor<Vec<u8>>, use_tgir: bool) -> Result<(), Box<dyn Error>> {
+	pub fn write<W: Write + Seek>(&self, writer: &mut W, use_tgir: bool) -> Result<(), Box<dyn Error>> {
 		u32::from(self.type_id).write_le(writer)?;
 		self.group_id.write_le(writer)?;
 		self.instance_id.write_le(writer)?;
@@ -337,6 +390,12 @@ impl fmt::Display for SevenBitString {
 	}
 }
 
+impl fmt::Debug for SevenBitString {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{:?}", String::from_utf8_lossy(&self.0))
+	}
+}
+
 impl SevenBitString {
 	pub fn new(string: &str) -> Self {
 		Self(string.as_bytes().to_vec())
@@ -370,6 +429,12 @@ impl fmt::Display for PascalString {
 	}
 }
 
+impl fmt::Debug for PascalString {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{:?}", String::from_utf8_lossy(&self.0))
+	}
+}
+
 impl PascalString {
 	pub fn new(string: &str) -> Self {
 		Self(string.as_bytes().to_vec())
@@ -393,3 +458,4 @@ impl PascalString {
 			Ok(())
 	}
 }
+
@@ -1,10 +1,12 @@
 use std::error::Error;
-use std::io::{ Cursor, Read, Write };
+use std::io::{ Read, Seek, SeekFrom, Write };
 
 use refpack::{ CompressionOptions, easy_compress, easy_decompress, format };
 
 use crate::dbpf::{ Identifier, TypeId };
+use crate::error::ClodError;
 use crate::dbpf::index_entry::IndexEntry;
+use crate::dbpf::resource_types::dir::Dir;
 
 use crate::dbpf::resource_types::gmdc::Gmdc;
 use crate::dbpf::resource_types::gmnd::Gmnd;
@@ -22,7 +24,7 @@ use crate::dbpf::resource_types::text_list::TextList;
 
 use crate::dbpf::resource_types::xtol::Xtol;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub enum DecodedResource {
 	Gmdc(Gmdc),
 	Gmnd(Gmnd),
@@ -41,21 +43,22 @@ pub enum DecodedResource {
 
 impl DecodedResource {
 	pub fn new(resource: &Resource, title: &str) -> Result<Self, Box<dyn Error>> {
-		match resource.id.type_id {
-			TypeId::Gmdc => Ok(DecodedResource::Gmdc(Gmdc::new(resource)?)),
-			TypeId::Gmnd => Ok(DecodedResource::Gmnd(Gmnd::new(resource)?)),
-			TypeId::Shpe => Ok(DecodedResource::Shpe(Shpe::new(resource)?)),
-			TypeId::Cres => Ok(DecodedResource::Cres(Cres::new(resource)?)),
-			TypeId::Mmat => Ok(DecodedResource::Mmat(Mmat::new(resource)?)),
-			TypeId::Txmt => Ok(DecodedResource::Txmt(Txmt::new(resource)?)),
-			TypeId::Txtr => Ok(DecodedResource::Txtr(Txtr::new(resource)?)),
-			TypeId::Gzps => Ok(DecodedResource::Gzps(Gzps::new(resource, title)?)),
-			TypeId::Idr => Ok(DecodedResource::Idr(Idr::new(resource)?)),
-			TypeId::Binx => Ok(DecodedResource::Binx(Binx::new(resource)?)),
-			TypeId::Xtol => Ok(DecodedResource::Xtol(Xtol::new(resource)?)),
-			TypeId::TextList => Ok(DecodedResource::TextList(TextList::new(resource)?)),
+		let decoded = match resource.id.type_id {
+			TypeId::Gmdc => Gmdc::new(resource).map(DecodedResource::Gmdc),
+			TypeId::Gmnd => Gmnd::new(resource).map(DecodedResource::Gmnd),
+			TypeId::Shpe => Shpe::new(resource).map(DecodedResource::Shpe),
+			TypeId::Cres => Cres::new(resource).map(DecodedResource::Cres),
+			TypeId::Mmat => Mmat::new(resource).map(DecodedResource::Mmat),
+			TypeId::Txmt => Txmt::new(resource).map(DecodedResource::Txmt),
+			TypeId::Txtr => Txtr::new(resource).map(DecodedResource::Txtr),
+			TypeId::Gzps => Gzps::new(resource, title).map(DecodedResource::Gzps),
+			TypeId::Idr => Idr::new(resource).map(DecodedResource::Idr),
+			TypeId::Binx => Binx::new(resource).map(DecodedResource::Binx),
+			TypeId::Xtol => Xtol::new(resource).map(DecodedResource::Xtol),
+			TypeId::TextList => TextList::new(resource).map(DecodedResource::TextList),
 			_ => Ok(DecodedResource::Other(resource.clone()))
-		}
+		};
+		decoded.map_err(|err| ClodError::decode(&resource.id, err).into())
 	}
 
 	pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
@@ -94,6 +97,16 @@ impl DecodedResource {
 		}
 	}
 
+	// the scenegraph file name other resources refer to this one by, for the types that have one
+	pub fn sg_name(&self) -> Option<String> {
+		match self {
+			Self::Shpe(shpe) => Some(shpe.block.file_name.to_string()),
+			Self::Txmt(txmt) => Some(txmt.block.material_definition.to_string()),
+			Self::Txtr(txtr) => Some(txtr.name.to_string()),
+			_ => None
+		}
+	}
+
 	pub fn to_resource(&self) -> Result<Resource, Box<dyn Error>> {
 		Ok(Resource {
 			id: self.get_id(),
@@ -102,37 +115,44 @@ impl DecodedResource {
 	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Resource {
 	pub id: Identifier,
 	pub data: Vec<u8>
 }
 
 impl Resource {
-	pub fn read_all(cur: &mut Cursor<&[u8]>, index_entries: &[IndexEntry]) -> Result<Vec<Resource>, Box<dyn Error>> {
-		let mut resources = Vec::new();
-		for index_entry in index_entries {
-			resources.push(Self::read(cur, index_entry)?);
-		}
-		Ok(resources)
-	}
-
-	pub fn read(cur: &mut Cursor<&[u8]>, index_entry: &IndexEntry) -> Result<Self, Box<dyn Error>> {
-		cur.set_position(index_entry.resource_offset as u64);
-		let mut raw_data = vec![0u8; index_entry.resource_size as usize];
-		cur.read_exact(&mut raw_data)?;
-
-		let data = easy_decompress::<format::Maxis>(&raw_data)
-			.or_else(|_| easy_decompress::<format::SimEA>(&raw_data))
-			// .or_else(|_| easy_decompress::<format::Reference>(&raw_data))
-			.unwrap_or(raw_data.clone());
-
+	// only resources listed in the DIR are decompressed
+	pub fn read<R: Read + Seek>(reader: &mut R, index_entry: &IndexEntry, dir: Option<&Dir>) -> Result<Self, Box<dyn Error>> {
+		let stored_data = Self::read_stored(reader, index_entry)?;
+		let data = match dir.and_then(|dir| dir.uncompressed_size(&index_entry.id)) {
+			Some(uncompressed_size) => Self::decompress(&stored_data, uncompressed_size)?,
+			None => stored_data
+		};
 		Ok(Self {
 			id: index_entry.id.clone(),
 			data
 		})
 	}
 
+	pub fn read_stored<R: Read + Seek>(reader: &mut R, index_entry: &IndexEntry) -> Result<Vec<u8>, Box<dyn Error>> {
+		reader.seek(SeekFrom::Start(index_entry.resource_offset as u64))?;
+		let mut raw_data = vec![0u8; index_entry.resource_size as usize];
+		reader.read_exact(&mut raw_data)?;
+		Ok(raw_data)
+	}
+
+	pub fn decompress(stored_data: &[u8], uncompressed_size: u32) -> Result<Vec<u8>, Box<dyn Error>> {
+		let data = easy_decompress::<format::Maxis>(stored_data)
+			.or_else(|_| easy_decompress::<format::SimEA>(stored_data))
+			// .or_else(|_| easy_decompress::<format::Reference>(stored_data))
+			.map_err(|err| format!("corrupt compressed data: {err}"))?;
+		if data.len() != uncompressed_size as usize {
+			return Err(format!("decompressed to {} bytes, but the DIR lists {uncompressed_size}", data.len()).into());
+		}
+		Ok(data)
+	}
+
 	pub fn decode(&self, title: &str) -> Result<DecodedResource, Box<dyn Error>> {
 		DecodedResource::new(self, title)
 	}
@@ -147,7 +167,7 @@ impl Resource {
 		}
 	}
 
-	pub fn write(&self, writer: &mut Cursor<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+	pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
 		writer.write_all(&self.data)?;
 		Ok(())
 	}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use crate::dbpf::{ Identifier, TypeId };
+use crate::dbpf::resource::DecodedResource;
+
+// Lookup tables over a list of decoded resources, for resolving references
+// without scanning the whole list each time.
+pub struct ResourceIndex<'a> {
+	resources: &'a [DecodedResource],
+	by_id: HashMap<Identifier, usize>,
+	by_type: HashMap<TypeId, Vec<usize>>,
+	// lowercase scenegraph name -> resources with that name
+	by_name: HashMap<String, Vec<usize>>
+}
+
+impl<'a> ResourceIndex<'a> {
+	pub fn new(resources: &'a [DecodedResource]) -> Self {
+		let mut by_id = HashMap::new();
+		let mut by_type: HashMap<TypeId, Vec<usize>> = HashMap::new();
+		let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+		for (i, resource) in resources.iter().enumerate() {
+			let id = resource.get_id();
+			by_type.entry(id.type_id).or_default().push(i);
+			if let Some(name) = resource.sg_name() {
+				let name = name.to_lowercase();
+				// names without a group prefix can also be referred to with the resource's own group
+				if !name.starts_with("##0x") {
+					by_name.entry(format!("##0x{:08x}!{name}", id.group_id)).or_default().push(i);
+				}
+				by_name.entry(name).or_default().push(i);
+			}
+			// the first resource with an id wins, same as a linear search
+			by_id.entry(id).or_insert(i);
+		}
+		Self {
+			resources,
+			by_id,
+			by_type,
+			by_name
+		}
+	}
+
+	pub fn resources(&self) -> &'a [DecodedResource] {
+		self.resources
+	}
+
+	pub fn get(&self, id: &Identifier) -> Option<&'a DecodedResource> {
+		self.by_id.get(id).map(|i| &self.resources[*i])
+	}
+
+	pub fn contains(&self, id: &Identifier) -> bool {
+		self.by_id.contains_key(id)
+	}
+
+	// resources of a type, in their original order
+	pub fn of_type(&self, type_id: TypeId) -> impl Iterator<Item = &'a DecodedResource> + '_ {
+		self.indices(self.by_type.get(&type_id))
+	}
+
+	// resources with a scenegraph name, compared case-insensitively, in their original order
+	pub fn by_name(&self, name: &str) -> Vec<&'a DecodedResource> {
+		self.by_names([name])
+	}
+
+	// resources with any of the names, each one once, in their original order
+	pub fn by_names<S: AsRef<str>>(&self, names: impl IntoIterator<Item = S>) -> Vec<&'a DecodedResource> {
+		let mut indices = names.into_iter()
+			.filter_map(|name| self.by_name.get(&name.as_ref().to_lowercase()))
+			.flatten()
+			.copied()
+			.collect::<Vec<usize>>();
+		indices.sort();
+		indices.dedup();
+		indices.into_iter().map(|i| &self.resources[i]).collect()
+	}
+
+	fn indices<'b>(&'b self, indices: Option<&'b Vec<usize>>) -> impl Iterator<Item = &'a DecodedResource> + 'b {
+		let resources = self.resources;
+		indices.into_iter().flatten().map(move |i| &resources[*i])
+	}
+}
@@ -6,7 +6,7 @@ use crate::dbpf::resource::Resource;
 use crate::dbpf::resource_types::cpf::{ Cpf, CpfType, PropertyValue };
 use crate::dbpf::resource_types::gzps::Gzps;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Binx {
 	pub id: Identifier,
 	pub icon_idx: u32,
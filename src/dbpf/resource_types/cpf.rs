@@ -9,8 +9,9 @@ use binrw::{ BinRead, BinWrite };
 use xmltree::{ XMLNode, Element, ParserConfig };
 
 use crate::dbpf::PascalString;
+use crate::error::ClodError;
 
-#[derive(Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default)]
 pub enum CpfType {
 	#[default]
 	Normal,
@@ -30,7 +31,8 @@ impl Cpf {
 		let mut cur = Cursor::new(data);
 		let cpf_id = u32::read_le(&mut cur)?;
 		if cpf_id == 0xCBE750E0 {
-			Self::read_normal(&mut cur)
+			let cpf = Self::read_normal(&mut cur);
+			cpf.map_err(|err| ClodError::at(cur.position(), err))
 		} else {
 			cur.set_position(0);
 			Self::read_xml(&mut cur)
@@ -118,12 +120,12 @@ impl Cpf {
 				let raw_value = el.get_text().unwrap_or("".into());
 				let prop_value = match prop_type {
 					DataType::Bool => PropertyValue::Bool(
-						raw_value == "True"
+						raw_value.eq_ignore_ascii_case("true")
 					),
 					DataType::Uint => PropertyValue::Uint(
 						match raw_value.strip_prefix("0x") {
 							Some(hex) => u32::from_str_radix(hex, 16)?,
-							None => i32::from_str(&raw_value)? as u32
+							None => u32::from_str(&raw_value).or_else(|_| i32::from_str(&raw_value).map(|v| v as u32))?
 						}
 					),
 					DataType::Int => PropertyValue::Int(
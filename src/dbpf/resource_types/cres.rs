@@ -9,40 +9,66 @@ use crate::dbpf::resource_types::rcol::{ Rcol, RcolBlock };
 use crate::dbpf::resource_types::nodes::sg_resource::SGResource;
 use crate::dbpf::resource_types::nodes::composition_tree::CompositionTreeNode;
 use crate::dbpf::resource_types::nodes::object_graph::ObjectGraphNode;
+use crate::dbpf::resource_types::nodes::transform::TransformNode;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Cres {
 	pub id: Identifier,
-	pub data: Vec<u8>
+	// SHPEs used by the shape reference nodes
+	pub links: Vec<Identifier>,
+	// the resource node first, then the nodes of the scenegraph it indexes into.
+	// these stop at the first block that can't be decoded (like a light or viewer reference node)
+	pub blocks: Vec<RcolBlock>,
+	// the resource as read, kept when some of its blocks can't be decoded so it's written back unchanged
+	pub data: Option<Vec<u8>>
 }
 
 impl Cres {
 	pub fn new(resource: &Resource) -> Result<Self, Box<dyn Error>> {
 		let rcol = Rcol::read(&resource.data)?;
-		if !rcol.blocks.is_empty() {
-			if let RcolBlock::Cres(_cres_block) = &rcol.blocks[0] {
-				let _shpe_ref = (*rcol.links.first().ok_or("SHPE reference not found.")?).clone();
-				return Ok(Self {
-					id: resource.id.clone(),
-					data: resource.data.clone()
-				});
-			}
+		if let Some(RcolBlock::Cres(_)) = rcol.blocks.first() {
+			let is_complete = !rcol.blocks.iter().any(|block| matches!(block, RcolBlock::Unknown(_)));
+			return Ok(Self {
+				id: resource.id.clone(),
+				links: rcol.links,
+				blocks: rcol.blocks,
+				data: (!is_complete).then(|| resource.data.clone())
+			});
 		}
 		Err("Invalid CRES resource.".into())
 	}
 
 	pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
-		Ok(self.data.clone())
+		if let Some(data) = &self.data {
+			return Ok(data.clone());
+		}
+		let rcol = Rcol {
+			links: self.links.clone(),
+			blocks: self.blocks.clone()
+		};
+		let mut cur = Cursor::new(Vec::new());
+		rcol.write(&mut cur)?;
+		Ok(cur.into_inner())
+	}
+
+	// every transform node, including the ones of the shape reference nodes
+	pub fn transforms(&self) -> impl Iterator<Item = &TransformNode> {
+		self.blocks.iter().filter_map(|block| match block {
+			RcolBlock::Transform(transform) => Some(transform),
+			RcolBlock::ShapeRef(shape_ref) => Some(&shape_ref.transform),
+			_ => None
+		})
 	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct CresBlock {
-	version: u32,
-	file_name: SevenBitString,
-	ogn: ObjectGraphNode,
-	chains: Vec<(bool, bool, u32)>,
-	purpose: u32
+	pub version: u32,
+	pub file_name: SevenBitString,
+	pub ogn: ObjectGraphNode,
+	// (is_enabled, is_dependent, block index) of the root nodes
+	pub chains: Vec<(bool, bool, u32)>,
+	pub purpose: u32
 }
 
 impl CresBlock {
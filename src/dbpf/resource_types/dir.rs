@@ -1,17 +1,21 @@
+use std::collections::HashMap;
 use std::error::Error;
 use std::io::Cursor;
 
-use binrw::BinWrite;
+use binrw::{ BinRead, BinWrite };
 
 use crate::dbpf::Identifier;
+use crate::dbpf::resource::Resource;
 
 #[derive(Clone)]
 pub struct Dir {
 	pub id: Identifier,
-	pub items: Vec<DirItem>
+	pub items: Vec<DirItem>,
+	// uncompressed size by id, so lookups don't scan the items
+	sizes: HashMap<Identifier, u32>
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct DirItem {
 	pub id: Identifier,
 	pub uncompressed_size: u32
@@ -19,17 +23,42 @@ pub struct DirItem {
 
 impl Dir {
 	pub fn new(items: Vec<DirItem>) -> Self {
+		Self::with_id(Identifier::new(0xE86B1EEF, 0xE86B1EEF, 0, 0x286B1F03), items)
+	}
+
+	fn with_id(id: Identifier, items: Vec<DirItem>) -> Self {
+		let mut sizes = HashMap::new();
+		for item in &items {
+			sizes.entry(item.id.clone()).or_insert(item.uncompressed_size);
+		}
 		Self {
-			id: Identifier::new(0xE86B1EEF, 0xE86B1EEF, 0, 0x286B1F03),
-			items
+			id,
+			items,
+			sizes
 		}
 	}
 
-	pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+	pub fn read(resource: &Resource, use_tgir: bool) -> Result<Self, Box<dyn Error>> {
+		let mut cur = Cursor::new(&resource.data[..]);
+		let item_size = if use_tgir { 20 } else { 16 };
+		let mut items = Vec::new();
+		for _ in 0..resource.data.len() / item_size {
+			let id = Identifier::read(&mut cur, use_tgir)?;
+			let uncompressed_size = u32::read_le(&mut cur)?;
+			items.push(DirItem { id, uncompressed_size });
+		}
+		Ok(Self::with_id(resource.id.clone(), items))
+	}
+
+	pub fn uncompressed_size(&self, id: &Identifier) -> Option<u32> {
+		self.sizes.get(id).copied()
+	}
+
+	pub fn to_bytes(&self, use_tgir: bool) -> Result<Vec<u8>, Box<dyn Error>> {
 		let mut cur = Cursor::new(Vec::new());
 
 		for item in &self.items {
-			item.id.write(&mut cur, true)?;
+			item.id.write(&mut cur, use_tgir)?;
 			item.uncompressed_size.write_le(&mut cur)?;
 		}
 
@@ -1,27 +1,28 @@
 use std::error::Error;
-use std::io::{ Cursor, Read };
+use std::fmt;
+use std::io::Cursor;
 
 use binrw::{ BinRead, BinWrite };
 
-use crate::dbpf::{ Identifier, TypeId, PascalString };
+use crate::dbpf::{ Identifier, TypeId, SevenBitString, PascalString };
 use crate::dbpf::resource::Resource;
 use crate::dbpf::resource_types::rcol::{ Rcol, RcolBlock };
+use crate::dbpf::resource_types::nodes::sg_resource::SGResource;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Gmdc {
 	pub id: Identifier,
-	pub data: Vec<u8>
+	pub block: GmdcBlock
 }
 
 impl Gmdc {
 	pub fn new(resource: &Resource) -> Result<Self, Box<dyn Error>> {
 		let rcol = Rcol::read(&resource.data)?;
 		if rcol.blocks.len() == 1 {
-			if let RcolBlock::Gmdc(_gmdc_block) = &rcol.blocks[0] {
+			if let RcolBlock::Gmdc(gmdc_block) = &rcol.blocks[0] {
 				return Ok(Self {
 					id: resource.id.clone(),
-					// block: gmdc_block.clone(),
-					data: resource.data.clone()
+					block: gmdc_block.clone()
 				});
 			}
 		}
@@ -29,14 +30,26 @@ impl Gmdc {
 	}
 
 	pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
-		Ok(self.data.clone())
+		let rcol = Rcol {
+			links: Vec::new(),
+			blocks: vec![RcolBlock::Gmdc(self.block.clone())]
+		};
+		let mut cur = Cursor::new(Vec::new());
+		rcol.write(&mut cur)?;
+		Ok(cur.into_inner())
 	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct GmdcBlock {
-	version: u32,
-	remaining_data: Vec<u8>
+	pub version: u32,
+	pub file_name: SevenBitString,
+	pub elements: Vec<GmdcElement>,
+	pub links: Vec<GmdcLink>,
+	pub groups: Vec<GmdcGroup>,
+	pub model: GmdcModel,
+	// bounding mesh of the vertices assigned to each joint
+	pub joints: Vec<GmdcMesh>
 }
 
 impl GmdcBlock {
@@ -45,12 +58,42 @@ impl GmdcBlock {
 		let _block_id = u32::read_le(cur)?;
 		let version = u32::read_le(cur)?;
 
-		let mut remaining_data: Vec<u8> = Vec::new();
-		cur.read_to_end(&mut remaining_data)?;
+		let file_name = SGResource::read(cur)?.file_name;
+
+		let mut elements = Vec::new();
+		let num_elements = u32::read_le(cur)?;
+		for _ in 0..num_elements {
+			elements.push(GmdcElement::read(cur, version)?);
+		}
+
+		let mut links = Vec::new();
+		let num_links = u32::read_le(cur)?;
+		for _ in 0..num_links {
+			links.push(GmdcLink::read(cur, version)?);
+		}
+
+		let mut groups = Vec::new();
+		let num_groups = u32::read_le(cur)?;
+		for _ in 0..num_groups {
+			groups.push(GmdcGroup::read(cur, version)?);
+		}
+
+		let model = GmdcModel::read(cur, version)?;
+
+		let mut joints = Vec::new();
+		let num_joints = u32::read_le(cur)?;
+		for _ in 0..num_joints {
+			joints.push(GmdcMesh::read(cur, version)?);
+		}
 
 		Ok(Self {
 			version,
-			remaining_data
+			file_name,
+			elements,
+			links,
+			groups,
+			model,
+			joints
 		})
 	}
 
@@ -59,8 +102,478 @@ impl GmdcBlock {
 		u32::from(TypeId::Gmdc).write_le(writer)?;
 		self.version.write_le(writer)?;
 
-		self.remaining_data.write(writer)?;
+		(SGResource { file_name: self.file_name.clone() }).write(writer)?;
+
+		(self.elements.len() as u32).write_le(writer)?;
+		for element in &self.elements {
+			element.write(writer, self.version)?;
+		}
+
+		(self.links.len() as u32).write_le(writer)?;
+		for link in &self.links {
+			link.write(writer, self.version)?;
+		}
+
+		(self.groups.len() as u32).write_le(writer)?;
+		for group in &self.groups {
+			group.write(writer, self.version)?;
+		}
+
+		self.model.write(writer, self.version)?;
+
+		(self.joints.len() as u32).write_le(writer)?;
+		for joint in &self.joints {
+			joint.write(writer, self.version)?;
+		}
+
+		Ok(())
+	}
+}
+
+// version 4 stores indices as u16, earlier versions as u32
+fn read_indices(cur: &mut Cursor<&[u8]>, version: u32, count: u32) -> Result<Vec<u32>, Box<dyn Error>> {
+	let mut indices = Vec::new();
+	for _ in 0..count {
+		indices.push(if version == 4 { u16::read_le(cur)? as u32 } else { u32::read_le(cur)? });
+	}
+	Ok(indices)
+}
+
+fn write_indices(writer: &mut Cursor<Vec<u8>>, version: u32, indices: &[u32]) -> Result<(), Box<dyn Error>> {
+	for index in indices {
+		if version == 4 {
+			let index = u16::try_from(*index).map_err(|_| format!("index {index} is too large for a version 4 GMDC"))?;
+			index.write_le(writer)?;
+		} else {
+			index.write_le(writer)?;
+		}
+	}
+	Ok(())
+}
+
+fn read_index_list(cur: &mut Cursor<&[u8]>, version: u32) -> Result<Vec<u32>, Box<dyn Error>> {
+	let count = u32::read_le(cur)?;
+	read_indices(cur, version, count)
+}
+
+fn write_index_list(writer: &mut Cursor<Vec<u8>>, version: u32, indices: &[u32]) -> Result<(), Box<dyn Error>> {
+	(indices.len() as u32).write_le(writer)?;
+	write_indices(writer, version, indices)
+}
+
+fn read_vector(cur: &mut Cursor<&[u8]>) -> Result<[f32; 3], Box<dyn Error>> {
+	Ok([f32::read_le(cur)?, f32::read_le(cur)?, f32::read_le(cur)?])
+}
+
+fn write_floats(writer: &mut Cursor<Vec<u8>>, values: &[f32]) -> Result<(), Box<dyn Error>> {
+	for value in values {
+		value.write_le(writer)?;
+	}
+	Ok(())
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ElementIdentity {
+	BlendIndices,
+	BlendWeights,
+	TargetIndices,
+	NormalMorphDeltas,
+	Colour,
+	ColourDeltas,
+	Normals,
+	Vertices,
+	UvCoordinates,
+	UvCoordinateDeltas,
+	Binormals,
+	BoneWeights,
+	BoneAssignments,
+	BumpMapNormals,
+	BumpMapNormalDeltas,
+	MorphVertexDeltas,
+	MorphVertexMap,
+	VertexId,
+	RegionMask,
+	Other(u32)
+}
+
+impl From<ElementIdentity> for u32 {
+	fn from(identity: ElementIdentity) -> u32 {
+		match identity {
+			ElementIdentity::BlendIndices => 0x1C4AFC56,
+			ElementIdentity::BlendWeights => 0x5C4AFC5C,
+			ElementIdentity::TargetIndices => 0x7C4DEE82,
+			ElementIdentity::NormalMorphDeltas => 0xCB6F3A6A,
+			ElementIdentity::Colour => 0xCB7206A1,
+			ElementIdentity::ColourDeltas => 0xEB720693,
+			ElementIdentity::Normals => 0x3B83078B,
+			ElementIdentity::Vertices => 0x5B830781,
+			ElementIdentity::UvCoordinates => 0xBB8307AB,
+			ElementIdentity::UvCoordinateDeltas => 0xDB830795,
+			ElementIdentity::Binormals => 0x9BB38AFB,
+			ElementIdentity::BoneWeights => 0x3BD70105,
+			ElementIdentity::BoneAssignments => 0xFBD70111,
+			ElementIdentity::BumpMapNormals => 0x89D92BA0,
+			ElementIdentity::BumpMapNormalDeltas => 0x69D92B93,
+			ElementIdentity::MorphVertexDeltas => 0x5CF2CFE1,
+			ElementIdentity::MorphVertexMap => 0xDCF2CFDC,
+			ElementIdentity::VertexId => 0x114113C3,
+			ElementIdentity::RegionMask => 0x114113CD,
+			ElementIdentity::Other(inner) => inner
+		}
+	}
+}
+
+impl From<u32> for ElementIdentity {
+	fn from(value: u32) -> Self {
+		match value {
+			0x1C4AFC56 => Self::BlendIndices,
+			0x5C4AFC5C => Self::BlendWeights,
+			0x7C4DEE82 => Self::TargetIndices,
+			0xCB6F3A6A => Self::NormalMorphDeltas,
+			0xCB7206A1 => Self::Colour,
+			0xEB720693 => Self::ColourDeltas,
+			0x3B83078B => Self::Normals,
+			0x5B830781 => Self::Vertices,
+			0xBB8307AB => Self::UvCoordinates,
+			0xDB830795 => Self::UvCoordinateDeltas,
+			0x9BB38AFB => Self::Binormals,
+			0x3BD70105 => Self::BoneWeights,
+			0xFBD70111 => Self::BoneAssignments,
+			0x89D92BA0 => Self::BumpMapNormals,
+			0x69D92B93 => Self::BumpMapNormalDeltas,
+			0x5CF2CFE1 => Self::MorphVertexDeltas,
+			0xDCF2CFDC => Self::MorphVertexMap,
+			0x114113C3 => Self::VertexId,
+			0x114113CD => Self::RegionMask,
+			inner => Self::Other(inner)
+		}
+	}
+}
+
+impl fmt::Display for ElementIdentity {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::BlendIndices => write!(f, "Blend Indices"),
+			Self::BlendWeights => write!(f, "Blend Weights"),
+			Self::TargetIndices => write!(f, "Target Indices"),
+			Self::NormalMorphDeltas => write!(f, "Normal Morph Deltas"),
+			Self::Colour => write!(f, "Colour"),
+			Self::ColourDeltas => write!(f, "Colour Deltas"),
+			Self::Normals => write!(f, "Normals"),
+			Self::Vertices => write!(f, "Vertices"),
+			Self::UvCoordinates => write!(f, "UV Coordinates"),
+			Self::UvCoordinateDeltas => write!(f, "UV Coordinate Deltas"),
+			Self::Binormals => write!(f, "Binormals"),
+			Self::BoneWeights => write!(f, "Bone Weights"),
+			Self::BoneAssignments => write!(f, "Bone Assignments"),
+			Self::BumpMapNormals => write!(f, "Bump Map Normals"),
+			Self::BumpMapNormalDeltas => write!(f, "Bump Map Normal Deltas"),
+			Self::MorphVertexDeltas => write!(f, "Morph Vertex Deltas"),
+			Self::MorphVertexMap => write!(f, "Morph Vertex Map"),
+			Self::VertexId => write!(f, "Vertex Id"),
+			Self::RegionMask => write!(f, "Region Mask"),
+			Self::Other(inner) => write!(f, "0x{inner:08X}")
+		}
+	}
+}
+
+// the values of an element, in the block format they're stored in
+#[derive(Debug, Clone)]
+pub enum ElementData {
+	Float1(Vec<f32>),
+	Float2(Vec<[f32; 2]>),
+	Float3(Vec<[f32; 3]>),
+	Dword(Vec<u32>)
+}
 
+impl ElementData {
+	fn read(cur: &mut Cursor<&[u8]>, block_format: u32) -> Result<Self, Box<dyn Error>> {
+		let block_size = u32::read_le(cur)?;
+		let value_size = match block_format {
+			0 | 4 => 4,
+			1 => 8,
+			2 => 12,
+			_ => return Err(format!("Unknown GMDC block format {block_format}.").into())
+		};
+		if block_size % value_size != 0 {
+			return Err(format!("GMDC block of {block_size} bytes does not fit block format {block_format}.").into());
+		}
+		let count = block_size / value_size;
+
+		Ok(match block_format {
+			0 => Self::Float1((0..count).map(|_| f32::read_le(cur)).collect::<Result<_, _>>()?),
+			1 => Self::Float2((0..count).map(|_| Ok([f32::read_le(cur)?, f32::read_le(cur)?])).collect::<Result<_, Box<dyn Error>>>()?),
+			2 => Self::Float3((0..count).map(|_| read_vector(cur)).collect::<Result<_, _>>()?),
+			_ => Self::Dword((0..count).map(|_| u32::read_le(cur)).collect::<Result<_, _>>()?)
+		})
+	}
+
+	fn write(&self, writer: &mut Cursor<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+		match self {
+			Self::Float1(values) => {
+				(values.len() as u32 * 4).write_le(writer)?;
+				write_floats(writer, values)?;
+			}
+			Self::Float2(values) => {
+				(values.len() as u32 * 8).write_le(writer)?;
+				write_floats(writer, values.as_flattened())?;
+			}
+			Self::Float3(values) => {
+				(values.len() as u32 * 12).write_le(writer)?;
+				write_floats(writer, values.as_flattened())?;
+			}
+			Self::Dword(values) => {
+				(values.len() as u32 * 4).write_le(writer)?;
+				for value in values {
+					value.write_le(writer)?;
+				}
+			}
+		}
+		Ok(())
+	}
+
+	pub fn block_format(&self) -> u32 {
+		match self {
+			Self::Float1(_) => 0,
+			Self::Float2(_) => 1,
+			Self::Float3(_) => 2,
+			Self::Dword(_) => 4
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		match self {
+			Self::Float1(values) => values.len(),
+			Self::Float2(values) => values.len(),
+			Self::Float3(values) => values.len(),
+			Self::Dword(values) => values.len()
+		}
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
+// one section of per-vertex values (positions, normals, uvs, bone weights, morph deltas...)
+#[derive(Debug, Clone)]
+pub struct GmdcElement {
+	pub number: u32,
+	pub identity: ElementIdentity,
+	pub repetition: u32,
+	pub set_format: u32,
+	pub data: ElementData,
+	pub references: Vec<u32>
+}
+
+impl GmdcElement {
+	pub fn read(cur: &mut Cursor<&[u8]>, version: u32) -> Result<Self, Box<dyn Error>> {
+		let number = u32::read_le(cur)?;
+		let identity = ElementIdentity::from(u32::read_le(cur)?);
+		let repetition = u32::read_le(cur)?;
+		let block_format = u32::read_le(cur)?;
+		let set_format = u32::read_le(cur)?;
+		let data = ElementData::read(cur, block_format)?;
+		let references = read_index_list(cur, version)?;
+
+		Ok(Self {
+			number,
+			identity,
+			repetition,
+			set_format,
+			data,
+			references
+		})
+	}
+
+	pub fn write(&self, writer: &mut Cursor<Vec<u8>>, version: u32) -> Result<(), Box<dyn Error>> {
+		self.number.write_le(writer)?;
+		u32::from(self.identity).write_le(writer)?;
+		self.repetition.write_le(writer)?;
+		self.data.block_format().write_le(writer)?;
+		self.set_format.write_le(writer)?;
+		self.data.write(writer)?;
+		write_index_list(writer, version, &self.references)?;
+		Ok(())
+	}
+}
+
+// ties the elements used by a group together into one vertex list
+#[derive(Debug, Clone)]
+pub struct GmdcLink {
+	pub elements: Vec<u32>,
+	pub referenced_size: u32,
+	pub active_elements: u32,
+	// per-submodel remapping of vertex, normal and uv indices
+	pub aliases: [Vec<u32>; 3]
+}
+
+impl GmdcLink {
+	pub fn read(cur: &mut Cursor<&[u8]>, version: u32) -> Result<Self, Box<dyn Error>> {
+		let elements = read_index_list(cur, version)?;
+		let referenced_size = u32::read_le(cur)?;
+		let active_elements = u32::read_le(cur)?;
+		let aliases = [
+			read_index_list(cur, version)?,
+			read_index_list(cur, version)?,
+			read_index_list(cur, version)?
+		];
+
+		Ok(Self {
+			elements,
+			referenced_size,
+			active_elements,
+			aliases
+		})
+	}
+
+	pub fn write(&self, writer: &mut Cursor<Vec<u8>>, version: u32) -> Result<(), Box<dyn Error>> {
+		write_index_list(writer, version, &self.elements)?;
+		self.referenced_size.write_le(writer)?;
+		self.active_elements.write_le(writer)?;
+		for alias in &self.aliases {
+			write_index_list(writer, version, alias)?;
+		}
+		Ok(())
+	}
+}
+
+// a named subset of the mesh, drawn from one link's vertices
+#[derive(Debug, Clone)]
+pub struct GmdcGroup {
+	pub primitive_type: u32,
+	pub link: u32,
+	pub name: SevenBitString,
+	pub faces: Vec<u32>,
+	pub opacity: u32,
+	// joints that vertices in this group are assigned to
+	pub used_joints: Vec<u32>
+}
+
+impl GmdcGroup {
+	pub fn read(cur: &mut Cursor<&[u8]>, version: u32) -> Result<Self, Box<dyn Error>> {
+		let primitive_type = u32::read_le(cur)?;
+		let link = u32::read_le(cur)?;
+		let name = SevenBitString::read(cur)?;
+		let faces = read_index_list(cur, version)?;
+		let opacity = u32::read_le(cur)?;
+		let used_joints = if version != 3 { read_index_list(cur, version)? } else { Vec::new() };
+
+		Ok(Self {
+			primitive_type,
+			link,
+			name,
+			faces,
+			opacity,
+			used_joints
+		})
+	}
+
+	pub fn write(&self, writer: &mut Cursor<Vec<u8>>, version: u32) -> Result<(), Box<dyn Error>> {
+		self.primitive_type.write_le(writer)?;
+		self.link.write_le(writer)?;
+		self.name.write(writer)?;
+		write_index_list(writer, version, &self.faces)?;
+		self.opacity.write_le(writer)?;
+		if version != 3 {
+			write_index_list(writer, version, &self.used_joints)?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct GmdcTransform {
+	pub rotation: [f32; 4],
+	pub translation: [f32; 3]
+}
+
+#[derive(Debug, Clone)]
+pub struct GmdcModel {
+	pub transforms: Vec<GmdcTransform>,
+	// blend group and the element it's assigned to
+	pub blend_groups: Vec<(SevenBitString, SevenBitString)>,
+	pub bounding_mesh: GmdcMesh
+}
+
+impl GmdcModel {
+	pub fn read(cur: &mut Cursor<&[u8]>, version: u32) -> Result<Self, Box<dyn Error>> {
+		let mut transforms = Vec::new();
+		let num_transforms = u32::read_le(cur)?;
+		for _ in 0..num_transforms {
+			let rotation = [f32::read_le(cur)?, f32::read_le(cur)?, f32::read_le(cur)?, f32::read_le(cur)?];
+			let translation = read_vector(cur)?;
+			transforms.push(GmdcTransform { rotation, translation });
+		}
+
+		let mut blend_groups = Vec::new();
+		let num_blend_groups = u32::read_le(cur)?;
+		for _ in 0..num_blend_groups {
+			blend_groups.push((SevenBitString::read(cur)?, SevenBitString::read(cur)?));
+		}
+
+		let bounding_mesh = GmdcMesh::read(cur, version)?;
+
+		Ok(Self {
+			transforms,
+			blend_groups,
+			bounding_mesh
+		})
+	}
+
+	pub fn write(&self, writer: &mut Cursor<Vec<u8>>, version: u32) -> Result<(), Box<dyn Error>> {
+		(self.transforms.len() as u32).write_le(writer)?;
+		for transform in &self.transforms {
+			write_floats(writer, &transform.rotation)?;
+			write_floats(writer, &transform.translation)?;
+		}
+
+		(self.blend_groups.len() as u32).write_le(writer)?;
+		for (blend_group, element) in &self.blend_groups {
+			blend_group.write(writer)?;
+			element.write(writer)?;
+		}
+
+		self.bounding_mesh.write(writer, version)?;
+		Ok(())
+	}
+}
+
+// a plain triangle mesh, used for bounding meshes; the face count is only there when there are vertices
+#[derive(Debug, Clone, Default)]
+pub struct GmdcMesh {
+	pub vertices: Vec<[f32; 3]>,
+	pub faces: Vec<u32>
+}
+
+impl GmdcMesh {
+	pub fn read(cur: &mut Cursor<&[u8]>, version: u32) -> Result<Self, Box<dyn Error>> {
+		let num_vertices = u32::read_le(cur)?;
+		if num_vertices == 0 {
+			return Ok(Self::default());
+		}
+		let num_faces = u32::read_le(cur)?;
+
+		let mut vertices = Vec::new();
+		for _ in 0..num_vertices {
+			vertices.push(read_vector(cur)?);
+		}
+		let faces = read_indices(cur, version, num_faces)?;
+
+		Ok(Self {
+			vertices,
+			faces
+		})
+	}
+
+	pub fn write(&self, writer: &mut Cursor<Vec<u8>>, version: u32) -> Result<(), Box<dyn Error>> {
+		(self.vertices.len() as u32).write_le(writer)?;
+		if self.vertices.is_empty() {
+			return Ok(());
+		}
+		(self.faces.len() as u32).write_le(writer)?;
+		write_floats(writer, self.vertices.as_flattened())?;
+		write_indices(writer, version, &self.faces)?;
 		Ok(())
 	}
 }
@@ -9,7 +9,7 @@ use crate::dbpf::resource_types::rcol::{ Rcol, RcolBlock };
 use crate::dbpf::resource_types::nodes::sg_resource::SGResource;
 use crate::dbpf::resource_types::nodes::object_graph::ObjectGraphNode;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Gmnd {
 	pub id: Identifier,
 	pub gmdc_ref: Identifier,
@@ -35,7 +35,7 @@ impl Gmnd {
 	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct GmndBlock {
 	pub version: u32,
 	pub file_name: SevenBitString,
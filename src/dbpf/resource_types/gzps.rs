@@ -7,7 +7,7 @@ use crate::dbpf::{ Identifier, PascalString, TypeId };
 use crate::dbpf::resource::Resource;
 use crate::dbpf::resource_types::cpf::{ Cpf, CpfType, PropertyValue };
 
-#[derive(Clone, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Gzps {
 	pub id: Identifier,
 	pub cpf_type: CpfType,
@@ -406,7 +406,7 @@ impl OutfitSpec {
 }
 
 #[repr(u32)]
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Category {
 	Everyday = 7,
 	Swimwear = 8,
@@ -669,7 +669,7 @@ impl Gender {
 }
 
 #[repr(u32)]
-#[derive(Copy, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
 pub enum Shoe {
 	#[default]
 	None = 0,
@@ -711,7 +711,7 @@ impl Shoe {
 }
 
 #[repr(u32)]
-#[derive(Copy, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
 pub enum Part {
 	#[default]
 	None = 0,
@@ -780,7 +780,7 @@ impl Part {
 	}
 }
 
-#[derive(Copy, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
 pub enum HairTone {
 	None,
 	Black,
@@ -834,7 +834,7 @@ impl HairTone {
 	}
 }
 
-#[derive(Clone, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Override {
 	pub shape: u32,
 	pub subset: PascalString,
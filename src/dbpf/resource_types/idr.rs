@@ -5,10 +5,13 @@ use binrw::{ BinRead, BinWrite };
 
 use crate::dbpf::{ Identifier, TypeId };
 use crate::dbpf::resource::Resource;
+use crate::error::ClodError;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Idr {
 	pub id: Identifier,
+	// 2 for TGIR references, 1 for TGI references without a resource id
+	pub version: u32,
 	pub cres_ref: Option<Identifier>,
 	pub shpe_ref: Option<Identifier>,
 	pub txmt_refs: Vec<Identifier>,
@@ -20,6 +23,12 @@ pub struct Idr {
 
 impl Idr {
 	pub fn new(resource: &Resource) -> Result<Self, Box<dyn Error>> {
+		let mut cur = Cursor::new(&resource.data[..]);
+		let idr = Self::read(&resource.id, &mut cur);
+		idr.map_err(|err| ClodError::at(cur.position(), err))
+	}
+
+	fn read(id: &Identifier, cur: &mut Cursor<&[u8]>) -> Result<Self, Box<dyn Error>> {
 		let mut cres_ref = None;
 		let mut shpe_ref = None;
 		let mut txmt_refs = Vec::new();
@@ -28,15 +37,14 @@ impl Idr {
 		let mut coll_ref = None;
 		let mut gzps_ref = None;
 
-		let mut cur = Cursor::new(&resource.data[..]);
-
-		let _ = u32::read_le(&mut cur)?; // expect 0xDEADBEEF
+		let _ = u32::read_le(cur)?; // expect 0xDEADBEEF
 
-		let use_tgir = u32::read_le(&mut cur)? == 2;
+		let version = u32::read_le(cur)?;
+		let use_tgir = version == 2;
 
-		let num_entries = u32::read_le(&mut cur)?;
+		let num_entries = u32::read_le(cur)?;
 		for _ in 0..num_entries {
-			let entry_id = Identifier::read(&mut cur, use_tgir)?;
+			let entry_id = Identifier::read(cur, use_tgir)?;
 			match entry_id.type_id {
 				TypeId::Cres => { cres_ref = Some(entry_id); }
 				TypeId::Shpe => { shpe_ref = Some(entry_id); }
@@ -50,7 +58,8 @@ impl Idr {
 		}
 
 		Ok(Self {
-			id: resource.id.clone(),
+			id: id.clone(),
+			version,
 			cres_ref,
 			shpe_ref,
 			txmt_refs,
@@ -66,6 +75,7 @@ impl Idr {
 		id.type_id = TypeId::Idr;
 		Self {
 			id,
+			version: 2,
 			cres_ref: None,
 			shpe_ref: None,
 			txmt_refs: Vec::new(),
@@ -81,7 +91,7 @@ impl Idr {
 
 		0xDEADBEEFu32.write_le(&mut cur)?;
 
-		2u32.write_le(&mut cur)?;
+		self.version.write_le(&mut cur)?;
 
 		let mut entries = Vec::new();
 
@@ -113,7 +123,7 @@ impl Idr {
 
 		(entries.len() as u32).write_le(&mut cur)?;
 		for entry in entries {
-			entry.write(&mut cur, true)?;
+			entry.write(&mut cur, self.version == 2)?;
 		}
 
 		Ok(cur.into_inner())
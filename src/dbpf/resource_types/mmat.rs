@@ -5,7 +5,7 @@ use crate::dbpf::{ Identifier, PascalString };
 use crate::dbpf::resource::Resource;
 use crate::dbpf::resource_types::cpf::{ Cpf, CpfType, PropertyValue };
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Mmat {
 	pub id: Identifier,
 	pub flags: u32,
@@ -19,3 +19,6 @@ pub mod xtol;
 pub mod text_list;
 
 pub mod dir;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,60 @@
+use std::error::Error;
+use std::io::Cursor;
+
+use binrw::{ BinRead, BinWrite };
+
+use crate::dbpf::{ TypeId, PascalString };
+use crate::dbpf::resource_types::nodes::data_list::Extension;
+
+#[derive(Debug, Clone)]
+pub struct BoneDataExtension {
+	pub version: u32,
+	pub extension: Extension,
+	pub unknown1: u32,
+	pub unknown2: u32,
+	pub unknown3: f32,
+	pub rotation: [f32; 4]
+}
+
+impl BoneDataExtension {
+	pub fn read(cur: &mut Cursor<&[u8]>) -> Result<Self, Box<dyn Error>> {
+		let block_name = PascalString::read::<u8>(cur)?;
+		if &block_name.to_string() != "cBoneDataExtension" {
+			return Err("Invalid cBoneDataExtension header.".into());
+		}
+
+		let _block_id = u32::read_le(cur)?;
+		let version = u32::read_le(cur)?; // expect 5
+
+		let extension = Extension::read(cur)?;
+
+		let unknown1 = u32::read_le(cur)?;
+		let unknown2 = u32::read_le(cur)?;
+		let unknown3 = f32::read_le(cur)?;
+		let rotation = <[f32; 4]>::read_le(cur)?;
+
+		Ok(Self {
+			version,
+			extension,
+			unknown1,
+			unknown2,
+			unknown3,
+			rotation
+		})
+	}
+
+	pub fn write(&self, writer: &mut Cursor<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+		PascalString::new("cBoneDataExtension").write::<u8>(writer)?;
+		u32::from(TypeId::BoneData).write_le(writer)?;
+		self.version.write_le(writer)?;
+
+		self.extension.write(writer)?;
+
+		self.unknown1.write_le(writer)?;
+		self.unknown2.write_le(writer)?;
+		self.unknown3.write_le(writer)?;
+		self.rotation.write_le(writer)?;
+
+		Ok(())
+	}
+}
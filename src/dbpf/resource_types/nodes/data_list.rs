@@ -0,0 +1,199 @@
+use std::error::Error;
+use std::io::{ Cursor, Read };
+
+use binrw::{ BinRead, BinWrite };
+
+use crate::dbpf::{ TypeId, SevenBitString, PascalString };
+
+#[derive(Debug, Clone)]
+pub struct DataListExtension {
+	pub version: u32,
+	pub extension: Extension
+}
+
+impl DataListExtension {
+	pub fn read(cur: &mut Cursor<&[u8]>) -> Result<Self, Box<dyn Error>> {
+		let block_name = PascalString::read::<u8>(cur)?;
+		if &block_name.to_string() != "cDataListExtension" {
+			return Err("Invalid cDataListExtension header.".into());
+		}
+
+		let _block_id = u32::read_le(cur)?;
+		let version = u32::read_le(cur)?; // expect 1
+
+		let extension = Extension::read(cur)?;
+
+		Ok(Self {
+			version,
+			extension
+		})
+	}
+
+	pub fn write(&self, writer: &mut Cursor<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+		PascalString::new("cDataListExtension").write::<u8>(writer)?;
+		u32::from(TypeId::DataList).write_le(writer)?;
+		self.version.write_le(writer)?;
+
+		self.extension.write(writer)?;
+
+		Ok(())
+	}
+}
+
+// named values attached to scenegraph nodes, the list itself being an array item
+#[derive(Debug, Clone)]
+pub struct Extension {
+	// only in version 4
+	pub name: Option<SevenBitString>,
+	pub items: Vec<ExtensionItem>
+}
+
+impl Extension {
+	pub fn read(cur: &mut Cursor<&[u8]>) -> Result<Self, Box<dyn Error>> {
+		let block_name = SevenBitString::read(cur)?;
+		if &block_name.to_string() != "cExtension" {
+			return Err("Invalid cExtension header.".into());
+		}
+
+		let _block_id = u32::read_le(cur)?; // expect 0
+		let version = u32::read_le(cur)?; // expect 3 or 4
+
+		let typecode = u8::read(cur)?;
+		if typecode != ARRAY {
+			return Err(format!("Invalid cExtension type {typecode}.").into());
+		}
+
+		let name = if version == 4 {
+			Some(SevenBitString::read(cur)?)
+		} else {
+			None
+		};
+
+		let items = ExtensionItem::read_list(cur)?;
+
+		Ok(Self {
+			name,
+			items
+		})
+	}
+
+	pub fn write(&self, writer: &mut Cursor<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+		SevenBitString::new("cExtension").write(writer)?;
+		0u32.write_le(writer)?;
+
+		let version = if self.name.is_none() { 3 } else { 4 };
+		version.write_le(writer)?;
+
+		ARRAY.write(writer)?;
+
+		if let Some(name) = &self.name {
+			name.write(writer)?;
+		}
+
+		ExtensionItem::write_list(&self.items, writer)?;
+
+		Ok(())
+	}
+
+	pub fn get(&self, name: &str) -> Option<&ExtensionValue> {
+		self.items.iter().find(|item| item.name.to_string() == name).map(|item| &item.value)
+	}
+}
+
+const VALUE: u8 = 2;
+const FLOAT: u8 = 3;
+const TRANSLATION: u8 = 5;
+const STRING: u8 = 6;
+const ARRAY: u8 = 7;
+const ROTATION: u8 = 8;
+const BINARY: u8 = 9;
+
+#[derive(Debug, Clone)]
+pub enum ExtensionValue {
+	Value(i32),
+	Float(f32),
+	Translation([f32; 3]),
+	String(SevenBitString),
+	Array(Vec<ExtensionItem>),
+	Rotation([f32; 4]),
+	Binary(Vec<u8>)
+}
+
+#[derive(Debug, Clone)]
+pub struct ExtensionItem {
+	pub name: SevenBitString,
+	pub value: ExtensionValue
+}
+
+impl ExtensionItem {
+	pub fn read(cur: &mut Cursor<&[u8]>) -> Result<Self, Box<dyn Error>> {
+		let typecode = u8::read(cur)?;
+		let name = SevenBitString::read(cur)?;
+		let value = match typecode {
+			VALUE => ExtensionValue::Value(i32::read_le(cur)?),
+			FLOAT => ExtensionValue::Float(f32::read_le(cur)?),
+			TRANSLATION => ExtensionValue::Translation(<[f32; 3]>::read_le(cur)?),
+			STRING => ExtensionValue::String(SevenBitString::read(cur)?),
+			ARRAY => ExtensionValue::Array(Self::read_list(cur)?),
+			ROTATION => ExtensionValue::Rotation(<[f32; 4]>::read_le(cur)?),
+			BINARY => {
+				let size = u32::read_le(cur)?;
+				let mut data = vec![0u8; size as usize];
+				cur.read_exact(&mut data)?;
+				ExtensionValue::Binary(data)
+			}
+			_ => return Err(format!("Invalid extension item type {typecode} for \"{name}\".").into())
+		};
+
+		Ok(Self {
+			name,
+			value
+		})
+	}
+
+	pub fn write(&self, writer: &mut Cursor<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+		let typecode = match &self.value {
+			ExtensionValue::Value(_) => VALUE,
+			ExtensionValue::Float(_) => FLOAT,
+			ExtensionValue::Translation(_) => TRANSLATION,
+			ExtensionValue::String(_) => STRING,
+			ExtensionValue::Array(_) => ARRAY,
+			ExtensionValue::Rotation(_) => ROTATION,
+			ExtensionValue::Binary(_) => BINARY
+		};
+		typecode.write(writer)?;
+		self.name.write(writer)?;
+
+		match &self.value {
+			ExtensionValue::Value(value) => value.write_le(writer)?,
+			ExtensionValue::Float(value) => value.write_le(writer)?,
+			ExtensionValue::Translation(value) => value.write_le(writer)?,
+			ExtensionValue::String(value) => value.write(writer)?,
+			ExtensionValue::Array(items) => Self::write_list(items, writer)?,
+			ExtensionValue::Rotation(value) => value.write_le(writer)?,
+			ExtensionValue::Binary(data) => {
+				(data.len() as u32).write_le(writer)?;
+				data.write(writer)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	fn read_list(cur: &mut Cursor<&[u8]>) -> Result<Vec<Self>, Box<dyn Error>> {
+		let num_items = u32::read_le(cur)?;
+		let mut items = Vec::new();
+		for _ in 0..num_items {
+			items.push(Self::read(cur)?);
+		}
+		Ok(items)
+	}
+
+	fn write_list(items: &[Self], writer: &mut Cursor<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+		(items.len() as u32).write_le(writer)?;
+		for item in items {
+			item.write(writer)?;
+		}
+		Ok(())
+	}
+}
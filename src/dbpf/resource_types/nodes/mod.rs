@@ -2,7 +2,7 @@ pub mod composition_tree;
 pub mod object_graph;
 pub mod referent;
 pub mod sg_resource;
-// pub mod data_list;
-// pub mod transform;
-// pub mod bone_data;
-// pub mod shape_ref;
+pub mod data_list;
+pub mod transform;
+pub mod bone_data;
+pub mod shape_ref;
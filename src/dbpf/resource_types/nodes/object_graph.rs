@@ -5,17 +5,17 @@ use binrw::{ BinRead, BinWrite };
 
 use crate::dbpf::SevenBitString;
 
-#[derive(Clone)]
-struct ObjectGraphNodeExtension {
+#[derive(Debug, Clone)]
+pub struct ObjectGraphNodeExtension {
 	pub is_enabled: bool,
 	pub is_dependent: bool,
 	pub extension_index: u32,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct ObjectGraphNode {
-	extensions: Vec<ObjectGraphNodeExtension>,
-	file_name: Option<SevenBitString>
+	pub extensions: Vec<ObjectGraphNodeExtension>,
+	pub file_name: Option<SevenBitString>
 }
 
 impl ObjectGraphNode {
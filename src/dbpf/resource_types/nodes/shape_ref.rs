@@ -0,0 +1,146 @@
+use std::error::Error;
+use std::io::{ Cursor, Read };
+
+use binrw::{ BinRead, BinWrite };
+
+use crate::dbpf::{ TypeId, SevenBitString, PascalString };
+use crate::dbpf::resource_types::nodes::transform::TransformNode;
+
+#[derive(Debug, Clone)]
+pub struct ShapeRefNode {
+	pub version: u32,
+	pub transform: TransformNode,
+	pub unknown1: u16,
+	pub unknown2: u32,
+	// render group, usually "Practical"
+	pub name: SevenBitString,
+	pub unknown3: u32,
+	pub unknown4: u8,
+	// (is_enabled, is_dependent, link index) of the referenced SHPEs
+	pub shapes: Vec<(bool, bool, u32)>,
+	pub unknown5: u32,
+	// (is_enabled, name) of the morphs applied to the shapes
+	pub blends: Vec<(u32, SevenBitString)>,
+	pub blend_data: Vec<u8>,
+	pub unknown6: u32
+}
+
+impl ShapeRefNode {
+	pub fn read(cur: &mut Cursor<&[u8]>) -> Result<Self, Box<dyn Error>> {
+		let block_name = PascalString::read::<u8>(cur)?;
+		if &block_name.to_string() != "cShapeRefNode" {
+			return Err("Invalid cShapeRefNode header.".into());
+		}
+
+		let _block_id = u32::read_le(cur)?;
+		let version = u32::read_le(cur)?; // expect 21
+
+		read_node_header(cur, "cRenderableNode")?;
+		read_node_header(cur, "cBoundedNode")?;
+		let transform = TransformNode::read(cur)?;
+
+		let unknown1 = u16::read_le(cur)?;
+		let unknown2 = u32::read_le(cur)?;
+		let name = SevenBitString::read(cur)?;
+		let unknown3 = u32::read_le(cur)?;
+		let unknown4 = u8::read(cur)?;
+
+		let num_shapes = u32::read_le(cur)?;
+		let mut shapes = Vec::new();
+		for _ in 0..num_shapes {
+			let is_enabled = u8::read(cur)? == 1;
+			let is_dependent = u8::read(cur)? == 1;
+			let link_index = u32::read_le(cur)?;
+			shapes.push((is_enabled, is_dependent, link_index));
+		}
+
+		let unknown5 = u32::read_le(cur)?;
+
+		let num_blends = u32::read_le(cur)?;
+		let mut blends = Vec::new();
+		for _ in 0..num_blends {
+			let is_enabled = u32::read_le(cur)?;
+			let blend_name = SevenBitString::read(cur)?;
+			blends.push((is_enabled, blend_name));
+		}
+
+		let blend_data_size = u32::read_le(cur)?;
+		let mut blend_data = vec![0u8; blend_data_size as usize];
+		cur.read_exact(&mut blend_data)?;
+
+		let unknown6 = u32::read_le(cur)?;
+
+		Ok(Self {
+			version,
+			transform,
+			unknown1,
+			unknown2,
+			name,
+			unknown3,
+			unknown4,
+			shapes,
+			unknown5,
+			blends,
+			blend_data,
+			unknown6
+		})
+	}
+
+	pub fn write(&self, writer: &mut Cursor<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+		PascalString::new("cShapeRefNode").write::<u8>(writer)?;
+		u32::from(TypeId::ShapeRef).write_le(writer)?;
+		self.version.write_le(writer)?;
+
+		write_node_header(writer, "cRenderableNode")?;
+		write_node_header(writer, "cBoundedNode")?;
+		self.transform.write_nested(writer)?;
+
+		self.unknown1.write_le(writer)?;
+		self.unknown2.write_le(writer)?;
+		self.name.write(writer)?;
+		self.unknown3.write_le(writer)?;
+		self.unknown4.write(writer)?;
+
+		(self.shapes.len() as u32).write_le(writer)?;
+		for (is_enabled, is_dependent, link_index) in &self.shapes {
+			if *is_enabled { 1u8.write(writer)?; } else { 0u8.write(writer)?; }
+			if *is_dependent { 1u8.write(writer)?; } else { 0u8.write(writer)?; }
+			link_index.write_le(writer)?;
+		}
+
+		self.unknown5.write_le(writer)?;
+
+		(self.blends.len() as u32).write_le(writer)?;
+		for (is_enabled, blend_name) in &self.blends {
+			is_enabled.write_le(writer)?;
+			blend_name.write(writer)?;
+		}
+
+		(self.blend_data.len() as u32).write_le(writer)?;
+		self.blend_data.write(writer)?;
+
+		self.unknown6.write_le(writer)?;
+
+		Ok(())
+	}
+}
+
+// cRenderableNode and cBoundedNode only carry their header
+fn read_node_header(cur: &mut Cursor<&[u8]>, expected_name: &str) -> Result<(), Box<dyn Error>> {
+	let block_name = SevenBitString::read(cur)?;
+	if block_name.to_string() != expected_name {
+		return Err(format!("Invalid {expected_name} header.").into());
+	}
+
+	let _block_id = u32::read_le(cur)?; // expect 0
+	let _block_version = u32::read_le(cur)?; // expect 5
+
+	Ok(())
+}
+
+fn write_node_header(writer: &mut Cursor<Vec<u8>>, name: &str) -> Result<(), Box<dyn Error>> {
+	SevenBitString::new(name).write(writer)?;
+	0u32.write_le(writer)?;
+	5u32.write_le(writer)?;
+	Ok(())
+}
@@ -0,0 +1,99 @@
+use std::error::Error;
+use std::io::Cursor;
+
+use binrw::{ BinRead, BinWrite };
+
+use crate::dbpf::{ TypeId, PascalString };
+use crate::dbpf::resource_types::nodes::composition_tree::CompositionTreeNode;
+use crate::dbpf::resource_types::nodes::object_graph::ObjectGraphNode;
+
+// joint id of transform nodes that aren't bones, like slots
+pub const NO_JOINT: u32 = 0x7FFFFFFF;
+
+#[derive(Debug, Clone)]
+pub struct TransformNode {
+	pub version: u32,
+	// the node's name (like "auskel_root" or a slot name) is the object graph node's file name
+	pub ogn: ObjectGraphNode,
+	// (is_enabled, is_dependent, block index) of the child nodes
+	pub children: Vec<(bool, bool, u32)>,
+	pub translation: [f32; 3],
+	pub rotation: [f32; 4],
+	pub joint_id: u32
+}
+
+impl TransformNode {
+	pub fn read(cur: &mut Cursor<&[u8]>) -> Result<Self, Box<dyn Error>> {
+		let block_name = PascalString::read::<u8>(cur)?;
+		if &block_name.to_string() != "cTransformNode" {
+			return Err("Invalid cTransformNode header.".into());
+		}
+
+		let _block_id = u32::read_le(cur)?;
+		let version = u32::read_le(cur)?; // expect 7
+
+		CompositionTreeNode::read(cur)?;
+		let ogn = ObjectGraphNode::read(cur)?;
+
+		let num_children = u32::read_le(cur)?;
+		let mut children = Vec::new();
+		for _ in 0..num_children {
+			let is_enabled = u8::read(cur)? == 1;
+			let is_dependent = u8::read(cur)? == 1;
+			let child_index = u32::read_le(cur)?;
+			children.push((is_enabled, is_dependent, child_index));
+		}
+
+		let translation = <[f32; 3]>::read_le(cur)?;
+		let rotation = <[f32; 4]>::read_le(cur)?;
+		let joint_id = u32::read_le(cur)?;
+
+		Ok(Self {
+			version,
+			ogn,
+			children,
+			translation,
+			rotation,
+			joint_id
+		})
+	}
+
+	pub fn write(&self, writer: &mut Cursor<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+		self.write_with_id(writer, u32::from(TypeId::Transform))
+	}
+
+	// the transform node nested in a cShapeRefNode has block id 0
+	pub fn write_nested(&self, writer: &mut Cursor<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+		self.write_with_id(writer, 0)
+	}
+
+	fn write_with_id(&self, writer: &mut Cursor<Vec<u8>>, block_id: u32) -> Result<(), Box<dyn Error>> {
+		PascalString::new("cTransformNode").write::<u8>(writer)?;
+		block_id.write_le(writer)?;
+		self.version.write_le(writer)?;
+
+		CompositionTreeNode::write(writer)?;
+		self.ogn.write(writer)?;
+
+		(self.children.len() as u32).write_le(writer)?;
+		for (is_enabled, is_dependent, child_index) in &self.children {
+			if *is_enabled { 1u8.write(writer)?; } else { 0u8.write(writer)?; }
+			if *is_dependent { 1u8.write(writer)?; } else { 0u8.write(writer)?; }
+			child_index.write_le(writer)?;
+		}
+
+		self.translation.write_le(writer)?;
+		self.rotation.write_le(writer)?;
+		self.joint_id.write_le(writer)?;
+
+		Ok(())
+	}
+
+	pub fn name(&self) -> Option<String> {
+		self.ogn.file_name.as_ref().map(|file_name| file_name.to_string())
+	}
+
+	pub fn is_joint(&self) -> bool {
+		self.joint_id != NO_JOINT
+	}
+}
@@ -4,6 +4,7 @@ use std::io::Cursor;
 use binrw::{ BinRead, BinWrite };
 
 use crate::dbpf::{ Identifier, TypeId };
+use crate::error::ClodError;
 
 use crate::dbpf::resource_types::gmdc::GmdcBlock;
 use crate::dbpf::resource_types::gmnd::GmndBlock;
@@ -11,6 +12,10 @@ use crate::dbpf::resource_types::shpe::ShpeBlock;
 use crate::dbpf::resource_types::cres::CresBlock;
 use crate::dbpf::resource_types::txmt::TxmtBlock;
 use crate::dbpf::resource_types::txtr::TxtrBlock;
+use crate::dbpf::resource_types::nodes::data_list::DataListExtension;
+use crate::dbpf::resource_types::nodes::bone_data::BoneDataExtension;
+use crate::dbpf::resource_types::nodes::transform::TransformNode;
+use crate::dbpf::resource_types::nodes::shape_ref::ShapeRefNode;
 
 pub struct Rcol {
 	pub links: Vec<Identifier>,
@@ -20,28 +25,39 @@ pub struct Rcol {
 impl Rcol {
 	pub fn read(data: &[u8]) -> Result<Self, Box<dyn Error>> {
 		let mut cur = Cursor::new(data);
-		let first = u32::read_le(&mut cur)?;
+		let rcol = Self::read_from(&mut cur);
+		rcol.map_err(|err| ClodError::at(cur.position(), err))
+	}
+
+	fn read_from(cur: &mut Cursor<&[u8]>) -> Result<Self, Box<dyn Error>> {
+		let first = u32::read_le(cur)?;
 		let use_tgir = first == 0xFFFF0001;
-		let num_links = if use_tgir { u32::read_le(&mut cur)? } else { first };
+		let num_links = if use_tgir { u32::read_le(cur)? } else { first };
 		let mut links = Vec::new();
 		for _ in 0..num_links {
-			let group_id = u32::read_le(&mut cur)?;
-			let instance_id = u32::read_le(&mut cur)?;
-			let resource_id = if use_tgir { u32::read_le(&mut cur)? } else { 0 };
-			let type_id = u32::read_le(&mut cur)?;
+			let group_id = u32::read_le(cur)?;
+			let instance_id = u32::read_le(cur)?;
+			let resource_id = if use_tgir { u32::read_le(cur)? } else { 0 };
+			let type_id = u32::read_le(cur)?;
 			links.push(Identifier::new(type_id, group_id, resource_id, instance_id));
 		}
 
-		let num_blocks = u32::read_le(&mut cur)?;
+		let num_blocks = u32::read_le(cur)?;
 		let mut block_ids = Vec::new();
 		for _ in 0..num_blocks {
-			let block_id = u32::read_le(&mut cur)?;
+			let block_id = u32::read_le(cur)?;
 			block_ids.push(block_id);
 		}
 
 		let mut blocks = Vec::new();
 		for block_id in block_ids {
-			blocks.push(RcolBlock::read(&mut cur, block_id)?);
+			let block = RcolBlock::read(cur, block_id)?;
+			let is_unknown = matches!(block, RcolBlock::Unknown(_));
+			blocks.push(block);
+			// unknown blocks aren't read, so there's no telling where the ones after them start
+			if is_unknown {
+				break;
+			}
 		}
 
 		Ok(Self {
@@ -74,7 +90,7 @@ impl Rcol {
 	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub enum RcolBlock {
 	Gmdc(GmdcBlock),
 	Gmnd(GmndBlock),
@@ -82,6 +98,10 @@ pub enum RcolBlock {
 	Cres(CresBlock),
 	Txmt(TxmtBlock),
 	Txtr(TxtrBlock),
+	DataList(DataListExtension),
+	BoneData(BoneDataExtension),
+	Transform(TransformNode),
+	ShapeRef(ShapeRefNode),
 	Unknown(())
 }
 
@@ -112,6 +132,22 @@ impl RcolBlock {
 				let txtr_block = TxtrBlock::read(cur)?;
 				Ok(RcolBlock::Txtr(txtr_block))
 			},
+			TypeId::DataList => {
+				let data_list = DataListExtension::read(cur)?;
+				Ok(RcolBlock::DataList(data_list))
+			},
+			TypeId::BoneData => {
+				let bone_data = BoneDataExtension::read(cur)?;
+				Ok(RcolBlock::BoneData(bone_data))
+			},
+			TypeId::Transform => {
+				let transform = TransformNode::read(cur)?;
+				Ok(RcolBlock::Transform(transform))
+			},
+			TypeId::ShapeRef => {
+				let shape_ref = ShapeRefNode::read(cur)?;
+				Ok(RcolBlock::ShapeRef(shape_ref))
+			},
 			_ => {
 				Ok(RcolBlock::Unknown(()))
 			}
@@ -126,6 +162,10 @@ impl RcolBlock {
 			RcolBlock::Cres(_) => u32::from(TypeId::Cres).write_le(writer)?,
 			RcolBlock::Txmt(_) => u32::from(TypeId::Txmt).write_le(writer)?,
 			RcolBlock::Txtr(_) => u32::from(TypeId::Txtr).write_le(writer)?,
+			RcolBlock::DataList(_) => u32::from(TypeId::DataList).write_le(writer)?,
+			RcolBlock::BoneData(_) => u32::from(TypeId::BoneData).write_le(writer)?,
+			RcolBlock::Transform(_) => u32::from(TypeId::Transform).write_le(writer)?,
+			RcolBlock::ShapeRef(_) => u32::from(TypeId::ShapeRef).write_le(writer)?,
 			RcolBlock::Unknown(block_id) => block_id.write_le(writer)?
 		}
 		Ok(())
@@ -139,6 +179,10 @@ impl RcolBlock {
 			RcolBlock::Cres(cres_block) => cres_block.write(writer)?,
 			RcolBlock::Txmt(txmt_block) => txmt_block.write(writer)?,
 			RcolBlock::Txtr(txtr_block) => txtr_block.write(writer)?,
+			RcolBlock::DataList(data_list) => data_list.write(writer)?,
+			RcolBlock::BoneData(bone_data) => bone_data.write(writer)?,
+			RcolBlock::Transform(transform) => transform.write(writer)?,
+			RcolBlock::ShapeRef(shape_ref) => shape_ref.write(writer)?,
 			RcolBlock::Unknown(_block_id) => {}
 		}
 		Ok(())
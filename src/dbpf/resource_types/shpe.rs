@@ -13,7 +13,7 @@ use crate::dbpf::resource_types::nodes::sg_resource::SGResource;
 use crate::dbpf::resource_types::nodes::referent::ReferentNode;
 use crate::dbpf::resource_types::nodes::object_graph::ObjectGraphNode;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Shpe {
 	pub id: Identifier,
 	pub block: ShpeBlock,
@@ -64,7 +64,7 @@ impl Shpe {
 	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct ShpeBlock {
 	pub version: u32,
 	pub file_name: SevenBitString,
@@ -148,7 +148,7 @@ impl ShpeBlock {
 	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct GmndItem {
 	pub item_type: u32,
 	pub enabled: u8,
@@ -187,32 +187,38 @@ impl GmndItem {
 	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Material {
 	pub subset: SevenBitString,
-	pub txmt_name: SevenBitString
+	pub txmt_name: SevenBitString,
+	pub lod_type: u32,
+	pub enabled: u8,
+	pub index: u32
 }
 
 impl Material {
 	pub fn read(cur: &mut Cursor<&[u8]>) -> Result<Self, Box<dyn Error>> {
 		let subset = SevenBitString::read(cur)?;
 		let txmt_name = SevenBitString::read(cur)?;
-		let _lod_type = u32::read_le(cur)?; // always 0
-		let _enabled = u8::read_le(cur)?; // always 0
-		let _index = u32::read_le(cur)?; // always 0
+		let lod_type = u32::read_le(cur)?; // usually 0
+		let enabled = u8::read_le(cur)?; // usually 0
+		let index = u32::read_le(cur)?; // usually 0
 
 		Ok(Self {
 			subset,
-			txmt_name
+			txmt_name,
+			lod_type,
+			enabled,
+			index
 		})
 	}
 
 	pub fn write(&self, writer: &mut Cursor<Vec<u8>>) -> Result<(), Box<dyn Error>> {
 		self.subset.write(writer)?;
 		self.txmt_name.write(writer)?;
-		0u32.write_le(writer)?;
-		0u8.write_le(writer)?;
-		0u32.write_le(writer)?;
+		self.lod_type.write_le(writer)?;
+		self.enabled.write_le(writer)?;
+		self.index.write_le(writer)?;
 		Ok(())
 	}
 }
@@ -0,0 +1,212 @@
+use crate::dbpf::{ TypeId, SevenBitString };
+use crate::dbpf::resource::DecodedResource;
+use crate::dbpf::resource_types::gmdc::ElementIdentity;
+use crate::dbpf::resource_types::rcol::RcolBlock;
+use crate::dbpf::resource_types::txtr::TxtrPurpose;
+use crate::dbpf::resource_types::nodes::data_list::ExtensionValue;
+use crate::test_support::{ Bytes, assert_round_trip, diff_report, rcol_blocks, resource };
+use crate::test_support::fixtures::*;
+
+// every DecodedResource variant; kept in sync by the exhaustive match in variant_name
+const ALL_VARIANTS: [&str; 13] = [
+	"Gmdc", "Gmnd", "Shpe", "Cres", "Mmat", "Txmt", "Txtr", "Gzps", "Idr", "Binx", "Xtol", "TextList", "Other"
+];
+
+fn variant_name(decoded: &DecodedResource) -> &'static str {
+	match decoded {
+		DecodedResource::Gmdc(_) => "Gmdc",
+		DecodedResource::Gmnd(_) => "Gmnd",
+		DecodedResource::Shpe(_) => "Shpe",
+		DecodedResource::Cres(_) => "Cres",
+		DecodedResource::Mmat(_) => "Mmat",
+		DecodedResource::Txmt(_) => "Txmt",
+		DecodedResource::Txtr(_) => "Txtr",
+		DecodedResource::Gzps(_) => "Gzps",
+		DecodedResource::Idr(_) => "Idr",
+		DecodedResource::Binx(_) => "Binx",
+		DecodedResource::Xtol(_) => "Xtol",
+		DecodedResource::TextList(_) => "TextList",
+		DecodedResource::Other(_) => "Other"
+	}
+}
+
+#[test]
+fn every_variant_is_covered() {
+	let covered = synthetic_resources().iter()
+		.map(|resource| variant_name(&DecodedResource::new(resource, "test").unwrap()))
+		.collect::<Vec<&str>>();
+	for variant in ALL_VARIANTS {
+		assert!(covered.contains(&variant), "no synthetic resource decodes to DecodedResource::{variant}");
+	}
+}
+
+#[test]
+fn gmdc_round_trip() {
+	assert_round_trip(&gmdc_resource());
+}
+
+#[test]
+fn gmdc_v3_round_trip() {
+	assert_round_trip(&gmdc_v3_resource());
+}
+
+#[test]
+fn gmdc_decodes_geometry() {
+	let DecodedResource::Gmdc(gmdc) = assert_round_trip(&gmdc_resource()) else { panic!("not a GMDC") };
+	let identities = gmdc.block.elements.iter().map(|element| element.identity).collect::<Vec<ElementIdentity>>();
+	assert_eq!(identities, [ElementIdentity::Vertices, ElementIdentity::Normals, ElementIdentity::UvCoordinates,
+		ElementIdentity::BoneAssignments, ElementIdentity::MorphVertexDeltas]);
+	assert_eq!(gmdc.block.elements[0].data.len(), 3);
+	assert_eq!(gmdc.block.elements[4].references, [0, 0, 0]);
+	assert_eq!(gmdc.block.links[0].elements, [0, 1, 2, 3]);
+	assert_eq!(gmdc.block.groups[0].name.to_string(), "body");
+	assert_eq!(gmdc.block.groups[0].faces, [0, 1, 2]);
+	assert_eq!(gmdc.block.groups[0].used_joints, [0, 1]);
+	assert_eq!(gmdc.block.model.transforms[1].translation, [0.0, 1.0, 0.0]);
+	assert_eq!(gmdc.block.model.bounding_mesh.vertices.len(), 3);
+	assert_eq!(gmdc.block.joints.len(), 2);
+	assert!(gmdc.block.joints[1].vertices.is_empty());
+}
+
+#[test]
+fn gmdc_v4_refuses_indices_past_u16() {
+	let DecodedResource::Gmdc(mut gmdc) = assert_round_trip(&gmdc_resource()) else { panic!("not a GMDC") };
+	gmdc.block.groups[0].faces[2] = 70000;
+	let err = DecodedResource::Gmdc(gmdc).to_bytes().unwrap_err();
+	assert!(err.to_string().contains("70000"), "{err}");
+}
+
+#[test]
+fn gmnd_round_trip() {
+	assert_round_trip(&gmnd_resource());
+}
+
+#[test]
+fn shpe_round_trip() {
+	assert_round_trip(&shpe_resource());
+}
+
+#[test]
+fn cres_round_trip() {
+	assert_round_trip(&cres_resource());
+}
+
+#[test]
+fn cres_decodes_scenegraph() {
+	let DecodedResource::Cres(cres) = assert_round_trip(&cres_resource()) else { panic!("not a CRES") };
+	assert!(cres.data.is_none());
+	assert_eq!(cres.blocks.len(), 6);
+	let RcolBlock::Cres(cres_block) = &cres.blocks[0] else { panic!("no resource node") };
+	assert_eq!(cres_block.chains, [(true, false, 1)]);
+	let RcolBlock::ShapeRef(shape_ref) = &cres.blocks[1] else { panic!("no shape reference node") };
+	assert_eq!(shape_ref.shapes, [(true, false, 0)]);
+	assert_eq!(shape_ref.blends[0].1.to_string(), "fat");
+	let names = cres.transforms().map(|transform| transform.name().unwrap()).collect::<Vec<String>>();
+	assert_eq!(names, ["test_shape", "auskel_root", "r_hand_slot"]);
+	let joints = cres.transforms().filter(|transform| transform.is_joint()).count();
+	assert_eq!(joints, 1);
+	let RcolBlock::DataList(data_list) = &cres.blocks[4] else { panic!("no data list") };
+	assert!(matches!(data_list.extension.get("bone"), Some(ExtensionValue::String(bone)) if bone.to_string() == "r_hand"));
+	let RcolBlock::BoneData(bone_data) = &cres.blocks[5] else { panic!("no bone data") };
+	assert_eq!(bone_data.extension.name.as_ref().unwrap().to_string(), "bone");
+	assert!(matches!(bone_data.extension.get("data"), Some(ExtensionValue::Binary(data)) if data == &[0xAB, 0xCD]));
+}
+
+#[test]
+fn cres_keeps_unsupported_blocks() {
+	let resource_node = Bytes::new()
+		.block_header("cResourceNode", TypeId::Cres, 7)
+		.u8(1)
+		.sg_resource("test_cres")
+		.node("cCompositionTreeNode", 11)
+		.object_graph("test_cres")
+		.u32(2).u8(1).u8(0).u32(1).u8(1).u8(0).u32(2)
+		.u8(0)
+		.u32(0)
+		.finish();
+	// a cLightRefNode, which isn't decoded, followed by a transform that is
+	let light_ref = Bytes::new()
+		.block_header("cLightRefNode", TypeId::Other(0x253D2018), 10)
+		.node("cRenderableNode", 5)
+		.u32(0x12345678)
+		.finish();
+	let root = Bytes::new().transform_node(u32::from(TypeId::Transform), "auskel_root", &[], 0).finish();
+	let resource = resource(TypeId::Cres, rcol_blocks(&[], &[
+		(TypeId::Cres, resource_node),
+		(TypeId::Other(0x253D2018), light_ref),
+		(TypeId::Transform, root)
+	]));
+
+	let DecodedResource::Cres(cres) = assert_round_trip(&resource) else { panic!("not a CRES") };
+	assert!(matches!(cres.blocks.as_slice(), [RcolBlock::Cres(_), RcolBlock::Unknown(_)]), "{:?}", cres.blocks);
+	assert_eq!(cres.data.as_ref(), Some(&resource.data));
+}
+
+#[test]
+fn mmat_round_trip() {
+	assert_round_trip(&mmat_resource());
+}
+
+#[test]
+fn txmt_round_trip() {
+	assert_round_trip(&txmt_resource());
+}
+
+#[test]
+fn txtr_round_trip() {
+	assert_round_trip(&txtr_resource());
+	assert_round_trip(&txtr_v7_resource());
+}
+
+#[test]
+fn txtr_purpose_flags_match() {
+	for purpose in [TxtrPurpose::Object, TxtrPurpose::Outfit, TxtrPurpose::Interface] {
+		assert_eq!(TxtrPurpose::from_flag(purpose.to_flag()), purpose);
+	}
+}
+
+#[test]
+fn gzps_round_trip() {
+	assert_round_trip(&gzps_resource());
+}
+
+#[test]
+fn idr_round_trip() {
+	assert_round_trip(&idr_resource());
+	assert_round_trip(&idr_v1_resource());
+}
+
+#[test]
+fn binx_round_trip() {
+	assert_round_trip(&binx_resource());
+}
+
+#[test]
+fn xtol_round_trip() {
+	assert_round_trip(&xtol_resource());
+}
+
+#[test]
+fn xtol_keeps_subtype_and_species() {
+	let DecodedResource::Xtol(xtol) = assert_round_trip(&xtol_resource()) else { panic!("not an XTOL") };
+	assert_eq!((xtol.subtype, xtol.species), (5, 1));
+}
+
+#[test]
+fn text_list_round_trip() {
+	assert_round_trip(&text_list_resource());
+}
+
+#[test]
+fn other_round_trip() {
+	assert_round_trip(&other_resource());
+}
+
+#[test]
+fn diff_report_names_changed_fields() {
+	let resource = txmt_resource();
+	let DecodedResource::Txmt(mut txmt) = assert_round_trip(&resource) else { unreachable!() };
+	txmt.block.material_type = SevenBitString::new("Changed");
+	let report = diff_report(&resource, &DecodedResource::Txmt(txmt).to_bytes().unwrap());
+	assert!(report.contains("material_type"), "{report}");
+}
@@ -6,8 +6,9 @@ use binrw::{ BinRead, BinWrite };
 
 use crate::dbpf::{ Identifier, TypeId };
 use crate::dbpf::resource::Resource;
+use crate::error::ClodError;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct TextList {
 	pub id: Identifier,
 	pub key_name: [u8;64],
@@ -17,25 +18,29 @@ pub struct TextList {
 impl TextList {
 	pub fn new(resource: &Resource) -> Result<Self, Box<dyn Error>> {
 		let mut cur = Cursor::new(&resource.data[..]);
+		let text_list = Self::read(&resource.id, &mut cur);
+		text_list.map_err(|err| ClodError::at(cur.position(), err))
+	}
 
+	fn read(id: &Identifier, cur: &mut Cursor<&[u8]>) -> Result<Self, Box<dyn Error>> {
 		let mut key_name = [0u8; 64];
 		cur.read_exact(&mut key_name)?;
 
-		let format = u16::read_le(&mut cur)?;
+		let format = u16::read_le(cur)?;
 		if format != 0xfffd {
 			return Err("STR# has invalid data format".into());
 		}
 
-		let num_strings = u16::read_le(&mut cur)?;
+		let num_strings = u16::read_le(cur)?;
 
 		let mut strings = Vec::new();
 		for _ in 0..num_strings {
-			let string_item = StringItem::new(&mut cur)?;
+			let string_item = StringItem::new(cur)?;
 			strings.push(string_item);
 		}
 
 		Ok(Self {
-			id: resource.id.clone(),
+			id: id.clone(),
 			key_name,
 			strings
 		})
@@ -91,7 +96,7 @@ impl TextList {
 	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct StringItem {
 	pub language_code: u8,
 	pub title: String,
@@ -9,7 +9,7 @@ use crate::dbpf::resource::Resource;
 use crate::dbpf::resource_types::rcol::{ Rcol, RcolBlock };
 use crate::dbpf::resource_types::nodes::sg_resource::SGResource;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Txmt {
 	pub id: Identifier,
 	pub block: TxmtBlock,
@@ -65,6 +65,7 @@ impl Txmt {
 				material_definition: SevenBitString::new(&title_txmt),
 				material_description: SevenBitString::new(&format!("##0x{:08x}!{}", group_id, title)),
 				material_type: SevenBitString::new(material),
+				texture_names: Vec::new(),
 				properties: vec![
 					TxmtProperty::new("stdMatAlphaBlendMode", "none"),
 					TxmtProperty::new("stdMatAlphaMultiplier", "1"),
@@ -84,6 +85,7 @@ impl Txmt {
 		let mut txmt = Self::create_textureless(group_id, title, material);
 		txmt.block.properties.push(TxmtProperty::new("stdMatBaseTextureEnabled", "true"));
 		txmt.block.properties.push(TxmtProperty::new("stdMatBaseTextureName", texture_name));
+		txmt.block.texture_names.push(SevenBitString::new(texture_name));
 		txmt.txtr_names.push(SevenBitString::new(texture_name));
 		txmt
 	}
@@ -103,11 +105,14 @@ impl Txmt {
 				_ => {}
 			}
 		}
+		for texture_name in new_txmt.block.texture_names.iter_mut() {
+			*texture_name = texture_name.replace(&old_guid_str, &new_guid_str);
+		}
 		new_txmt
 	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct TxmtProperty {
 	pub name: SevenBitString,
 	pub value: SevenBitString
@@ -122,13 +127,14 @@ impl TxmtProperty {
 	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct TxmtBlock {
 	pub version: u32,
 	pub material_definition: SevenBitString,
 	pub material_description: SevenBitString,
 	pub material_type: SevenBitString,
-	pub properties: Vec<TxmtProperty>
+	pub properties: Vec<TxmtProperty>,
+	pub texture_names: Vec<SevenBitString>
 }
 
 impl TxmtBlock {
@@ -150,15 +156,37 @@ impl TxmtBlock {
 			});
 		}
 
+		let mut texture_names = Vec::new();
+		if version > 8 {
+			let num_texture_names = u32::read_le(cur)?;
+			for _ in 0..num_texture_names {
+				texture_names.push(SevenBitString::read(cur)?);
+			}
+		}
+
 		Ok(Self {
 			version,
 			material_definition,
 			material_description,
 			material_type,
-			properties
+			properties,
+			texture_names
 		})
 	}
 
+	// sets a property, keeping the texture name list in sync if the old value was listed there
+	pub fn set_property(&mut self, name: &str, value: SevenBitString) {
+		match self.properties.iter_mut().find(|prop| prop.name.to_string() == name) {
+			Some(prop) => {
+				if let Some(texture_name) = self.texture_names.iter_mut().find(|t| **t == prop.value) {
+					*texture_name = value.clone();
+				}
+				prop.value = value;
+			}
+			None => self.properties.push(TxmtProperty { name: SevenBitString::new(name), value })
+		}
+	}
+
 	pub fn write(&self, writer: &mut Cursor<Vec<u8>>) -> Result<(), Box<dyn Error>> {
 		PascalString::new("cMaterialDefinition").write::<u8>(writer)?;
 		u32::from(TypeId::Txmt).write_le(writer)?;
@@ -169,22 +197,15 @@ impl TxmtBlock {
 		self.material_description.write(writer)?;
 		self.material_type.write(writer)?;
 
-		let mut texture_names = Vec::new();
 		(self.properties.len() as u32).write_le(writer)?;
 		for prop in &self.properties {
 			prop.name.write(writer)?;
 			prop.value.write(writer)?;
-			let prop_name = prop.name.to_string();
-			if prop_name == "stdMatBaseTextureName" ||
-				prop_name == "stdMatNormalMapTextureName" ||
-				prop_name == "stdMatEnvCubeTextureName" {
-					texture_names.push(prop.value.clone());
-			}
 		}
 
 		if self.version > 8 {
-			(texture_names.len() as u32).write_le(writer)?;
-			for texture_name in &texture_names{
+			(self.texture_names.len() as u32).write_le(writer)?;
+			for texture_name in &self.texture_names {
 				texture_name.write(writer)?;
 			}
 		}
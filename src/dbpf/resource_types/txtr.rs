@@ -11,7 +11,7 @@ use crate::dbpf::resource::Resource;
 use crate::dbpf::resource_types::rcol::{ Rcol, RcolBlock };
 use crate::dbpf::resource_types::nodes::sg_resource::SGResource;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Txtr {
 	pub id: Identifier,
 	pub block: TxtrBlock,
@@ -49,6 +49,7 @@ impl Txtr {
 		let mut new_txtr = self.clone();
 		new_txtr.id.group_id = new_guid;
 		new_txtr.block.file_name = new_txtr.block.file_name.replace(&old_guid_str, &new_guid_str);
+		new_txtr.block.file_name_repeat = new_txtr.block.file_name_repeat.map(|name| name.replace(&old_guid_str, &new_guid_str));
 		let re = Regex::new(r"^##0x([0-9,a-f,A-F]+)!(.+)$").unwrap();
 		if let Some(captures) = re.captures(&new_txtr.block.file_name.to_string()) {
 			new_txtr.id.resource_id = hash_crc32(&captures[2]);
@@ -69,8 +70,14 @@ impl Txtr {
 			height: 1024,
 			format: TxtrFormat::DXT3,
 			mipmap_count: 1,
-			purpose,
-			image_groups: vec![vec![TxtrData::Image(vec![0u8; 1024*1024])]]
+			purpose: purpose.to_flag(),
+			unknown: 0,
+			file_name_repeat: Some(SevenBitString::new(title)),
+			image_groups: vec![TxtrImageGroup {
+				images: vec![TxtrData::Image(vec![0u8; 1024*1024])],
+				creator_id: 0xffffffff,
+				format_flag: 0x41200000
+			}]
 		};
 		Self {
 			id,
@@ -80,7 +87,7 @@ impl Txtr {
 	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct TxtrBlock {
 	pub version: u32,
 	pub file_name: SevenBitString,
@@ -88,8 +95,11 @@ pub struct TxtrBlock {
 	pub height: u32,
 	pub format: TxtrFormat,
 	pub mipmap_count: u32,
-	pub purpose: TxtrPurpose,
-	pub image_groups: Vec<Vec<TxtrData>>
+	// kept as stored, since TxtrPurpose doesn't cover every value
+	pub purpose: f32,
+	pub unknown: u32,
+	pub file_name_repeat: Option<SevenBitString>,
+	pub image_groups: Vec<TxtrImageGroup>
 }
 
 impl TxtrBlock {
@@ -104,14 +114,16 @@ impl TxtrBlock {
 		let height = u32::read_le(cur)?;
 		let format = TxtrFormat::from_flag(u32::read_le(cur)?);
 		let mipmap_count = u32::read_le(cur)?;
-		let purpose = TxtrPurpose::from_flag(f32::read_le(cur)?);
+		let purpose = f32::read_le(cur)?;
 
 		let image_group_count = u32::read_le(cur)?;
-		let _ = u32::read_le(cur)?;
+		let unknown = u32::read_le(cur)?;
 
-		if version == 9 {
-			let _file_name_repeat = SevenBitString::read(cur)?;
-		}
+		let file_name_repeat = if version == 9 {
+			Some(SevenBitString::read(cur)?)
+		} else {
+			None
+		};
 
 		let mut image_groups = Vec::new();
 		for _ in 0..image_group_count {
@@ -135,14 +147,15 @@ impl TxtrBlock {
 					return Err("TXTR resource contains invalid texture".into());
 				}
 			}
-			image_groups.push(images);
 
-			if version == 7 {
-				let _creator_id = u32::read_le(cur)?;
-			} else {
-				let _creator_id = u32::read_le(cur)?;
-				let _format_flag = u32::read_le(cur)?;
-			}
+			let creator_id = u32::read_le(cur)?;
+			let format_flag = if version == 7 { 0 } else { u32::read_le(cur)? };
+
+			image_groups.push(TxtrImageGroup {
+				images,
+				creator_id,
+				format_flag
+			});
 		}
 
 		Ok(Self {
@@ -153,6 +166,8 @@ impl TxtrBlock {
 			format,
 			mipmap_count,
 			purpose,
+			unknown,
+			file_name_repeat,
 			image_groups
 		})
 	}
@@ -168,20 +183,23 @@ impl TxtrBlock {
 		self.height.write_le(writer)?;
 		self.format.to_flag().write_le(writer)?;
 		self.mipmap_count.write_le(writer)?;
-		self.purpose.to_flag().write_le(writer)?;
+		self.purpose.write_le(writer)?;
 
 		(self.image_groups.len() as u32).write_le(writer)?;
-		0u32.write_le(writer)?;
+		self.unknown.write_le(writer)?;
 
 		if self.version == 9 {
-			SevenBitString::new(&self.file_name.to_string().replace("_txtr", "")).write(writer)?;
+			match &self.file_name_repeat {
+				Some(file_name_repeat) => file_name_repeat.write(writer)?,
+				None => SevenBitString::new(&self.file_name.to_string().replace("_txtr", "")).write(writer)?
+			}
 		}
 
 		for image_group in &self.image_groups {
 			if self.version == 9 {
-				(image_group.len() as u32).write_le(writer)?;
+				(image_group.images.len() as u32).write_le(writer)?;
 			}
-			for image in image_group {
+			for image in &image_group.images {
 				match image {
 					TxtrData::Image(image_data) => {
 						0u8.write(writer)?;
@@ -194,11 +212,9 @@ impl TxtrBlock {
 					}
 				}
 			}
-			if self.version == 7 {
-				0xff000000u32.write_le(writer)?;
-			} else {
-				0xffffffffu32.write_le(writer)?;
-				0x41200000u32.write_le(writer)?;
+			image_group.creator_id.write_le(writer)?;
+			if self.version != 7 {
+				image_group.format_flag.write_le(writer)?;
 			}
 		}
 
@@ -249,7 +265,7 @@ impl TxtrFormat {
 	}
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum TxtrPurpose {
 	Object,
 	Outfit,
@@ -267,14 +283,21 @@ impl TxtrPurpose {
 
 	pub fn to_flag(&self) -> f32 {
 		match self {
-			Self::Outfit => 1.0,
-			Self::Interface => 2.0,
-			Self::Object => 3.0
+			Self::Object => 1.0,
+			Self::Outfit => 2.0,
+			Self::Interface => 3.0
 		}
 	}
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
+pub struct TxtrImageGroup {
+	pub images: Vec<TxtrData>,
+	pub creator_id: u32,
+	pub format_flag: u32
+}
+
+#[derive(Debug, Clone)]
 pub enum TxtrData {
 	Lifo(SevenBitString),
 	Image(Vec<u8>)
@@ -6,7 +6,7 @@ use crate::dbpf::resource::Resource;
 use crate::dbpf::resource_types::cpf::{ Cpf, CpfType, PropertyValue };
 use crate::dbpf::resource_types::gzps::{ Age, Gender, Category, HairTone };
 
-#[derive(Clone, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Xtol {
 	pub id: Identifier,
 	pub cpf_type: CpfType,
@@ -64,7 +64,7 @@ impl Xtol {
 			_ => return Err("XTOL is missing \"type\" property.".into())
 		};
 
-		xtol.species = match cpf.get_prop("subtype") {
+		xtol.subtype = match cpf.get_prop("subtype") {
 			Some(PropertyValue::Uint(val)) => *val,
 			_ => return Err("XTOL is missing \"subtype\" property.".into())
 		};
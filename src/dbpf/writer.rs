@@ -0,0 +1,184 @@
+use std::error::Error;
+use std::io::{ Seek, SeekFrom, Write };
+
+use crate::dbpf::format::PackageFormat;
+use crate::dbpf::header::Header;
+use crate::dbpf::index_entry::IndexEntry;
+use crate::dbpf::resource::Resource;
+use crate::dbpf::resource_types::dir::{ Dir, DirItem };
+use crate::parallel::{ threads, try_par_map };
+
+// Writes a package one resource at a time, so only the resource being written has to be in memory.
+// The header is written as a placeholder and backfilled once the index is known.
+// Any DIR for the compressed resources is written after them, since its size isn't known until then,
+// but it still comes first in the index like it always has, unless another position is asked for.
+pub struct PackageWriter<W: Write + Seek> {
+	writer: W,
+	header: Header,
+	start: u64,
+	offset: u32,
+	index_entries: Vec<IndexEntry>,
+	dir_items: Vec<DirItem>,
+	dir_position: usize,
+	// format to convert added resources to, when it differs from the original
+	format: Option<PackageFormat>
+}
+
+impl<W: Write + Seek> PackageWriter<W> {
+	pub fn new(mut writer: W, header: Header) -> Result<Self, Box<dyn Error>> {
+		let start = writer.stream_position()?;
+		header.write(&mut writer)?;
+		let offset = (writer.stream_position()? - start) as u32;
+		Ok(Self {
+			writer,
+			header,
+			start,
+			offset,
+			index_entries: Vec::new(),
+			dir_items: Vec::new(),
+			dir_position: 0,
+			format: None
+		})
+	}
+
+	// writes the package in the given format, converting the references inside added resources to match
+	pub fn with_format(mut self, format: Option<PackageFormat>) -> Self {
+		if let Some(format) = format {
+			format.apply_to_header(&mut self.header);
+		}
+		self.format = format;
+		self
+	}
+
+	// where the DIR's entry goes in the index, counted among the entries written
+	pub fn with_dir_position(mut self, dir_position: usize) -> Self {
+		self.dir_position = dir_position;
+		self
+	}
+
+	fn convert(&self, resource: Resource) -> Result<Resource, Box<dyn Error>> {
+		match self.format {
+			Some(format) => format.convert(resource),
+			None => Ok(resource)
+		}
+	}
+
+	// compresses the resource if asked to and it gets smaller, then writes it
+	pub fn add(&mut self, resource: Resource, compress: bool) -> Result<(), Box<dyn Error>> {
+		let mut resource = self.convert(resource)?;
+		let uncompressed_size = resource.data.len() as u32;
+		if compress && resource.compress()? {
+			self.dir_items.push(DirItem { id: resource.id.clone(), uncompressed_size });
+		}
+		self.write_stored(&resource)
+	}
+
+	// like add, but compresses a batch of resources at a time across the worker threads.
+	// resources are still written in the order given
+	pub fn add_all(
+			&mut self,
+			resources: impl IntoIterator<Item = Result<Resource, Box<dyn Error>>>,
+			compress: impl Fn(&Resource) -> bool + Sync
+		) -> Result<(), Box<dyn Error>> {
+
+		let batch_size = threads() * 4;
+		let mut resources = resources.into_iter().peekable();
+		while resources.peek().is_some() {
+			let batch = resources.by_ref()
+				.take(batch_size)
+				.map(|resource| self.convert(resource?))
+				.collect::<Result<Vec<Resource>, Box<dyn Error>>>()?;
+			let compressed = try_par_map(batch, |mut resource| {
+				let uncompressed_size = resource.data.len() as u32;
+				let is_compressed = compress(&resource) && resource.compress()?;
+				Ok((resource, is_compressed.then_some(uncompressed_size)))
+			})?;
+			for (resource, uncompressed_size) in compressed {
+				if let Some(uncompressed_size) = uncompressed_size {
+					self.dir_items.push(DirItem { id: resource.id.clone(), uncompressed_size });
+				}
+				self.write_stored(&resource)?;
+			}
+		}
+		Ok(())
+	}
+
+	// writes the resource exactly as given; any DIR entry for it is up to the caller
+	pub fn write_stored(&mut self, resource: &Resource) -> Result<(), Box<dyn Error>> {
+		resource.write(&mut self.writer)?;
+		self.index_entries.push(IndexEntry::from_resource(resource, self.offset));
+		self.offset += resource.data.len() as u32;
+		Ok(())
+	}
+
+	// writes the DIR and index, then goes back to fill in the header
+	pub fn finish(mut self) -> Result<W, Box<dyn Error>> {
+		if !self.dir_items.is_empty() {
+			let dir = Dir::new(std::mem::take(&mut self.dir_items));
+			let use_tgir = self.header.index_minor_version >= 2;
+			self.write_stored(&Resource { id: dir.id.clone(), data: dir.to_bytes(use_tgir)? })?;
+			let dir_entry = self.index_entries.pop().unwrap();
+			self.index_entries.insert(self.dir_position.min(self.index_entries.len()), dir_entry);
+		}
+
+		let mut header = self.header;
+		header.index_entry_count = self.index_entries.len() as u32;
+		header.index_offset = self.offset;
+		header.index_size = (self.index_entries.len() * IndexEntry::size(&header)) as u32;
+		header.hole_entry_count = 0;
+		header.hole_offset = 0;
+		header.hole_size = 0;
+
+		for index_entry in &self.index_entries {
+			index_entry.write(&mut self.writer, header.index_minor_version >= 2)?;
+		}
+		let end = self.writer.stream_position()?;
+
+		self.writer.seek(SeekFrom::Start(self.start))?;
+		header.write(&mut self.writer)?;
+		self.writer.seek(SeekFrom::Start(end))?;
+		self.writer.flush()?;
+
+		Ok(self.writer)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+
+	use crate::dbpf::header::Header;
+	use crate::dbpf::index::DbpfIndex;
+	use crate::dbpf::index_entry::IndexEntry;
+	use crate::dbpf::writer::PackageWriter;
+	use crate::test_support::fixtures::{ binx_resource, gzps_resource, text_list_resource };
+
+	#[test]
+	fn written_packages_read_back() {
+		let resources = [(gzps_resource(), true), (binx_resource(), false), (text_list_resource(), false)];
+		let mut package_writer = PackageWriter::new(Cursor::new(Vec::new()), Header::default()).unwrap();
+		for (resource, compress) in resources.clone() {
+			package_writer.add(resource, compress).unwrap();
+		}
+		let bytes = package_writer.finish().unwrap().into_inner();
+
+		let mut index = DbpfIndex::new(Cursor::new(&bytes[..])).unwrap();
+		let header = &index.header;
+		assert_eq!(header.index_entry_count, 4);
+		assert_eq!(header.index_size, 4 * IndexEntry::size(header) as u32);
+		assert_eq!(header.index_offset + header.index_size, bytes.len() as u32);
+		assert_eq!((header.hole_entry_count, header.hole_offset, header.hole_size), (0, 0, 0));
+
+		// the DIR comes first in the index and lists only the compressed resource
+		assert!(index.all_entries()[0].id == index.dir_entry.as_ref().unwrap().id);
+		let dir_items = &index.dir.as_ref().unwrap().items;
+		assert_eq!(dir_items.len(), 1);
+		assert!(dir_items[0].id == gzps_resource().id && dir_items[0].uncompressed_size == gzps_resource().data.len() as u32);
+
+		let read = index.read_all().unwrap();
+		assert_eq!(read.len(), resources.len());
+		for (read, (resource, _)) in read.iter().zip(&resources) {
+			assert!(read.id == resource.id && read.data == resource.data);
+		}
+	}
+}
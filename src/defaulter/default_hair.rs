@@ -4,22 +4,33 @@ use std::path::PathBuf;
 
 use crate::dbpf::{ Dbpf, Identifier, TypeId };
 use crate::dbpf::resource::DecodedResource;
+use crate::dbpf::resource_index::ResourceIndex;
 use crate::dbpf::resource_types::gzps::{ Age, Gender, Category, HairTone };
+use crate::error::report_warnings;
 use crate::outfit::Outfit;
 
 use super::{ get_default_replacement_files, extract_resources, extract_gzps, default_output_path };
 
-pub fn default_hair(
-		source: Option<PathBuf>,
-		output: Option<PathBuf>,
-		add_ages: bool,
-		all_categories: bool,
-		visible: Option<bool>,
-		townified: Option<bool>,
-		hat: Option<bool>,
-		hide_pack_icon: bool,
-		same_family: bool
-	) -> Result<(), Box<dyn Error>> {
+#[derive(Clone, Default)]
+pub struct DefaultHairOptions {
+	// folder containing original hairs, and subfolder(s) containing replacements
+	pub source: Option<PathBuf>,
+	pub output: Option<PathBuf>,
+	// add ages from replacement hair, even if not included in original hair
+	pub add_ages: bool,
+	pub all_categories: bool,
+	pub visible: Option<bool>,
+	pub townified: Option<bool>,
+	pub hat: Option<bool>,
+	pub hide_pack_icon: bool,
+	// use first family value for all hairs
+	pub same_family: bool,
+	// keep going when resources fail to decode, and report them at the end
+	pub lenient: bool
+}
+
+pub fn default_hair(options: DefaultHairOptions) -> Result<(), Box<dyn Error>> {
+	let DefaultHairOptions { source, output, add_ages, all_categories, visible, townified, hat, hide_pack_icon, same_family, lenient } = options;
 
 	let source_dir = source.unwrap_or(PathBuf::from("./"));
 
@@ -28,20 +39,29 @@ pub fn default_hair(
 	let (original_files, replacement_files) = get_default_replacement_files(&source_dir)?;
 
 	// get all GZPS resources in original package(s)
-	let gzps_list = extract_gzps(&original_files)?;
+	let (gzps_list, mut warnings) = extract_gzps(&original_files, lenient)?;
 	let mut pairings: Vec<Option<usize>> = gzps_list.iter().map(|_| None).collect();
 	if gzps_list.is_empty() {
 		return Err("No GZPS resources found for original hairs".into());
 	}
 
 	// get all resources from replacement package(s)
-	let resources = extract_resources(&replacement_files)?;
+	let (resources, replacement_warnings) = extract_resources(&replacement_files, lenient)?;
+	warnings.extend(replacement_warnings);
 
 	// sort replacement resources into hairs
+	let index = ResourceIndex::new(&resources);
 	let mut replacement_hairs = Vec::new();
-	for resource in &resources {
+	for resource in index.of_type(TypeId::Gzps) {
 		if let DecodedResource::Gzps(gzps) = resource {
-			let mut hair = Outfit::from_resources(gzps.clone(), &resources, true)?;
+			let mut hair = match Outfit::from_index(gzps.clone(), &index, true) {
+				Ok(hair) => hair,
+				Err(err) if lenient => {
+					warnings.push(err.to_string());
+					continue;
+				}
+				Err(err) => return Err(err)
+			};
 			if hair.gzps.hairtone == HairTone::None {
 				hair.gzps.hairtone = HairTone::Other;
 			}
@@ -247,5 +267,7 @@ pub fn default_hair(
 	Dbpf::write_package_file(&all_resources, &output_path, true)?;
 	println!(" DONE");
 
+	report_warnings(&warnings);
+
 	Ok(())
 }
@@ -8,8 +8,10 @@ use cursive::views::{ Dialog, DialogFocus, Button, TextView, EditView, Checkbox,
 
 use crate::dbpf::{ Dbpf, Identifier, TypeId, PascalString };
 use crate::dbpf::resource::DecodedResource;
+use crate::dbpf::resource_index::ResourceIndex;
 use crate::dbpf::resource_types::gzps::{ Gzps, Age, Gender, Category };
 use crate::dbpf::resource_types::text_list::TextList;
+use crate::error::report_warnings;
 use crate::outfit::Outfit;
 
 use super::{ get_default_replacement_files, extract_resources, extract_gzps, default_output_path };
@@ -98,7 +100,19 @@ struct SivData {
 	pairings: Vec<Option<usize>>
 }
 
-pub fn default_outfit(source: Option<PathBuf>, auto: bool, hide_pack_icon: bool) -> Result<(), Box<dyn Error>> {
+#[derive(Clone, Default)]
+pub struct DefaultOutfitOptions {
+	// folder containing original outfits, and subfolder(s) containing replacements
+	pub source: Option<PathBuf>,
+	// make the default replacement without using the UI
+	pub auto: bool,
+	pub hide_pack_icon: bool,
+	// keep going when resources fail to decode, and report them at the end
+	pub lenient: bool
+}
+
+pub fn default_outfit(options: DefaultOutfitOptions) -> Result<(), Box<dyn Error>> {
+	let DefaultOutfitOptions { source, auto, hide_pack_icon, lenient } = options;
 	let source_dir = source.unwrap_or(PathBuf::from("./"));
 
 	print!("Reading files...");
@@ -108,17 +122,22 @@ pub fn default_outfit(source: Option<PathBuf>, auto: bool, hide_pack_icon: bool)
 	print!("Extracting resources...");
 
 	// get all GZPS resources in original package(s)
-	let gzps_list = extract_gzps(&original_files)?;
+	let (gzps_list, mut warnings) = extract_gzps(&original_files, lenient)?;
 
 	// get all resources from replacement package(s)
-	let resources = extract_resources(&replacement_files)?;
+	let (resources, replacement_warnings) = extract_resources(&replacement_files, lenient)?;
+	warnings.extend(replacement_warnings);
 
 	// sort replacement resources into outfits
+	let index = ResourceIndex::new(&resources);
 	let mut outfits = Vec::new();
-	for resource in &resources {
+	for resource in index.of_type(TypeId::Gzps) {
 		if let DecodedResource::Gzps(gzps) = resource {
-			let outfit = Outfit::from_resources(gzps.clone(), &resources, true)?;
-			outfits.push(outfit);
+			match Outfit::from_index(gzps.clone(), &index, true) {
+				Ok(outfit) => outfits.push(outfit),
+				Err(err) if lenient => warnings.push(err.to_string()),
+				Err(err) => return Err(err)
+			}
 		}
 	}
 	outfits.sort_by_key(|o| o.title.clone());
@@ -173,11 +192,13 @@ pub fn default_outfit(source: Option<PathBuf>, auto: bool, hide_pack_icon: bool)
 		save_extras(&data, &resources)?;
 		println!("DONE");
 
-		Ok(())
-
 	} else {
-		run_ui(data)
+		run_ui(data)?;
 	}
+
+	report_warnings(&warnings);
+
+	Ok(())
 }
 
 fn run_ui(data: SivData) -> Result<(), Box<dyn Error>> {
@@ -628,8 +649,9 @@ fn save_extras(data: &SivData, resources: &[DecodedResource]) -> Result<(), Box<
 	extra_resources.extend_from_slice(&text_list_resources);
 
 	// remove dupes
+	let index = ResourceIndex::new(resources);
 	extra_resources = extra_resources.into_iter()
-		.filter(|r| !resources.iter().any(|r2| r2.get_id() == r.get_id()))
+		.filter(|r| !index.contains(&r.get_id()))
 		.collect();
 
 	// save package
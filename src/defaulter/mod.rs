@@ -5,6 +5,7 @@ use std::path::{ Path, PathBuf };
 use crate::dbpf::Dbpf;
 use crate::dbpf::resource::DecodedResource;
 use crate::dbpf::resource_types::gzps::Gzps;
+use crate::parallel::try_par_map;
 
 pub mod default_outfit;
 pub mod default_hair;
@@ -30,23 +31,29 @@ pub fn get_default_replacement_files(source_dir: &Path) -> Result<(Vec<PathBuf>,
 	Ok((original_files, replacement_files))
 }
 
-pub fn extract_resources(files: &[PathBuf]) -> Result<Vec<DecodedResource>, Box<dyn Error>> {
-	let resources: Vec<DecodedResource> = files
-		.iter()
-		.map(|file| {
-			let new_name = file.file_stem().map_or("UNKNOWN".to_string(), |x| x.to_string_lossy().into_owned());
-			let dbpf = Dbpf::read_from_file(file, &new_name)?;
-			Ok(dbpf.resources)
-		})
-		.collect::<Result<Vec<Vec<DecodedResource>>, Box<dyn Error>>>()?
-		.into_iter()
-		.flatten()
-		.collect();
-	Ok(resources)
+// returns the decoded resources, and any warnings from decoding in lenient mode
+pub fn extract_resources(files: &[PathBuf], lenient: bool) -> Result<(Vec<DecodedResource>, Vec<String>), Box<dyn Error>> {
+	let packages = try_par_map(files, |file| {
+		let new_name = file.file_stem().map_or("UNKNOWN".to_string(), |x| x.to_string_lossy().into_owned());
+		if lenient {
+			Dbpf::read_from_file_lenient(file, &new_name)
+		} else {
+			Dbpf::read_from_file(file, &new_name)
+		}
+	})?;
+
+	let mut resources = Vec::new();
+	let mut warnings = Vec::new();
+	for dbpf in packages {
+		resources.extend(dbpf.resources);
+		warnings.extend(dbpf.warnings);
+	}
+	Ok((resources, warnings))
 }
 
-pub fn extract_gzps(files: &[PathBuf]) -> Result<Vec<Gzps>, Box<dyn Error>> {
-	let mut gzps_list = extract_resources(files)?
+pub fn extract_gzps(files: &[PathBuf], lenient: bool) -> Result<(Vec<Gzps>, Vec<String>), Box<dyn Error>> {
+	let (resources, warnings) = extract_resources(files, lenient)?;
+	let mut gzps_list = resources
 		.iter()
 		.filter_map(|res|
 			if let DecodedResource::Gzps(gzps) = res {
@@ -56,7 +63,7 @@ pub fn extract_gzps(files: &[PathBuf]) -> Result<Vec<Gzps>, Box<dyn Error>> {
 			})
 		.collect::<Vec<_>>();
 	gzps_list.sort_by_key(|gzps| gzps.name.to_string());
-	Ok(gzps_list)
+	Ok((gzps_list, warnings))
 }
 
 pub fn default_output_path(source_dir: &Path, suffix: &str) -> PathBuf {
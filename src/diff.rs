@@ -0,0 +1,316 @@
+use std::collections::hash_map::DefaultHasher;
+use std::error::Error;
+use std::hash::{ Hash, Hasher };
+use std::path::PathBuf;
+
+use crate::dbpf::{ Dbpf, Identifier };
+use crate::dbpf::resource::DecodedResource;
+use crate::dbpf::resource_index::ResourceIndex;
+use crate::dbpf::resource_types::txtr::{ TxtrData, TxtrPurpose };
+use crate::dbpf::resource_types::rcol::RcolBlock;
+use crate::dbpf::resource_types::nodes::transform::TransformNode;
+
+pub fn diff_packages(a: PathBuf, b: PathBuf) -> Result<(), Box<dyn Error>> {
+	let package_a = Dbpf::read_from_file(&a, "")?;
+	let package_b = Dbpf::read_from_file(&b, "")?;
+	let index_a = ResourceIndex::new(&package_a.resources);
+	let index_b = ResourceIndex::new(&package_b.resources);
+
+	let mut added = 0;
+	let mut removed = 0;
+	let mut changed = 0;
+	let mut unchanged = 0;
+
+	for resource_a in &package_a.resources {
+		let id = resource_a.get_id();
+		match index_b.get(&id) {
+			None => {
+				println!("- {id}");
+				removed += 1;
+			}
+			Some(resource_b) => {
+				if resource_a.to_bytes()? == resource_b.to_bytes()? {
+					unchanged += 1;
+					continue;
+				}
+				println!("~ {id}");
+				let differences = diff_fields(&fields(resource_a), &fields(resource_b));
+				if differences.is_empty() {
+					println!("    (binary difference only)");
+				}
+				for difference in differences {
+					println!("    {difference}");
+				}
+				changed += 1;
+			}
+		}
+	}
+
+	for resource_b in &package_b.resources {
+		let id = resource_b.get_id();
+		if !index_a.contains(&id) {
+			println!("+ {id}");
+			added += 1;
+		}
+	}
+
+	println!("{added} added, {removed} removed, {changed} changed, {unchanged} unchanged");
+
+	Ok(())
+}
+
+fn diff_fields(a: &[(String, String)], b: &[(String, String)]) -> Vec<String> {
+	let mut differences = Vec::new();
+	for (key, value_a) in a {
+		match b.iter().find(|(k, _)| k == key) {
+			Some((_, value_b)) if value_a != value_b => differences.push(format!("{key}: {value_a} -> {value_b}")),
+			Some(_) => {}
+			None => differences.push(format!("{key}: {value_a} -> (none)"))
+		}
+	}
+	for (key, value_b) in b {
+		if !a.iter().any(|(k, _)| k == key) {
+			differences.push(format!("{key}: (none) -> {value_b}"));
+		}
+	}
+	differences
+}
+
+fn blob(data: &[u8]) -> String {
+	let mut hasher = DefaultHasher::new();
+	data.hash(&mut hasher);
+	format!("{} bytes, hash {:016x}", data.len(), hasher.finish())
+}
+
+fn transform_summary(transform: &TransformNode) -> String {
+	format!("{}, joint {}, children {:?}, translation {:?}, rotation {:?}",
+		transform.name().unwrap_or_default(),
+		transform.joint_id,
+		transform.children.iter().map(|(_, _, child_index)| child_index).collect::<Vec<&u32>>(),
+		transform.translation,
+		transform.rotation)
+}
+
+fn optional_id(id: &Option<Identifier>) -> String {
+	id.as_ref().map_or("(none)".to_string(), |id| id.to_string())
+}
+
+// flattens a decoded resource into named fields, with list items keyed by index or name
+fn fields(decoded: &DecodedResource) -> Vec<(String, String)> {
+	let mut fields: Vec<(String, String)> = Vec::new();
+	let mut add = |key: &str, value: String| fields.push((key.to_string(), value));
+
+	match decoded {
+		DecodedResource::Gzps(gzps) => {
+			add("version", format!("{:?}", gzps.version));
+			add("product", format!("{:?}", gzps.product));
+			add("age", format!("{:?}", gzps.ages));
+			add("gender", format!("{:?}", gzps.genders));
+			add("species", gzps.species.to_string());
+			add("outfit", format!("{:?}", gzps.outfit));
+			add("parts", format!("{:?}", gzps.parts));
+			add("flags", format!("0x{:x}", gzps.flags));
+			add("name", gzps.name.to_string());
+			add("creator", gzps.creator.to_string());
+			add("family", gzps.family.to_string());
+			add("genetic", format!("{:?}", gzps.genetic));
+			add("priority", format!("{:?}", gzps.priority));
+			add("type", gzps.outfit_type.to_string());
+			add("skintone", gzps.skintone.to_string());
+			add("hairtone", format!("{:?}", gzps.hairtone));
+			add("category", format!("{:?}", gzps.categories));
+			add("shoe", format!("{:?}", gzps.shoe));
+			add("fitness", gzps.fitness.to_string());
+			add("resourcekeyidx", gzps.resource.to_string());
+			add("shapekeyidx", gzps.shape.to_string());
+			for (i, o) in gzps.overrides.iter().enumerate() {
+				add(&format!("override{i}shape"), o.shape.to_string());
+				add(&format!("override{i}subset"), o.subset.to_string());
+				add(&format!("override{i}resourcekeyidx"), o.resource.to_string());
+			}
+		}
+		DecodedResource::Xtol(xtol) => {
+			add("version", format!("{:?}", xtol.version));
+			add("product", format!("{:?}", xtol.product));
+			add("type", xtol.xtol_type.to_string());
+			add("subtype", xtol.subtype.to_string());
+			add("name", xtol.name.to_string());
+			add("creator", xtol.creator.to_string());
+			add("family", xtol.family.to_string());
+			add("age", format!("{:?}", xtol.age));
+			add("gender", format!("{:?}", xtol.gender));
+			add("species", xtol.species.to_string());
+			add("category", format!("{:?}", xtol.category));
+			add("skintone", xtol.skintone.to_string());
+			add("hairtone", format!("{:?}", xtol.hairtone));
+			add("genetic", xtol.genetic.to_string());
+			add("flags", format!("0x{:x}", xtol.flags));
+			add("bin", xtol.bin.to_string());
+			add("layer", xtol.layer.to_string());
+			add("materialkeyidx", format!("{:?}", xtol.materialkey));
+			add("materialid", format!("{:?}", xtol.material));
+			add("materialgroupid", format!("{:?}", xtol.materialgroup));
+			add("materialrestypeid", format!("{:?}", xtol.materialrestype));
+		}
+		DecodedResource::Idr(idr) => {
+			add("cres", optional_id(&idr.cres_ref));
+			add("shpe", optional_id(&idr.shpe_ref));
+			for (i, txmt_ref) in idr.txmt_refs.iter().enumerate() {
+				add(&format!("txmt[{i}]"), txmt_ref.to_string());
+			}
+			add("ui", optional_id(&idr.ui_ref));
+			add("str", optional_id(&idr.str_ref));
+			add("coll", optional_id(&idr.coll_ref));
+			add("gzps", optional_id(&idr.gzps_ref));
+		}
+		DecodedResource::Txmt(txmt) => {
+			add("version", txmt.block.version.to_string());
+			add("material_definition", txmt.block.material_definition.to_string());
+			add("material_description", txmt.block.material_description.to_string());
+			add("material_type", txmt.block.material_type.to_string());
+			for prop in &txmt.block.properties {
+				add(&prop.name.to_string(), prop.value.to_string());
+			}
+			for (i, texture_name) in txmt.block.texture_names.iter().enumerate() {
+				add(&format!("texture_names[{i}]"), texture_name.to_string());
+			}
+		}
+		DecodedResource::Txtr(txtr) => {
+			add("version", txtr.block.version.to_string());
+			add("file_name", txtr.block.file_name.to_string());
+			add("width", txtr.block.width.to_string());
+			add("height", txtr.block.height.to_string());
+			add("format", format!("{:?}", txtr.block.format));
+			add("mipmap_count", txtr.block.mipmap_count.to_string());
+			add("purpose", format!("{} ({:?})", txtr.block.purpose, TxtrPurpose::from_flag(txtr.block.purpose)));
+			for (i, group) in txtr.block.image_groups.iter().enumerate() {
+				add(&format!("image_groups[{i}].creator_id"), format!("0x{:08x}", group.creator_id));
+				add(&format!("image_groups[{i}].format_flag"), format!("0x{:08x}", group.format_flag));
+				for (j, image) in group.images.iter().enumerate() {
+					add(&format!("image_groups[{i}].images[{j}]"), match image {
+						TxtrData::Image(data) => blob(data),
+						TxtrData::Lifo(name) => format!("LIFO {name}")
+					});
+				}
+			}
+		}
+		DecodedResource::Shpe(shpe) => {
+			add("version", shpe.block.version.to_string());
+			add("file_name", shpe.block.file_name.to_string());
+			add("lod_values", format!("{:?}", shpe.block.lod_values));
+			for (i, item) in shpe.block.gmnd_items.iter().enumerate() {
+				add(&format!("gmnd_items[{i}]"), format!("type {}, enabled {}, {}", item.item_type, item.enabled, item.name));
+			}
+			for (i, material) in shpe.block.materials.iter().enumerate() {
+				add(&format!("materials[{i}].subset"), material.subset.to_string());
+				add(&format!("materials[{i}].txmt_name"), material.txmt_name.to_string());
+			}
+		}
+		DecodedResource::Mmat(mmat) => {
+			add("flags", format!("0x{:x}", mmat.flags));
+			add("name", mmat.name.to_string());
+			add("copyright", format!("{:?}", mmat.copyright));
+			add("creator", mmat.creator.to_string());
+			add("type", mmat.material_type.to_string());
+			add("objectGUID", format!("0x{:08x}", mmat.object_guid));
+			add("modelName", mmat.model_name.to_string());
+			add("materialStateFlags", format!("0x{:x}", mmat.material_state_flags));
+			add("objectStateIndex", mmat.object_state_index.to_string());
+			add("family", mmat.family.to_string());
+			add("subsetName", mmat.subset_name.to_string());
+			add("defaultMaterial", mmat.default_material.to_string());
+		}
+		DecodedResource::Binx(binx) => {
+			add("iconidx", binx.icon_idx.to_string());
+			add("stringsetidx", binx.stringset_idx.to_string());
+			add("binidx", binx.bin_idx.to_string());
+			add("objectidx", binx.object_idx.to_string());
+			add("creatorid", binx.creator_id.to_string());
+			add("sortindex", binx.sort_index.to_string());
+			add("stringindex", binx.string_index.to_string());
+		}
+		DecodedResource::TextList(text_list) => {
+			for (i, string) in text_list.strings.iter().enumerate() {
+				add(&format!("strings[{i}].language"), string.language_code.to_string());
+				add(&format!("strings[{i}].title"), string.title.clone());
+				add(&format!("strings[{i}].description"), string.description.clone());
+			}
+		}
+		DecodedResource::Gmnd(gmnd) => {
+			add("gmdc", gmnd.gmdc_ref.to_string());
+			add("data", blob(&gmnd.data));
+		}
+		DecodedResource::Gmdc(gmdc) => {
+			add("version", gmdc.block.version.to_string());
+			add("file_name", gmdc.block.file_name.to_string());
+			for (i, element) in gmdc.block.elements.iter().enumerate() {
+				add(&format!("elements[{i}]"), format!("{}, {} values", element.identity, element.data.len()));
+			}
+			for (i, group) in gmdc.block.groups.iter().enumerate() {
+				add(&format!("groups[{i}].name"), group.name.to_string());
+				add(&format!("groups[{i}].faces"), group.faces.len().to_string());
+				add(&format!("groups[{i}].used_joints"), format!("{:?}", group.used_joints));
+			}
+			add("transforms", gmdc.block.model.transforms.len().to_string());
+			add("joints", gmdc.block.joints.len().to_string());
+		}
+		DecodedResource::Cres(cres) => {
+			for (i, link) in cres.links.iter().enumerate() {
+				add(&format!("links[{i}]"), link.to_string());
+			}
+			for (i, block) in cres.blocks.iter().enumerate() {
+				let key = format!("blocks[{i}]");
+				match block {
+					RcolBlock::Cres(cres_block) => {
+						add(&format!("{key}.file_name"), cres_block.file_name.to_string());
+						add(&format!("{key}.chains"), format!("{:?}", cres_block.chains));
+						add(&format!("{key}.purpose"), cres_block.purpose.to_string());
+					}
+					RcolBlock::Transform(transform) => add(&key, transform_summary(transform)),
+					RcolBlock::ShapeRef(shape_ref) => {
+						add(&key, transform_summary(&shape_ref.transform));
+						add(&format!("{key}.name"), shape_ref.name.to_string());
+						add(&format!("{key}.shapes"), format!("{:?}", shape_ref.shapes));
+						add(&format!("{key}.blends"), format!("{:?}", shape_ref.blends));
+						add(&format!("{key}.blend_data"), blob(&shape_ref.blend_data));
+					}
+					RcolBlock::DataList(data_list) => {
+						for item in &data_list.extension.items {
+							add(&format!("{key}.{}", item.name), format!("{:?}", item.value));
+						}
+					}
+					RcolBlock::BoneData(bone_data) => {
+						for item in &bone_data.extension.items {
+							add(&format!("{key}.{}", item.name), format!("{:?}", item.value));
+						}
+						add(&format!("{key}.rotation"), format!("{:?}", bone_data.rotation));
+					}
+					_ => add(&key, "(other)".to_string())
+				}
+			}
+		}
+		DecodedResource::Other(resource) => add("data", blob(&resource.data))
+	}
+
+	fields
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::dbpf::SevenBitString;
+	use crate::dbpf::resource::DecodedResource;
+	use crate::diff::{ diff_fields, fields };
+	use crate::test_support::assert_round_trip;
+	use crate::test_support::fixtures::txmt_resource;
+
+	#[test]
+	fn diff_names_changed_and_missing_fields() {
+		let before = assert_round_trip(&txmt_resource());
+		let DecodedResource::Txmt(mut txmt) = before.clone() else { unreachable!() };
+		txmt.block.material_type = SevenBitString::new("Changed");
+		txmt.block.texture_names.clear();
+		let differences = diff_fields(&fields(&before), &fields(&DecodedResource::Txmt(txmt)));
+		assert!(differences.iter().any(|d| d.starts_with("material_type: ") && d.ends_with("-> Changed")), "{differences:?}");
+		assert!(differences.iter().any(|d| d.starts_with("texture_names[0]: ") && d.ends_with("-> (none)")), "{differences:?}");
+	}
+}
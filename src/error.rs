@@ -0,0 +1,187 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{ Path, PathBuf };
+
+use crate::dbpf::{ Identifier, TypeId };
+
+#[derive(Debug)]
+pub enum ClodError {
+	// a file couldn't be read or written
+	Io {
+		path: PathBuf,
+		source: io::Error
+	},
+	// the package header, index or resource table couldn't be read
+	Parse {
+		path: Option<PathBuf>,
+		offset: u64,
+		message: String
+	},
+	// a resource was read but its contents couldn't be decoded. offset is where the resource starts in
+	// the package, position is the byte of its (uncompressed) data the decoder stopped at
+	Decode {
+		path: Option<PathBuf>,
+		id: Identifier,
+		offset: Option<u32>,
+		position: Option<u64>,
+		source: Box<dyn Error + Send + Sync>
+	},
+	// a decoder failed at position bytes into the data it was reading, before the resource is known
+	Data {
+		position: u64,
+		source: Box<dyn Error + Send + Sync>
+	},
+	// a resource references another that isn't there; id is None when the reference itself is missing
+	MissingReference {
+		from: Identifier,
+		type_id: TypeId,
+		id: Option<Identifier>
+	}
+}
+
+impl ClodError {
+	pub fn io(path: &Path, source: io::Error) -> Self {
+		Self::Io { path: path.to_path_buf(), source }
+	}
+
+	pub fn parse(offset: u64, message: impl fmt::Display) -> Self {
+		Self::Parse { path: None, offset, message: message.to_string() }
+	}
+
+	pub fn decode(id: &Identifier, source: Box<dyn Error>) -> Self {
+		match source.downcast::<Self>() {
+			Ok(err) => match *err {
+				Self::Data { position, source } => Self::Decode { path: None, id: id.clone(), offset: None, position: Some(position), source },
+				err => Self::Decode { path: None, id: id.clone(), offset: None, position: None, source: err.into() }
+			},
+			Err(source) => Self::Decode { path: None, id: id.clone(), offset: None, position: None, source: sendable(source) }
+		}
+	}
+
+	// errors of nested decoders keep the innermost position
+	pub fn at(position: u64, source: Box<dyn Error>) -> Box<dyn Error> {
+		match source.downcast::<Self>() {
+			Ok(err) if matches!(*err, Self::Data { .. }) => err,
+			Ok(err) => Self::Data { position, source: err }.into(),
+			Err(source) => Self::Data { position, source: sendable(source) }.into()
+		}
+	}
+
+	pub fn missing(from: &Identifier, type_id: TypeId, id: Option<&Identifier>) -> Self {
+		Self::MissingReference { from: from.clone(), type_id, id: id.cloned() }
+	}
+
+	// fills in the package path and resource offset of an error that doesn't have them yet
+	pub fn locate(err: Box<dyn Error>, file: Option<&Path>, resource_offset: Option<u32>) -> Box<dyn Error> {
+		let err = match err.downcast::<Self>() {
+			Ok(clod_err) => clod_err,
+			Err(err) => match (err.downcast::<io::Error>(), file) {
+				(Ok(source), Some(file)) => return Self::io(file, *source).into(),
+				(Ok(source), None) => return source,
+				(Err(err), _) => return err
+			}
+		};
+		match *err {
+			Self::Parse { path: None, offset, message } => Self::Parse { path: file.map(Path::to_path_buf), offset, message }.into(),
+			Self::Decode { path, id, offset, position, source } => Self::Decode {
+				path: path.or(file.map(Path::to_path_buf)),
+				id,
+				offset: offset.or(resource_offset),
+				position,
+				source
+			}.into(),
+			err => err.into()
+		}
+	}
+}
+
+// makes an error safe to send between threads, keeping its type where it's one of ours, io or binrw.
+// anything else only keeps its message
+pub fn sendable(err: Box<dyn Error>) -> Box<dyn Error + Send + Sync> {
+	let err = match err.downcast::<ClodError>() {
+		Ok(err) => return err,
+		Err(err) => err
+	};
+	let err = match err.downcast::<io::Error>() {
+		Ok(err) => return err,
+		Err(err) => err
+	};
+	match err.downcast::<binrw::Error>() {
+		Ok(err) => err,
+		Err(err) => err.to_string().into()
+	}
+}
+
+// prints the problems collected while decoding in lenient mode
+pub fn report_warnings(warnings: &[String]) {
+	if warnings.is_empty() {
+		return;
+	}
+	println!();
+	println!("{} problems found:", warnings.len());
+	for warning in warnings {
+		println!("WARNING: {warning}");
+	}
+}
+
+impl fmt::Display for ClodError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Io { path, source } => write!(f, "{}: {source}", path.to_string_lossy()),
+			Self::Parse { path, offset, message } => {
+				if let Some(path) = path {
+					write!(f, "{}: ", path.to_string_lossy())?;
+				}
+				write!(f, "{message} (at offset 0x{offset:X})")
+			}
+			Self::Decode { path, id, offset, position, source } => {
+				if let Some(path) = path {
+					write!(f, "{}: ", path.to_string_lossy())?;
+				}
+				write!(f, "failed to decode {id}")?;
+				match (offset, position) {
+					(Some(offset), Some(position)) => write!(f, " (at offset 0x{offset:X}, byte 0x{position:X} of the resource)")?,
+					(Some(offset), None) => write!(f, " (at offset 0x{offset:X})")?,
+					(None, Some(position)) => write!(f, " (at byte 0x{position:X} of the resource)")?,
+					(None, None) => {}
+				}
+				write!(f, ": {source}")
+			}
+			Self::Data { position, source } => write!(f, "{source} (at byte 0x{position:X})"),
+			Self::MissingReference { from, id: Some(id), .. } => write!(f, "{from}: missing {id}"),
+			Self::MissingReference { from, type_id, id: None } => write!(f, "{from}: no {type_id} reference")
+		}
+	}
+}
+
+impl Error for ClodError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			Self::Io { source, .. } => Some(source),
+			Self::Decode { source, .. } | Self::Data { source, .. } => Some(source.as_ref()),
+			_ => None
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::dbpf::resource::DecodedResource;
+	use crate::error::ClodError;
+	use crate::test_support::fixtures::txmt_resource;
+
+	#[test]
+	fn decode_errors_report_the_failing_byte() {
+		let mut resource = txmt_resource();
+		resource.data.truncate(resource.data.len() - 10);
+		let err = DecodedResource::new(&resource, "test").err().unwrap();
+		match err.downcast_ref::<ClodError>() {
+			Some(ClodError::Decode { id, position: Some(position), .. }) => {
+				assert_eq!(*id, resource.id);
+				assert!(*position > 0 && *position <= resource.data.len() as u64, "{position}");
+			}
+			_ => panic!("no decode error with a position: {err}")
+		}
+	}
+}
@@ -3,7 +3,9 @@ use std::fs;
 use std::fs::DirEntry;
 use std::path::{ Path, PathBuf };
 
-use crate::dbpf::Dbpf;
+use crate::dbpf::{ Dbpf, Identifier, TypeId };
+use crate::dbpf::index::DbpfIndex;
+use crate::parallel::try_par_map;
 
 pub mod extract_outfits;
 pub mod extract_hairs;
@@ -19,15 +21,37 @@ pub fn get_skin_packages(path: &Path) -> Result<Vec<Dbpf>, Box<dyn Error>> {
 
 	dir_entries.sort_by_key(|entry| entry.file_name().to_string_lossy().into_owned());
 
-	let mut packages = Vec::new();
+	let package_paths = dir_entries.iter()
+		.map(|entry| entry.path())
+		.filter(|path| path.is_file() && path.extension().is_some_and(|e| e == "package"))
+		.collect::<Vec<PathBuf>>();
 
-	for dir_entry in dir_entries {
-		let entry_path = dir_entry.path();
-		if entry_path.is_file() && entry_path.extension().is_some_and(|e| e == "package") {
-			let dbpf = Dbpf::read_from_file(&entry_path, &entry_path.file_stem().unwrap().to_string_lossy())?;
-			packages.push(dbpf);
+	let packages = try_par_map(package_paths, |entry_path| {
+		let title = entry_path.file_stem().unwrap().to_string_lossy().into_owned();
+		let mut index = DbpfIndex::open(&entry_path)?;
+
+		// only decode GZPS/XTOL resources and the 3IDRs that go with them
+		let mut index_entries = index.find_by_type(TypeId::Gzps);
+		index_entries.extend(index.find_by_type(TypeId::Xtol));
+		let index_entries: Vec<_> = index_entries.into_iter().cloned().collect();
+
+		let mut resources = Vec::new();
+		for index_entry in index_entries {
+			resources.push(index.decode_entry(&index_entry, &title)?);
+			let idr_id = Identifier { type_id: TypeId::Idr, ..index_entry.id };
+			if index.find(&idr_id).is_some() {
+				resources.push(index.decode_resource(&idr_id, &title)?);
+			}
 		}
-	}
+
+		Ok(Dbpf {
+			header: index.header.clone(),
+			resources,
+			is_compressed: index.is_compressed(),
+			layout: None,
+			warnings: Vec::new()
+		})
+	})?;
 
 	Ok(packages)
 }
@@ -0,0 +1,18 @@
+use std::collections::HashSet;
+
+// replaces anything that isn't safe in a file name
+pub fn sanitize(name: &str) -> String {
+	name.trim().chars().map(|c| if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' { c } else { '_' }).collect()
+}
+
+// numbers names that were already used, ignoring case
+pub fn unique_name(used_names: &mut HashSet<String>, name: &str) -> String {
+	let mut unique = name.to_string();
+	let mut i = 2;
+	while used_names.contains(&unique.to_lowercase()) {
+		unique = format!("{name}_{i}");
+		i += 1;
+	}
+	used_names.insert(unique.to_lowercase());
+	unique
+}
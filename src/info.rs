@@ -0,0 +1,164 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::dbpf::TypeId;
+use crate::dbpf::index::DbpfIndex;
+use crate::dbpf::resource::DecodedResource;
+use crate::dbpf::resource_types::gzps::{ Age, Gender, Category };
+use crate::mesh::TRIANGLES;
+
+#[derive(Serialize)]
+struct PackageInfo {
+	file: String,
+	major_version: u32,
+	minor_version: u32,
+	index_major_version: u32,
+	index_minor_version: u32,
+	date_created: u32,
+	date_modified: u32,
+	entries: Vec<EntryInfo>
+}
+
+#[derive(Serialize)]
+struct EntryInfo {
+	type_name: String,
+	type_id: String,
+	group_id: String,
+	resource_id: String,
+	instance_id: String,
+	offset: u32,
+	size: u32,
+	uncompressed_size: Option<u32>,
+	summary: String
+}
+
+pub fn package_info(file: PathBuf, json: bool) -> Result<(), Box<dyn Error>> {
+	let title = file.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+	let mut index = DbpfIndex::open(&file)?;
+
+	let index_entries = index.all_entries().into_iter().cloned().collect::<Vec<_>>();
+	let mut entries = Vec::new();
+	for index_entry in index_entries {
+		let id = &index_entry.id;
+		let uncompressed_size = index.dir.as_ref().and_then(|dir| dir.uncompressed_size(id));
+
+		let summary = if id.type_id == TypeId::Dir {
+			format!("{} compressed resources", index.dir.as_ref().map_or(0, |dir| dir.items.len()))
+		} else {
+			match index.read_entry(&index_entry).and_then(|resource| resource.decode(&title)) {
+				Ok(decoded) => summarize(&decoded),
+				Err(err) => err.to_string()
+			}
+		};
+
+		entries.push(EntryInfo {
+			type_name: id.type_id.to_string(),
+			type_id: format!("0x{:08X}", u32::from(id.type_id)),
+			group_id: format!("0x{:08X}", id.group_id),
+			resource_id: format!("0x{:08X}", id.resource_id),
+			instance_id: format!("0x{:08X}", id.instance_id),
+			offset: index_entry.resource_offset,
+			size: index_entry.resource_size,
+			uncompressed_size,
+			summary
+		});
+	}
+
+	let header = &index.header;
+	let info = PackageInfo {
+		file: file.to_string_lossy().into_owned(),
+		major_version: header.major_version,
+		minor_version: header.minor_version,
+		index_major_version: header.index_major_version,
+		index_minor_version: header.index_minor_version,
+		date_created: header.date_created,
+		date_modified: header.date_modified,
+		entries
+	};
+
+	if json {
+		println!("{}", serde_json::to_string_pretty(&info)?);
+	} else {
+		print_info(&info);
+	}
+
+	Ok(())
+}
+
+fn print_info(info: &PackageInfo) {
+	println!("{}", info.file);
+	println!("DBPF version {}.{}, index version {}.{}, {} entries",
+		info.major_version, info.minor_version,
+		info.index_major_version, info.index_minor_version,
+		info.entries.len());
+	println!();
+	println!("{:<20} {:<26} {:>10} {:>10} {:>12}  SUMMARY", "TYPE", "GROUP-RESOURCE-INSTANCE", "OFFSET", "SIZE", "UNCOMPRESSED");
+	for entry in &info.entries {
+		let tgir = format!("{}-{}-{}",
+			entry.group_id.trim_start_matches("0x"),
+			entry.resource_id.trim_start_matches("0x"),
+			entry.instance_id.trim_start_matches("0x"));
+		let uncompressed_size = entry.uncompressed_size.map_or("-".to_string(), |size| size.to_string());
+		println!("{:<20} {:<26} {:>10} {:>10} {:>12}  {}", entry.type_name, tgir, entry.offset, entry.size, uncompressed_size, entry.summary);
+	}
+}
+
+// one-line description of the interesting parts of a decoded resource
+fn summarize(decoded: &DecodedResource) -> String {
+	match decoded {
+		DecodedResource::Gzps(gzps) => {
+			format!("\"{}\" age: {}, gender: {}, category: {}",
+				gzps.name,
+				Age::stringify(&gzps.ages, false, false),
+				Gender::stringify(&gzps.genders),
+				Category::stringify(&gzps.categories))
+		}
+		DecodedResource::Xtol(xtol) => {
+			format!("\"{}\" {}, age: {}, gender: {}, category: {}",
+				xtol.name,
+				xtol.xtol_type,
+				Age::stringify(&xtol.age, false, false),
+				Gender::stringify(&xtol.gender),
+				Category::stringify(&xtol.category))
+		}
+		DecodedResource::Txmt(txmt) => {
+			format!("{} ({})", txmt.block.material_type, txmt.block.material_definition)
+		}
+		DecodedResource::Txtr(txtr) => {
+			format!("{}x{} {:?}, {} mipmaps ({})", txtr.block.width, txtr.block.height, txtr.block.format, txtr.block.mipmap_count, txtr.block.file_name)
+		}
+		DecodedResource::Shpe(shpe) => {
+			let subsets = shpe.block.materials.iter().map(|m| m.subset.to_string()).collect::<Vec<String>>();
+			format!("subsets: {}", subsets.join(", "))
+		}
+		DecodedResource::Mmat(mmat) => {
+			format!("subset: {}, material: {}{}", mmat.subset_name, mmat.name, if mmat.default_material { " (default)" } else { "" })
+		}
+		DecodedResource::Idr(idr) => {
+			format!("{} material references", idr.txmt_refs.len())
+		}
+		DecodedResource::TextList(text_list) => {
+			text_list.strings.first().map_or(String::new(), |s| format!("\"{}\"", s.title))
+		}
+		DecodedResource::Binx(binx) => {
+			format!("sort index: {}", binx.sort_index)
+		}
+		DecodedResource::Gmnd(gmnd) => {
+			format!("geometry: {}", gmnd.gmdc_ref)
+		}
+		DecodedResource::Gmdc(gmdc) => {
+			let faces = gmdc.block.groups.iter()
+				.filter(|group| group.primitive_type == TRIANGLES)
+				.map(|group| group.faces.len() / 3)
+				.sum::<usize>();
+			format!("{} groups, {faces} faces ({})", gmdc.block.groups.len(), gmdc.block.file_name)
+		}
+		DecodedResource::Cres(cres) => {
+			let joints = cres.transforms().filter(|transform| transform.is_joint()).count();
+			format!("{} nodes, {joints} joints", cres.blocks.len())
+		}
+		DecodedResource::Other(_) => String::new()
+	}
+}
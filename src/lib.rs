@@ -0,0 +1,28 @@
+pub mod crc;
+pub mod dbpf;
+pub mod error;
+pub mod backup;
+pub mod parallel;
+pub mod file_names;
+pub mod outfit;
+pub mod defaulter;
+pub mod extractor;
+pub mod compressor;
+pub mod info;
+pub mod unpacker;
+pub mod merge;
+pub mod split;
+pub mod diff;
+pub mod conflicts;
+pub mod check_links;
+pub mod bulk_edit;
+pub mod recolor;
+pub mod mesh;
+
+#[cfg(test)]
+mod test_support;
+
+pub use dbpf::{ Dbpf, Identifier, TypeId };
+pub use dbpf::resource::{ Resource, DecodedResource };
+pub use dbpf::resource_index::ResourceIndex;
+pub use outfit::Outfit;
@@ -1,22 +1,28 @@
 use std::error::Error;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
 use clap::{ Parser, Subcommand };
 
-mod crc;
-mod dbpf;
-mod outfit;
-mod defaulter;
-mod extractor;
-mod compressor;
-mod bulk_edit;
-mod recolor;
+use clod::{ defaulter, extractor, compressor, info, unpacker, merge, split, diff, conflicts, check_links, bulk_edit, recolor, mesh, parallel };
+use clod::backup::{ Backup, BackupPolicy };
+use clod::dbpf::TypeId;
+use clod::dbpf::compression::CompressionPolicy;
+use clod::dbpf::format::PackageFormat;
+use clod::mesh::MeshFormat;
+use clod::defaulter::default_outfit::DefaultOutfitOptions;
+use clod::defaulter::default_hair::DefaultHairOptions;
+use clod::recolor::recolor_outfit::{ TemplateRecolorOptions, MeshRecolorOptions };
+use clod::recolor::recolor_object::ObjectRecolorOptions;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Args {
 	#[command(subcommand)]
-	command: Option<Command>
+	command: Option<Command>,
+	/// Number of threads to decode and compress resources with (default: one per core)
+	#[arg(long, global = true, value_name = "N", default_value_t = 0, hide_default_value = true)]
+	threads: usize
 }
 
 #[derive(Subcommand)]
@@ -30,7 +36,10 @@ enum Command {
 		auto: bool,
 		/// Hide pack icon in auto mode
 		#[arg(short = 'p', long)]
-		hide_pack_icon: bool
+		hide_pack_icon: bool,
+		/// Keep resources that fail to decode as-is and list them at the end
+		#[arg(long)]
+		lenient: bool
 	},
 	/// Generates a default replacement for a TS2 outfit
 	DefaultHair {
@@ -59,7 +68,10 @@ enum Command {
 		same_family: bool,
 		/// Hide pack icon
 		#[arg(short = 'p', long)]
-		hide_pack_icon: bool
+		hide_pack_icon: bool,
+		/// Keep resources that fail to decode as-is and list them at the end
+		#[arg(long)]
+		lenient: bool
 	},
 	/// Extracts outfits from game files for use in default replacements
 	ExtractOutfits {
@@ -94,12 +106,166 @@ enum Command {
 		property: String,
 		/// New GZPS property value
 		#[arg(short, long)]
-		value: String
+		value: String,
+		/// Keep the original header, resource order and compressed data of untouched resources
+		#[arg(short = 'P', long)]
+		preserve: bool,
+		/// Copy to keep of each original file
+		#[arg(long, value_enum, default_value_t = BackupPolicy::Single)]
+		backup: BackupPolicy,
+		/// Folder to keep copies in with --backup dir (default: "backup" next to each file)
+		#[arg(long, value_name="FOLDER")]
+		backup_dir: Option<PathBuf>
 	},
 	/// Compresses resources in package files
 	Compress {
 		/// List of package files to compress
-		files: Vec<PathBuf>
+		files: Vec<PathBuf>,
+		/// Resource types to leave uncompressed, separated by "," (eg. "TXTR,GMDC")
+		#[arg(short, long, value_name="TYPES", value_delimiter = ',')]
+		skip: Vec<TypeId>,
+		/// Leave resources smaller than this many bytes uncompressed
+		#[arg(short, long, value_name="BYTES", default_value_t = 0)]
+		min_size: usize,
+		/// Copy to keep of each original file
+		#[arg(long, value_enum, default_value_t = BackupPolicy::Single)]
+		backup: BackupPolicy,
+		/// Folder to keep copies in with --backup dir (default: "backup" next to each file)
+		#[arg(long, value_name="FOLDER")]
+		backup_dir: Option<PathBuf>
+	},
+	/// Decompresses all resources in package files
+	Decompress {
+		/// List of package files to decompress
+		files: Vec<PathBuf>,
+		/// Copy to keep of each original file
+		#[arg(long, value_enum, default_value_t = BackupPolicy::Single)]
+		backup: BackupPolicy,
+		/// Folder to keep copies in with --backup dir (default: "backup" next to each file)
+		#[arg(long, value_name="FOLDER")]
+		backup_dir: Option<PathBuf>
+	},
+	/// Rewrites package files for another game version
+	Convert {
+		/// List of package files to convert
+		files: Vec<PathBuf>,
+		/// Package format to convert to
+		#[arg(short, long, value_enum, value_name = "VERSION")]
+		format: PackageFormat,
+		/// Copy to keep of each original file
+		#[arg(long, value_enum, default_value_t = BackupPolicy::Single)]
+		backup: BackupPolicy,
+		/// Folder to keep copies in with --backup dir (default: "backup" next to each file)
+		#[arg(long, value_name="FOLDER")]
+		backup_dir: Option<PathBuf>
+	},
+	/// Lists the header, index entries and resource summaries of a package file
+	#[command(alias = "list")]
+	Info {
+		/// Package file to inspect
+		file: PathBuf,
+		/// Print as JSON
+		#[arg(short, long)]
+		json: bool
+	},
+	/// Unpacks the resources of a package file into a folder, with a manifest for repacking
+	Unpack {
+		/// Package file to unpack
+		file: PathBuf,
+		/// Folder to unpack resources to
+		#[arg(short, long, value_name="FOLDER")]
+		output: Option<PathBuf>
+	},
+	/// Packs a folder created by unpack back into a package file
+	Pack {
+		/// Folder containing a manifest.json and resource files
+		input: PathBuf,
+		/// Path for the new package file
+		#[arg(short, long)]
+		output: Option<PathBuf>,
+		/// Write the package for an older or newer game version, converting references inside resources to match
+		#[arg(short, long, value_enum, value_name = "VERSION")]
+		format: Option<PackageFormat>
+	},
+	/// Combines several package files into one
+	Merge {
+		/// Package files to merge, in order of priority for first-wins/last-wins
+		files: Vec<PathBuf>,
+		/// Path for the merged package file
+		#[arg(short, long)]
+		output: PathBuf,
+		/// What to do when packages contain different resources with the same TGIR
+		#[arg(short, long, value_enum, default_value_t = merge::ConflictPolicy::Error)]
+		policy: merge::ConflictPolicy,
+		/// Write the package for an older or newer game version, converting references inside resources to match
+		#[arg(short, long, value_enum, value_name = "VERSION")]
+		format: Option<PackageFormat>
+	},
+	/// Splits a package file into several smaller packages
+	Split {
+		/// Package file to split
+		file: PathBuf,
+		/// Folder to write the new packages to
+		#[arg(short, long, value_name="FOLDER")]
+		output: Option<PathBuf>,
+		/// How to divide the resources
+		#[arg(short, long, value_enum, default_value_t = split::SplitMode::Outfit)]
+		by: split::SplitMode
+	},
+	/// Compares two package files, listing added, removed and changed resources
+	Diff {
+		/// Original package file
+		a: PathBuf,
+		/// Changed package file
+		b: PathBuf
+	},
+	/// Scans a folder of package files for resources that override each other
+	ScanConflicts {
+		/// Folder to scan, including subfolders
+		dir: PathBuf,
+		/// Report conflicts for every resource type, not just GZPS, 3IDR and XTOL
+		#[arg(short, long)]
+		all: bool
+	},
+	/// Finds recolors referencing missing meshes or materials, and meshes without recolors
+	CheckLinks {
+		/// Downloads folder to check, including subfolders
+		dir: PathBuf,
+		/// Game install folder, to resolve references to game resources
+		#[arg(short, long, value_name="FOLDER")]
+		game: Option<PathBuf>
+	},
+	/// Exports the GMDC meshes in a package file for editing in a 3D program
+	ExportMesh {
+		/// Package file containing the meshes
+		file: PathBuf,
+		/// Folder to write the mesh files to
+		#[arg(short, long, value_name="FOLDER")]
+		output: Option<PathBuf>,
+		/// File format to export to
+		#[arg(short, long, value_enum, default_value_t = MeshFormat::Obj)]
+		format: MeshFormat
+	},
+	/// Replaces the geometry of GMDC groups with the groups of the same name in an OBJ or glTF (.glb) file
+	ImportMesh {
+		/// Package file containing the GMDC
+		file: PathBuf,
+		/// Mesh file, named like export-mesh names it when the package has several GMDCs
+		mesh: PathBuf,
+		/// Copy to keep of the original file
+		#[arg(long, value_enum, default_value_t = BackupPolicy::Single)]
+		backup: BackupPolicy,
+		/// Folder to keep copies in with --backup dir (default: "backup" next to the file)
+		#[arg(long, value_name="FOLDER")]
+		backup_dir: Option<PathBuf>
+	},
+	/// Reports vertex, face, bone and morph counts of the GMDC meshes in a package file, and common problems with them
+	MeshInfo {
+		/// Package file containing the meshes
+		file: PathBuf,
+		/// Print as JSON
+		#[arg(short, long)]
+		json: bool
 	},
 	/// Create one or more outfit recolors from an existing recolor
 	RecolorOutfitTemplate {
@@ -113,7 +279,10 @@ enum Command {
 		number: Option<usize>,
 		/// Repository recolors to first age+gender
 		#[arg(short, long)]
-		repo: bool
+		repo: bool,
+		/// Keep resources that fail to decode as-is and list them at the end
+		#[arg(long)]
+		lenient: bool
 	},
 	/// Create one or more outfit recolors from a mesh package
 	RecolorOutfitMesh {
@@ -136,7 +305,10 @@ enum Command {
 		category: Option<String>,
 		/// Shoe type ("none", "boots", "heels", "normal", "sandals", "pajamas", "armor")
 		#[arg(short, long)]
-		shoe: Option<String>
+		shoe: Option<String>,
+		/// Keep resources that fail to decode as-is and list them at the end
+		#[arg(long)]
+		lenient: bool
 	},
 	/// Create one or more object recolors
 	RecolorObject {
@@ -151,6 +323,9 @@ enum Command {
 		/// Specify subset to recolor; otherwise recolors will include all subsets
 		#[arg(short, long)]
 		subset: Option<String>,
+		/// Keep resources that fail to decode as-is and list them at the end
+		#[arg(long)]
+		lenient: bool
 	},
 	/// Create new object recolors from an existing recolor
 	CloneObjectRecolor {
@@ -165,17 +340,30 @@ enum Command {
 		/// Specify subset to recolor; otherwise recolors will include all subsets
 		#[arg(short, long)]
 		subset: Option<String>,
+		/// Keep resources that fail to decode as-is and list them at the end
+		#[arg(long)]
+		lenient: bool
+	}
+}
+
+fn main() -> ExitCode {
+	match run(Args::parse()) {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(err) => {
+			eprintln!("Error: {err}");
+			ExitCode::FAILURE
+		}
 	}
 }
 
-fn main() -> Result<(), Box<dyn Error + 'static>> {
-	let args = Args::parse();
+fn run(args: Args) -> Result<(), Box<dyn Error>> {
+	parallel::set_threads(args.threads);
 	match args.command {
-		Some(Command::DefaultOutfit{ source, auto, hide_pack_icon }) => {
-			defaulter::default_outfit::default_outfit(source, auto, hide_pack_icon)
+		Some(Command::DefaultOutfit{ source, auto, hide_pack_icon, lenient }) => {
+			defaulter::default_outfit::default_outfit(DefaultOutfitOptions { source, auto, hide_pack_icon, lenient })
 		}
-		Some(Command::DefaultHair{ source, output, add_ages, all_categories, visible, townified, hat, hide_pack_icon, same_family }) => {
-			defaulter::default_hair::default_hair(source, output, add_ages, all_categories, visible, townified, hat, hide_pack_icon, same_family)
+		Some(Command::DefaultHair{ source, output, add_ages, all_categories, visible, townified, hat, hide_pack_icon, same_family, lenient }) => {
+			defaulter::default_hair::default_hair(DefaultHairOptions { source, output, add_ages, all_categories, visible, townified, hat, hide_pack_icon, same_family, lenient })
 		}
 		Some(Command::ExtractOutfits{ input, output }) => {
 			extractor::extract_outfits::extract_outfits(input, output)
@@ -186,23 +374,62 @@ fn main() -> Result<(), Box<dyn Error + 'static>> {
 		Some(Command::ExtractMakeup{ input, output }) => {
 			extractor::extract_makeup::extract_makeup(input, output)
 		}
-		Some(Command::EditGZPS{ files, property, value }) => {
-			bulk_edit::edit_gzps(files, &property, &value)
+		Some(Command::EditGZPS{ files, property, value, preserve, backup, backup_dir }) => {
+			bulk_edit::edit_gzps(files, &property, &value, preserve, &Backup { policy: backup, dir: backup_dir })
+		}
+		Some(Command::Compress{ files, skip, min_size, backup, backup_dir }) => {
+			compressor::compress_packages(files, CompressionPolicy { skip_types: skip, min_size }, &Backup { policy: backup, dir: backup_dir })
+		}
+		Some(Command::Decompress{ files, backup, backup_dir }) => {
+			compressor::decompress_packages(files, &Backup { policy: backup, dir: backup_dir })
+		}
+		Some(Command::Convert{ files, format, backup, backup_dir }) => {
+			compressor::convert_packages(files, format, &Backup { policy: backup, dir: backup_dir })
+		}
+		Some(Command::Info{ file, json }) => {
+			info::package_info(file, json)
+		}
+		Some(Command::Unpack{ file, output }) => {
+			unpacker::unpack_package(file, output)
+		}
+		Some(Command::Pack{ input, output, format }) => {
+			unpacker::pack_package(input, output, format)
+		}
+		Some(Command::Merge{ files, output, policy, format }) => {
+			merge::merge_packages(files, output, policy, format)
+		}
+		Some(Command::Split{ file, output, by }) => {
+			split::split_package(file, output, by)
+		}
+		Some(Command::Diff{ a, b }) => {
+			diff::diff_packages(a, b)
+		}
+		Some(Command::ScanConflicts{ dir, all }) => {
+			conflicts::scan_conflicts(dir, all)
+		}
+		Some(Command::CheckLinks{ dir, game }) => {
+			check_links::check_links(dir, game)
+		}
+		Some(Command::ExportMesh{ file, output, format }) => {
+			mesh::export_mesh::export_mesh(file, output, format)
+		}
+		Some(Command::ImportMesh{ file, mesh, backup, backup_dir }) => {
+			mesh::import_mesh::import_mesh(file, mesh, &Backup { policy: backup, dir: backup_dir })
 		}
-		Some(Command::Compress{ files }) => {
-			compressor::compress_packages(files)
+		Some(Command::MeshInfo{ file, json }) => {
+			mesh::mesh_info::mesh_info(file, json)
 		}
-		Some(Command::RecolorOutfitTemplate{ files, title, number, repo }) => {
-			recolor::recolor_outfit::recolor_outfit_from_template(files, title, number, repo)
+		Some(Command::RecolorOutfitTemplate{ files, title, number, repo, lenient }) => {
+			recolor::recolor_outfit::recolor_outfit_from_template(TemplateRecolorOptions { files, title, number, repo, lenient })
 		}
-		Some(Command::RecolorOutfitMesh{ files, title, number, part, age_gender, category, shoe }) => {
-			recolor::recolor_outfit::recolor_outfit_from_mesh(files, title, number, part, age_gender, category, shoe)
+		Some(Command::RecolorOutfitMesh{ files, title, number, part, age_gender, category, shoe, lenient }) => {
+			recolor::recolor_outfit::recolor_outfit_from_mesh(MeshRecolorOptions { files, title, number, part, age_gender, category, shoe, lenient })
 		}
-		Some(Command::RecolorObject { file, title, number, subset }) => {
-			recolor::recolor_object::recolor_object(file, title, number, subset)
+		Some(Command::RecolorObject { file, title, number, subset, lenient }) => {
+			recolor::recolor_object::recolor_object(ObjectRecolorOptions { file, title, number, subset, lenient })
 		}
-		Some(Command::CloneObjectRecolor { file, title, number, subset }) => {
-			recolor::recolor_object::clone_recolor(file, title, number, subset)
+		Some(Command::CloneObjectRecolor { file, title, number, subset, lenient }) => {
+			recolor::recolor_object::clone_recolor(ObjectRecolorOptions { file, title, number, subset, lenient })
 		}
 		None => Err("No command given.".into())
 	}
@@ -0,0 +1,187 @@
+use std::collections::{ HashMap, HashSet };
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+
+use crate::backup::write_atomic_with;
+use crate::dbpf::{ Dbpf, Identifier };
+use crate::dbpf::format::PackageFormat;
+use crate::dbpf::header::Header;
+use crate::dbpf::index::DbpfIndex;
+use crate::dbpf::resource::Resource;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConflictPolicy {
+	/// Keep the resource from the first package it appears in
+	FirstWins,
+	/// Keep the resource from the last package it appears in
+	LastWins,
+	/// Stop without writing anything if any resources conflict
+	Error,
+	/// Keep every conflicting resource
+	KeepBoth
+}
+
+pub fn merge_packages(files: Vec<PathBuf>, output: PathBuf, policy: ConflictPolicy, format: Option<PackageFormat>) -> Result<(), Box<dyn Error>> {
+	if files.is_empty() {
+		return Err("No package files given".into());
+	}
+
+	let merged = merge(&files, policy)?;
+
+	for conflict in &merged.conflicts {
+		println!("CONFLICT: {conflict}");
+	}
+
+	if policy == ConflictPolicy::Error && !merged.conflicts.is_empty() {
+		return Err(format!("{} conflicting resources found, nothing was written", merged.conflicts.len()).into());
+	}
+
+	// resources stay compressed or uncompressed like they were in the package they came from
+	let compressed_ids = merged.resources.iter()
+		.filter(|(_, compressed)| *compressed)
+		.map(|(resource, _)| resource.id.clone())
+		.collect::<HashSet<Identifier>>();
+	let num_resources = merged.resources.len();
+	let resources = merged.resources.into_iter().map(|(resource, _)| Ok(resource));
+
+	write_atomic_with(&output, |file| Dbpf::write_resources_with(resources, merged.header, file, |resource| compressed_ids.contains(&resource.id), format))?;
+
+	println!("Merged {} packages into {} ({num_resources} resources, {} conflicts, {} identical duplicates removed)",
+		files.len(), output.to_string_lossy(), merged.conflicts.len(), merged.duplicates);
+
+	Ok(())
+}
+
+struct Merged {
+	// resources to write, and whether each one was compressed
+	resources: Vec<(Resource, bool)>,
+	header: Header,
+	conflicts: Vec<String>,
+	duplicates: usize
+}
+
+fn merge(files: &[PathBuf], policy: ConflictPolicy) -> Result<Merged, Box<dyn Error>> {
+	// merged resources, with the index of the file each one came from and whether it was compressed
+	let mut merged: Vec<(Resource, usize, bool)> = Vec::new();
+	// position in merged of the first resource with each id
+	let mut positions: HashMap<Identifier, usize> = HashMap::new();
+	let mut header: Option<Header> = None;
+	let mut conflicts = Vec::new();
+	let mut duplicates = 0;
+
+	for (file_index, file) in files.iter().enumerate() {
+		let mut index = DbpfIndex::open(file)?;
+		let resources = index.read_all()?;
+		header = Some(merge_header(header, index.header.clone()));
+
+		for resource in resources {
+			let compressed = index.dir.as_ref().is_some_and(|dir| dir.uncompressed_size(&resource.id).is_some());
+			match positions.get(&resource.id).copied() {
+				None => {
+					positions.insert(resource.id.clone(), merged.len());
+					merged.push((resource, file_index, compressed));
+				}
+				// identical copies aren't conflicts
+				Some(i) if merged[i].0.data == resource.data => duplicates += 1,
+				Some(i) => {
+					let first_file = files[merged[i].1].to_string_lossy();
+					let kept = match policy {
+						ConflictPolicy::FirstWins | ConflictPolicy::Error => format!("kept {first_file}"),
+						ConflictPolicy::LastWins => format!("kept {}", file.to_string_lossy()),
+						ConflictPolicy::KeepBoth => "kept both".to_string()
+					};
+					conflicts.push(format!("{} in {first_file} and {} ({kept})", resource.id, file.to_string_lossy()));
+					match policy {
+						ConflictPolicy::FirstWins | ConflictPolicy::Error => {}
+						ConflictPolicy::LastWins => merged[i] = (resource, file_index, compressed),
+						ConflictPolicy::KeepBoth => merged.push((resource, file_index, compressed))
+					}
+				}
+			}
+		}
+	}
+
+	Ok(Merged {
+		resources: merged.into_iter().map(|(resource, _, compressed)| (resource, compressed)).collect(),
+		header: header.unwrap_or_default(),
+		conflicts,
+		duplicates
+	})
+}
+
+// the first package's header, with the newest index version so no resource ids are lost,
+// and the latest modification date
+fn merge_header(merged: Option<Header>, header: Header) -> Header {
+	match merged {
+		None => header,
+		Some(mut merged) => {
+			merged.index_minor_version = merged.index_minor_version.max(header.index_minor_version);
+			merged.date_modified = merged.date_modified.max(header.date_modified);
+			merged
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::path::{ Path, PathBuf };
+
+	use crate::dbpf::index::DbpfIndex;
+	use crate::dbpf::resource::Resource;
+	use crate::merge::{ ConflictPolicy, merge, merge_packages };
+	use crate::test_support::{ test_dir, write_package };
+	use crate::test_support::fixtures::{ binx_resource, gzps_resource, text_list_resource };
+
+	// a compressed GZPS in both, identical; a BINX that differs; a STR# only in the second
+	fn conflicting_packages(dir: &Path) -> (Resource, Vec<PathBuf>) {
+		let mut changed_binx = binx_resource();
+		changed_binx.data.push(0);
+		let files = vec![dir.join("a.package"), dir.join("b.package")];
+		write_package(&files[0], vec![(gzps_resource(), true), (binx_resource(), false)]);
+		write_package(&files[1], vec![(changed_binx.clone(), false), (gzps_resource(), true), (text_list_resource(), false)]);
+		(changed_binx, files)
+	}
+
+	#[test]
+	fn conflicts_follow_the_policy() {
+		let dir = test_dir("merge_policies");
+		let (changed_binx, files) = conflicting_packages(&dir);
+		let [a, b] = [&files[0], &files[1]].map(|file| file.to_string_lossy().to_string());
+		let id = binx_resource().id;
+
+		let data = |policy| merge(&files, policy).unwrap().resources.into_iter()
+			.map(|(resource, _)| resource.data)
+			.collect::<Vec<Vec<u8>>>();
+		assert_eq!(data(ConflictPolicy::FirstWins), [gzps_resource().data, binx_resource().data, text_list_resource().data]);
+		assert_eq!(data(ConflictPolicy::LastWins), [gzps_resource().data, changed_binx.data.clone(), text_list_resource().data]);
+		assert_eq!(data(ConflictPolicy::KeepBoth), [gzps_resource().data, binx_resource().data, changed_binx.data, text_list_resource().data]);
+
+		let merged = merge(&files, ConflictPolicy::FirstWins).unwrap();
+		assert_eq!(merged.conflicts, [format!("{id} in {a} and {b} (kept {a})")]);
+		assert_eq!(merged.duplicates, 1);
+		assert_eq!(merge(&files, ConflictPolicy::LastWins).unwrap().conflicts, [format!("{id} in {a} and {b} (kept {b})")]);
+		assert_eq!(merge(&files, ConflictPolicy::KeepBoth).unwrap().conflicts, [format!("{id} in {a} and {b} (kept both)")]);
+
+		let output = dir.join("merged.package");
+		assert!(merge_packages(files.clone(), output.clone(), ConflictPolicy::Error, None).is_err());
+		assert!(!output.exists());
+		fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn merged_resources_keep_their_compression() {
+		let dir = test_dir("merge_compression");
+		let (_, files) = conflicting_packages(&dir);
+		let output = dir.join("merged.package");
+		merge_packages(files, output.clone(), ConflictPolicy::LastWins, None).unwrap();
+
+		let index = DbpfIndex::open(&output).unwrap();
+		let dir_ids = index.dir.as_ref().unwrap().items.iter().map(|item| item.id.clone()).collect::<Vec<_>>();
+		assert_eq!(dir_ids, [gzps_resource().id]);
+		assert_eq!(index.index_entries.len(), 3);
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
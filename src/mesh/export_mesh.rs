@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::dbpf::Dbpf;
+use crate::dbpf::resource::DecodedResource;
+use crate::dbpf::resource_index::ResourceIndex;
+use crate::mesh::{ MeshFormat, MeshGroup, mesh_name, shpe_materials };
+use crate::mesh::obj::write_obj;
+use crate::mesh::gltf::write_glb;
+use crate::error::report_warnings;
+use crate::file_names::unique_name;
+
+pub fn export_mesh(file: PathBuf, output: Option<PathBuf>, format: MeshFormat) -> Result<(), Box<dyn Error>> {
+	let output_dir = output.unwrap_or(file.with_extension(""));
+	fs::create_dir_all(&output_dir)?;
+
+	let package = Dbpf::read_from_file(&file, "")?;
+	let index = ResourceIndex::new(&package.resources);
+	let materials = shpe_materials(&index);
+
+	let mut used_names: HashSet<String> = HashSet::new();
+	let mut warnings = Vec::new();
+	let mut num_exported = 0;
+	for resource in &package.resources {
+		let DecodedResource::Gmdc(gmdc) = resource else { continue };
+
+		let mut gmdc_warnings = Vec::new();
+		let groups = MeshGroup::from_gmdc(&gmdc.block, &mut gmdc_warnings).map_err(|err| format!("{}: {err}", gmdc.id))?;
+		warnings.extend(gmdc_warnings.into_iter().map(|warning| format!("{}: {warning}", gmdc.id)));
+		let gmdc_materials = materials.get(&gmdc.id).map(|m| m.as_slice()).unwrap_or_default();
+
+		let name = unique_name(&mut used_names, &mesh_name(&gmdc.block));
+		let path = output_dir.join(format!("{name}.{}", format.extension()));
+
+		match format {
+			MeshFormat::Obj => write_obj(&path, &groups, gmdc_materials)?,
+			MeshFormat::Gltf => write_glb(&path, &groups, gmdc_materials, &gmdc.block.model.transforms)?
+		}
+		println!("Wrote {} ({} groups)", path.to_string_lossy(), groups.len());
+		num_exported += 1;
+	}
+
+	report_warnings(&warnings);
+
+	if num_exported == 0 {
+		return Err(format!("{} does not contain any GMDC.", file.to_string_lossy()).into());
+	}
+
+	Ok(())
+}
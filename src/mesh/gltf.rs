@@ -0,0 +1,407 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use serde_json::{ json, Value };
+
+use crate::backup::write_atomic;
+use crate::dbpf::resource_types::gmdc::GmdcTransform;
+use crate::dbpf::resource_types::shpe::Material;
+use crate::mesh::{ MeshGroup, MorphTarget };
+
+const GLB_MAGIC: u32 = 0x46546C67;
+const JSON_CHUNK: u32 = 0x4E4F534A;
+const BIN_CHUNK: u32 = 0x004E4942;
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_BYTE: u32 = 5121;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+// accessors and buffer views over a single binary buffer
+#[derive(Default)]
+struct Buffer {
+	bin: Vec<u8>,
+	views: Vec<Value>,
+	accessors: Vec<Value>
+}
+
+impl Buffer {
+	fn add(&mut self, bytes: &[u8], target: Option<u32>, accessor: Value) -> usize {
+		let mut view = json!({
+			"buffer": 0,
+			"byteOffset": self.bin.len(),
+			"byteLength": bytes.len()
+		});
+		if let Some(target) = target {
+			view["target"] = json!(target);
+		}
+		self.views.push(view);
+		self.bin.extend_from_slice(bytes);
+		// every component is at least 4 bytes wide, but keep views aligned regardless
+		self.bin.resize(self.bin.len().next_multiple_of(4), 0);
+
+		let mut accessor = accessor;
+		accessor["bufferView"] = json!(self.views.len() - 1);
+		self.accessors.push(accessor);
+		self.accessors.len() - 1
+	}
+
+	fn vectors(&mut self, values: &[[f32; 3]]) -> usize {
+		let mut min = [f32::MAX; 3];
+		let mut max = [f32::MIN; 3];
+		for value in values {
+			for i in 0..3 {
+				min[i] = min[i].min(value[i]);
+				max[i] = max[i].max(value[i]);
+			}
+		}
+		let bytes = values.as_flattened().iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<u8>>();
+		self.add(&bytes, Some(ARRAY_BUFFER), json!({ "componentType": FLOAT, "count": values.len(), "type": "VEC3", "min": min, "max": max }))
+	}
+
+	fn floats<const N: usize>(&mut self, values: &[[f32; N]], accessor_type: &str) -> usize {
+		let bytes = values.as_flattened().iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<u8>>();
+		self.add(&bytes, Some(ARRAY_BUFFER), json!({ "componentType": FLOAT, "count": values.len(), "type": accessor_type }))
+	}
+
+	fn joints(&mut self, values: &[[u32; 4]]) -> usize {
+		let bytes = values.as_flattened().iter().flat_map(|v| (*v as u16).to_le_bytes()).collect::<Vec<u8>>();
+		self.add(&bytes, Some(ARRAY_BUFFER), json!({ "componentType": UNSIGNED_SHORT, "count": values.len(), "type": "VEC4" }))
+	}
+
+	fn indices(&mut self, triangles: &[[u32; 3]]) -> usize {
+		let bytes = triangles.as_flattened().iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<u8>>();
+		self.add(&bytes, Some(ELEMENT_ARRAY_BUFFER), json!({ "componentType": UNSIGNED_INT, "count": triangles.len() * 3, "type": "SCALAR" }))
+	}
+
+	// inverse bind matrices aren't vertex attributes, so their view has no target
+	fn matrices(&mut self, values: &[[f32; 16]]) -> usize {
+		let bytes = values.as_flattened().iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<u8>>();
+		self.add(&bytes, None, json!({ "componentType": FLOAT, "count": values.len(), "type": "MAT4" }))
+	}
+}
+
+// writes the groups as one mesh each into a binary glTF. the model's joints become a flat list of nodes
+// making up the skin, and materials are named after the SHPE subsets they're assigned to
+pub fn write_glb(path: &Path, groups: &[MeshGroup], materials: &[Material], transforms: &[GmdcTransform]) -> Result<(), Box<dyn Error>> {
+	let mut buffer = Buffer::default();
+	let mut meshes = Vec::new();
+	let mut nodes = Vec::new();
+	let is_skinned = groups.iter().any(|group| !group.joints.is_empty()) && !transforms.is_empty();
+
+	for group in groups {
+		let mut attributes = json!({ "POSITION": buffer.vectors(&group.positions) });
+		if !group.normals.is_empty() {
+			attributes["NORMAL"] = json!(buffer.vectors(&group.normals));
+		}
+		if !group.uvs.is_empty() {
+			attributes["TEXCOORD_0"] = json!(buffer.floats(&group.uvs, "VEC2"));
+		}
+		if is_skinned && !group.joints.is_empty() {
+			attributes["JOINTS_0"] = json!(buffer.joints(&group.joints));
+			attributes["WEIGHTS_0"] = json!(buffer.floats(&group.weights, "VEC4"));
+		}
+
+		let mut primitive = json!({ "attributes": attributes, "indices": buffer.indices(&group.triangles), "mode": 4 });
+		if let Some((index, _)) = group.material(materials) {
+			primitive["material"] = json!(index);
+		}
+		let mut mesh = json!({ "name": group.name, "primitives": [primitive] });
+		if !group.morphs.is_empty() {
+			let targets = group.morphs.iter()
+				.map(|morph| json!({ "POSITION": buffer.vectors(&morph.deltas) }))
+				.collect::<Vec<Value>>();
+			mesh["primitives"][0]["targets"] = json!(targets);
+			mesh["weights"] = json!(vec![0.0; group.morphs.len()]);
+			mesh["extras"] = json!({ "targetNames": group.morphs.iter().map(|morph| &morph.name).collect::<Vec<&String>>() });
+		}
+		meshes.push(mesh);
+
+		let mut node = json!({ "name": group.name, "mesh": meshes.len() - 1 });
+		if is_skinned && !group.joints.is_empty() {
+			node["skin"] = json!(0);
+		}
+		nodes.push(node);
+	}
+
+	let mut skins = Vec::new();
+	if is_skinned {
+		let first_joint = nodes.len();
+		for (i, transform) in transforms.iter().enumerate() {
+			nodes.push(json!({ "name": format!("joint{i}"), "rotation": transform.rotation, "translation": transform.translation }));
+		}
+		let inverse_bind_matrices = buffer.matrices(&transforms.iter().map(inverse_bind_matrix).collect::<Vec<[f32; 16]>>());
+		skins.push(json!({ "joints": (first_joint..nodes.len()).collect::<Vec<usize>>(), "inverseBindMatrices": inverse_bind_matrices }));
+	}
+
+	let materials = materials.iter()
+		.map(|material| json!({ "name": material.subset.to_string(), "extras": { "txmt": material.txmt_name.to_string() } }))
+		.collect::<Vec<Value>>();
+
+	let mut gltf = json!({
+		"asset": { "version": "2.0", "generator": concat!("clod ", env!("CARGO_PKG_VERSION")) },
+		"scene": 0,
+		"scenes": [{ "nodes": (0..nodes.len()).collect::<Vec<usize>>() }],
+		"nodes": nodes,
+		"meshes": meshes,
+		"accessors": buffer.accessors,
+		"bufferViews": buffer.views,
+		"buffers": [{ "byteLength": buffer.bin.len() }]
+	});
+	if !materials.is_empty() {
+		gltf["materials"] = json!(materials);
+	}
+	if !skins.is_empty() {
+		gltf["skins"] = json!(skins);
+	}
+
+	let mut json_chunk = serde_json::to_vec(&gltf)?;
+	json_chunk.resize(json_chunk.len().next_multiple_of(4), b' ');
+
+	let mut glb = Vec::new();
+	let total_length = 12 + 8 + json_chunk.len() + 8 + buffer.bin.len();
+	for value in [GLB_MAGIC, 2, total_length as u32, json_chunk.len() as u32, JSON_CHUNK] {
+		glb.extend_from_slice(&value.to_le_bytes());
+	}
+	glb.extend_from_slice(&json_chunk);
+	for value in [buffer.bin.len() as u32, BIN_CHUNK] {
+		glb.extend_from_slice(&value.to_le_bytes());
+	}
+	glb.extend_from_slice(&buffer.bin);
+
+	write_atomic(path, &glb)?;
+	Ok(())
+}
+
+// the inverse of a joint's rotation and translation, as a column-major matrix. the joint nodes have no parents,
+// so this undoes the joint's own transform and vertices stay where they are in the bind pose
+fn inverse_bind_matrix(transform: &GmdcTransform) -> [f32; 16] {
+	let [x, y, z, w] = transform.rotation;
+	let rotation = [
+		[1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - z * w), 2.0 * (x * z + y * w)],
+		[2.0 * (x * y + z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - x * w)],
+		[2.0 * (x * z - y * w), 2.0 * (y * z + x * w), 1.0 - 2.0 * (x * x + y * y)]
+	];
+	// the inverse rotation is the transpose, so each of its columns is a row of the rotation
+	let mut matrix = [0.0; 16];
+	for column in 0..3 {
+		matrix[column * 4..column * 4 + 3].copy_from_slice(&rotation[column]);
+	}
+	for row in 0..3 {
+		matrix[12 + row] = -(0..3).map(|i| rotation[i][row] * transform.translation[i]).sum::<f32>();
+	}
+	matrix[15] = 1.0;
+	matrix
+}
+
+// reads the first primitive of each mesh in a binary glTF as a mesh group named after the mesh.
+// joints are read back as the model's joints, from the "jointN" names export gives the skin's nodes
+pub fn read_glb(path: &Path) -> Result<Vec<MeshGroup>, Box<dyn Error>> {
+	let glb = fs::read(path)?;
+	let u32_at = |offset: usize| -> Result<u32, Box<dyn Error>> {
+		let bytes = glb.get(offset..offset + 4).ok_or("unexpected end of file")?;
+		Ok(u32::from_le_bytes(bytes.try_into()?))
+	};
+	if u32_at(0)? != GLB_MAGIC || u32_at(4)? != 2 {
+		return Err(format!("{} is not a binary glTF 2.0 file", path.to_string_lossy()).into());
+	}
+
+	let mut gltf = Value::Null;
+	let mut bin: &[u8] = &[];
+	let mut offset = 12;
+	while offset + 8 <= glb.len() {
+		let length = u32_at(offset)? as usize;
+		let chunk = glb.get(offset + 8..offset + 8 + length).ok_or("chunk runs past the end of the file")?;
+		match u32_at(offset + 4)? {
+			JSON_CHUNK => gltf = serde_json::from_slice(chunk)?,
+			BIN_CHUNK => bin = chunk,
+			_ => {}
+		}
+		offset += 8 + length;
+	}
+
+	let reader = Reader { gltf: &gltf, bin };
+	let mut groups = Vec::new();
+	for (mesh_index, mesh) in gltf["meshes"].as_array().into_iter().flatten().enumerate() {
+		let name = mesh["name"].as_str().unwrap_or_default().to_string();
+		let primitive = &mesh["primitives"][0];
+		let attributes = &primitive["attributes"];
+		let error = |err: Box<dyn Error>| format!("mesh \"{name}\": {err}");
+
+		let positions = reader.floats::<3>(&attributes["POSITION"]).map_err(error)?;
+		let normals = reader.optional(&attributes["NORMAL"], |a| reader.floats::<3>(a)).map_err(error)?;
+		let uvs = reader.optional(&attributes["TEXCOORD_0"], |a| reader.floats::<2>(a)).map_err(error)?;
+		let weights = reader.optional(&attributes["WEIGHTS_0"], |a| reader.floats::<4>(a)).map_err(error)?;
+		let skin_joints = reader.skin_joints(mesh_index);
+		let joints = reader.optional(&attributes["JOINTS_0"], |a| reader.uints::<4>(a)).map_err(error)?
+			.into_iter()
+			.map(|joints| joints.map(|joint| skin_joints.get(joint as usize).copied().unwrap_or(joint)))
+			.collect::<Vec<[u32; 4]>>();
+
+		let target_names = mesh["extras"]["targetNames"].as_array().cloned().unwrap_or_default();
+		let mut morphs = Vec::new();
+		for (i, target) in primitive["targets"].as_array().into_iter().flatten().enumerate() {
+			morphs.push(MorphTarget {
+				name: target_names.get(i).and_then(|name| name.as_str()).map_or(format!("morph{i}"), |name| name.to_string()),
+				deltas: reader.floats::<3>(&target["POSITION"]).map_err(error)?
+			});
+		}
+
+		let indices = match &primitive["indices"] {
+			Value::Null => (0..positions.len() as u32).collect(),
+			indices => reader.uints::<1>(indices).map_err(error)?.into_iter().map(|[index]| index).collect::<Vec<u32>>()
+		};
+		if primitive["mode"].as_u64().is_some_and(|mode| mode != 4) {
+			return Err(format!("mesh \"{name}\" is not made of triangles").into());
+		}
+		if let Some(index) = indices.iter().find(|index| **index as usize >= positions.len()) {
+			return Err(format!("mesh \"{name}\": index {index} out of {} vertices", positions.len()).into());
+		}
+
+		groups.push(MeshGroup {
+			name,
+			positions,
+			normals,
+			uvs,
+			joints,
+			weights,
+			morphs,
+			triangles: indices.chunks_exact(3).map(|face| [face[0], face[1], face[2]]).collect()
+		});
+	}
+	Ok(groups)
+}
+
+struct Elements<'a> {
+	elements: Vec<&'a [u8]>,
+	component_type: u32
+}
+
+struct Reader<'a> {
+	gltf: &'a Value,
+	bin: &'a [u8]
+}
+
+impl Reader<'_> {
+	fn optional<T>(&self, accessor: &Value, read: impl Fn(&Value) -> Result<Vec<T>, Box<dyn Error>>) -> Result<Vec<T>, Box<dyn Error>> {
+		if accessor.is_null() { Ok(Vec::new()) } else { read(accessor) }
+	}
+
+	// the raw bytes of each element of an accessor, along with its component type
+	fn elements(&self, accessor: &Value, num_components: usize) -> Result<Elements<'_>, Box<dyn Error>> {
+		let accessor = &self.gltf["accessors"][accessor.as_u64().ok_or("invalid accessor")? as usize];
+		if !accessor["sparse"].is_null() {
+			return Err("sparse accessors are not supported".into());
+		}
+		let component_type = accessor["componentType"].as_u64().ok_or("accessor has no component type")? as u32;
+		let component_size = match component_type {
+			UNSIGNED_BYTE => 1,
+			UNSIGNED_SHORT => 2,
+			UNSIGNED_INT | FLOAT => 4,
+			_ => return Err(format!("unsupported component type {component_type}").into())
+		};
+		let element_size = component_size * num_components;
+		let count = accessor["count"].as_u64().unwrap_or(0) as usize;
+
+		let view = &self.gltf["bufferViews"][accessor["bufferView"].as_u64().ok_or("accessor has no buffer view")? as usize];
+		if view["buffer"].as_u64().unwrap_or(0) != 0 {
+			return Err("only the embedded buffer is supported".into());
+		}
+		let start = view["byteOffset"].as_u64().unwrap_or(0) as usize + accessor["byteOffset"].as_u64().unwrap_or(0) as usize;
+		let stride = view["byteStride"].as_u64().map_or(element_size, |stride| stride as usize);
+
+		let elements = (0..count)
+			.map(|i| self.bin.get(start + i * stride..start + i * stride + element_size).ok_or("accessor runs past the end of the buffer"))
+			.collect::<Result<Vec<&[u8]>, &str>>()?;
+		Ok(Elements { elements, component_type })
+	}
+
+	fn floats<const N: usize>(&self, accessor: &Value) -> Result<Vec<[f32; N]>, Box<dyn Error>> {
+		let Elements { elements, component_type } = self.elements(accessor, N)?;
+		if component_type != FLOAT {
+			return Err("expected float values".into());
+		}
+		Ok(elements.iter().map(|bytes| std::array::from_fn(|i| f32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap()))).collect())
+	}
+
+	fn uints<const N: usize>(&self, accessor: &Value) -> Result<Vec<[u32; N]>, Box<dyn Error>> {
+		let Elements { elements, component_type } = self.elements(accessor, N)?;
+		Ok(elements.iter().map(|bytes| std::array::from_fn(|i| match component_type {
+			UNSIGNED_BYTE => bytes[i] as u32,
+			UNSIGNED_SHORT => u16::from_le_bytes([bytes[i * 2], bytes[i * 2 + 1]]) as u32,
+			_ => u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap())
+		})).collect())
+	}
+
+	// model joint of each joint in the skin of the node showing this mesh
+	fn skin_joints(&self, mesh_index: usize) -> Vec<u32> {
+		let nodes = self.gltf["nodes"].as_array().cloned().unwrap_or_default();
+		let Some(skin) = nodes.iter()
+			.find(|node| node["mesh"].as_u64() == Some(mesh_index as u64))
+			.and_then(|node| node["skin"].as_u64()) else { return Vec::new() };
+		self.gltf["skins"][skin as usize]["joints"].as_array().into_iter().flatten().enumerate()
+			.map(|(i, node)| {
+				let name = node.as_u64().and_then(|node| nodes.get(node as usize)).and_then(|node| node["name"].as_str()).unwrap_or_default();
+				name.strip_prefix("joint").and_then(|n| n.parse().ok()).unwrap_or(i as u32)
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	
+	use crate::mesh::MeshGroup;
+	use crate::mesh::gltf::{ read_glb, write_glb };
+	use crate::test_support::test_dir;
+	use crate::test_support::fixtures::{ mesh_gmdc, shpe_materials };
+
+	fn assert_near(a: [f32; 3], b: [f32; 3]) {
+		assert!((0..3).all(|i| (a[i] - b[i]).abs() < 1e-5), "{a:?} != {b:?}");
+	}
+
+	#[test]
+	fn glb_export_round_trips_and_binds_the_skin() {
+		let block = mesh_gmdc();
+		let groups = MeshGroup::from_gmdc(&block, &mut Vec::new()).unwrap();
+		let dir = test_dir("glb_export");
+		let path = dir.join("mesh.glb");
+		write_glb(&path, &groups, &shpe_materials(), &block.model.transforms).unwrap();
+
+		let read = read_glb(&path).unwrap();
+		assert_eq!(read.len(), 1);
+		let (before, after) = (&groups[0], &read[0]);
+		assert_eq!(after.name, before.name);
+		assert_eq!(after.positions, before.positions);
+		assert_eq!(after.normals, before.normals);
+		assert_eq!(after.uvs, before.uvs);
+		assert_eq!(after.joints, before.joints);
+		assert_eq!(after.weights, before.weights);
+		assert_eq!(after.morphs[0].name, before.morphs[0].name);
+		assert_eq!(after.morphs[0].deltas, before.morphs[0].deltas);
+		assert_eq!(after.triangles, before.triangles);
+
+		// the inverse bind matrix of a joint takes points from the joint's space back to the origin's
+		let glb = fs::read(&path).unwrap();
+		let json_length = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+		let gltf: serde_json::Value = serde_json::from_slice(&glb[20..20 + json_length]).unwrap();
+		let bin = &glb[20 + json_length + 8..];
+		let accessor = &gltf["accessors"][gltf["skins"][0]["inverseBindMatrices"].as_u64().unwrap() as usize];
+		assert_eq!(accessor["count"], 3);
+		let offset = gltf["bufferViews"][accessor["bufferView"].as_u64().unwrap() as usize]["byteOffset"].as_u64().unwrap() as usize;
+		let matrix = |joint: usize| -> [f32; 16] {
+			std::array::from_fn(|i| f32::from_le_bytes(bin[offset + (joint * 16 + i) * 4..][..4].try_into().unwrap()))
+		};
+		let apply = |m: [f32; 16], p: [f32; 3]| -> [f32; 3] {
+			std::array::from_fn(|row| m[row] * p[0] + m[4 + row] * p[1] + m[8 + row] * p[2] + m[12 + row])
+		};
+		assert_near(apply(matrix(0), [1.0, 2.0, 3.0]), [1.0, 2.0, 3.0]);
+		assert_near(apply(matrix(1), [0.0, 1.0, 0.0]), [0.0, 0.0, 0.0]);
+		assert_near(apply(matrix(1), [0.0, 2.0, 0.0]), [1.0, 0.0, 0.0]);
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
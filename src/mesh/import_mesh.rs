@@ -0,0 +1,355 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use crate::backup::Backup;
+use crate::dbpf::Dbpf;
+use crate::dbpf::resource::DecodedResource;
+use crate::dbpf::resource_types::gmdc::{ GmdcBlock, GmdcElement, GmdcMesh, GmdcTransform, ElementData, ElementIdentity };
+use crate::mesh::{ MeshGroup, TRIANGLES, NO_BONE, mesh_name, morph_name };
+use crate::mesh::obj::read_obj;
+use crate::mesh::gltf::read_glb;
+use crate::parallel::par_map;
+
+pub fn import_mesh(file: PathBuf, mesh: PathBuf, backup: &Backup) -> Result<(), Box<dyn Error>> {
+	let extension = mesh.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+	let groups = match extension.as_str() {
+		"obj" => read_obj(&mesh)?,
+		"glb" => read_glb(&mesh)?,
+		_ => return Err(format!("{} is not an .obj or .glb file.", mesh.to_string_lossy()).into())
+	};
+
+	let mut package = Dbpf::read_from_file(&file, "")?;
+
+	// with several GMDCs, the mesh file has to be named the way export-mesh names it
+	let stem = mesh.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+	let gmdcs = package.resources.iter_mut()
+		.filter_map(|r| if let DecodedResource::Gmdc(gmdc) = r { Some(gmdc) } else { None })
+		.collect::<Vec<_>>();
+	let num_gmdcs = gmdcs.len();
+	let gmdc = match gmdcs.into_iter().find(|gmdc| num_gmdcs == 1 || mesh_name(&gmdc.block).eq_ignore_ascii_case(&stem)) {
+		Some(gmdc) => gmdc,
+		None if num_gmdcs == 0 => return Err(format!("{} does not contain any GMDC.", file.to_string_lossy()).into()),
+		None => return Err(format!("{} has no GMDC named \"{stem}\".", file.to_string_lossy()).into())
+	};
+
+	for group in &groups {
+		replace_group(&mut gmdc.block, group).map_err(|err| format!("group \"{}\": {err}", group.name))?;
+		println!("Replaced \"{}\" ({} vertices, {} faces)", group.name, group.positions.len(), group.triangles.len());
+	}
+	update_bounds(&mut gmdc.block)?;
+
+	backup.backup(&file)?;
+	package.write_to_file(&file, true)?;
+
+	Ok(())
+}
+
+// replaces the vertices and faces of the group with the same name. anything the mesh file doesn't carry
+// (bone weights and morphs from OBJ, and any other element) is taken from the nearest original vertex
+fn replace_group(block: &mut GmdcBlock, new: &MeshGroup) -> Result<(), Box<dyn Error>> {
+	let group_index = block.groups.iter()
+		.position(|group| group.name.to_string().eq_ignore_ascii_case(&new.name))
+		.ok_or_else(|| {
+			let names = block.groups.iter().map(|group| format!("\"{}\"", group.name)).collect::<Vec<String>>();
+			format!("not found in the GMDC, which has {}", names.join(", "))
+		})?;
+
+	let num_vertices = new.positions.len();
+	if block.version == 4 && num_vertices > u16::MAX as usize + 1 {
+		return Err(format!("{num_vertices} vertices is more than this GMDC version can index").into());
+	}
+	let lengths = [new.normals.len(), new.uvs.len(), new.joints.len(), new.weights.len()];
+	if lengths.iter().any(|len| *len != 0 && *len != num_vertices) {
+		return Err("has attributes for only some of its vertices".into());
+	}
+
+	let group = &block.groups[group_index];
+	let link_index = group.link as usize;
+	if block.groups.iter().enumerate().any(|(i, other)| i != group_index && other.link == group.link) {
+		return Err("shares its vertices with another group".into());
+	}
+	let old = MeshGroup::from_group(block, group)?;
+	let nearest = par_map(&new.positions, |position| nearest_vertex(&old.positions, position));
+
+	// new bone assignments, adding any joint the group didn't use before to its used joints
+	let mut used_joints = group.used_joints.clone();
+	let mut link = block.links[link_index].clone();
+	let weight_components = link.elements.iter()
+		.filter_map(|i| block.elements.get(*i as usize))
+		.find(|element| element.identity == ElementIdentity::BoneWeights)
+		.map_or(0, |element| match element.data {
+			ElementData::Float1(_) => 1,
+			ElementData::Float2(_) => 2,
+			ElementData::Float3(_) => 3,
+			ElementData::Dword(_) => 0
+		});
+	let bones = if new.joints.is_empty() {
+		None
+	} else {
+		Some(new.joints.iter().zip(&new.weights)
+			.map(|(joints, weights)| pack_bones(joints, weights, weight_components + 1, &mut used_joints))
+			.collect::<Result<Vec<(u32, Vec<f32>)>, Box<dyn Error>>>()?)
+	};
+
+	for position in 0..link.elements.len() {
+		let element_index = link.elements[position] as usize;
+		let element = block.elements.get(element_index).ok_or(format!("element {element_index} does not exist"))?.clone();
+		let alias = link.aliases.get(position).filter(|alias| !alias.is_empty());
+		let old_indices = nearest.iter()
+			.map(|vertex| match alias {
+				Some(alias) => alias.get(*vertex).map(|index| *index as usize).ok_or(format!("vertex {vertex} has no alias in {}", element.identity)),
+				None => Ok(*vertex)
+			})
+			.collect::<Result<Vec<usize>, String>>()?;
+
+		let morph = new.morphs.iter().find(|morph| morph.name == morph_name(block, &element));
+		let data = match (element.identity, &bones, morph) {
+			(ElementIdentity::Vertices, _, _) => ElementData::Float3(new.positions.clone()),
+			(ElementIdentity::Normals, _, _) if !new.normals.is_empty() => ElementData::Float3(new.normals.clone()),
+			(ElementIdentity::UvCoordinates, _, _) if !new.uvs.is_empty() => ElementData::Float2(new.uvs.clone()),
+			(ElementIdentity::BoneAssignments, Some(bones), _) => ElementData::Dword(bones.iter().map(|(packed, _)| *packed).collect()),
+			(ElementIdentity::BoneWeights, Some(bones), _) => {
+				let weight = |weights: &Vec<f32>, i: usize| weights.get(i).copied().unwrap_or(0.0);
+				match element.data {
+					ElementData::Float1(_) => ElementData::Float1(bones.iter().map(|(_, w)| weight(w, 0)).collect()),
+					ElementData::Float2(_) => ElementData::Float2(bones.iter().map(|(_, w)| [weight(w, 0), weight(w, 1)]).collect()),
+					_ => ElementData::Float3(bones.iter().map(|(_, w)| [weight(w, 0), weight(w, 1), weight(w, 2)]).collect())
+				}
+			}
+			(ElementIdentity::MorphVertexDeltas, _, Some(morph)) if morph.deltas.len() == num_vertices => ElementData::Float3(morph.deltas.clone()),
+			_ => transfer(&element.data, &old_indices).map_err(|err| format!("{}: {err}", element.identity))?
+		};
+
+		// elements shared with other links are left for them, and this link gets its own copy
+		let new_element = GmdcElement { data, ..element };
+		let is_shared = block.links.iter().enumerate()
+			.any(|(i, other)| i != link_index && other.elements.contains(&(element_index as u32)));
+		if is_shared {
+			block.elements.push(new_element);
+			link.elements[position] = block.elements.len() as u32 - 1;
+		} else {
+			block.elements[element_index] = new_element;
+		}
+	}
+
+	link.referenced_size = num_vertices as u32;
+	link.aliases = Default::default();
+	block.links[link_index] = link;
+
+	let group = &mut block.groups[group_index];
+	group.faces = new.triangles.as_flattened().to_vec();
+	group.used_joints = used_joints;
+
+	Ok(())
+}
+
+fn nearest_vertex(positions: &[[f32; 3]], position: &[f32; 3]) -> usize {
+	let distance = |other: &[f32; 3]| (0..3).map(|i| (other[i] - position[i]).powi(2)).sum::<f32>();
+	positions.iter().enumerate()
+		.min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+		.map_or(0, |(i, _)| i)
+}
+
+fn transfer(data: &ElementData, indices: &[usize]) -> Result<ElementData, Box<dyn Error>> {
+	if let Some(index) = indices.iter().find(|index| **index >= data.len()) {
+		return Err(format!("value {index} out of range").into());
+	}
+	Ok(match data {
+		ElementData::Float1(values) => ElementData::Float1(indices.iter().map(|i| values[*i]).collect()),
+		ElementData::Float2(values) => ElementData::Float2(indices.iter().map(|i| values[*i]).collect()),
+		ElementData::Float3(values) => ElementData::Float3(indices.iter().map(|i| values[*i]).collect()),
+		ElementData::Dword(values) => ElementData::Dword(indices.iter().map(|i| values[*i]).collect())
+	})
+}
+
+// the reverse of unpack_bones: keeps the strongest max_bones joints, scaled back up to a total of 1,
+// and returns the packed group-local joint indices with the weight of each slot.
+// a group can use up to 255 joints, since each index has to fit in a byte other than 0xFF
+fn pack_bones(joints: &[u32; 4], weights: &[f32; 4], max_bones: usize, used_joints: &mut Vec<u32>) -> Result<(u32, Vec<f32>), Box<dyn Error>> {
+	let mut bones = (0..4).filter(|i| weights[*i] > 0.0).map(|i| (joints[i], weights[i])).collect::<Vec<(u32, f32)>>();
+	bones.sort_by(|a, b| b.1.total_cmp(&a.1));
+	bones.truncate(max_bones);
+	let total = bones.iter().map(|(_, weight)| weight).sum::<f32>();
+
+	let mut packed = u32::MAX;
+	let mut slot_weights = Vec::new();
+	for (slot, (joint, weight)) in bones.into_iter().enumerate() {
+		let local = match used_joints.iter().position(|used| *used == joint) {
+			Some(local) => local,
+			None if used_joints.len() >= NO_BONE as usize => {
+				return Err(format!("uses more than {NO_BONE} bones, which is as many as a group can have").into());
+			}
+			None => {
+				used_joints.push(joint);
+				used_joints.len() - 1
+			}
+		};
+		packed = (packed & !(NO_BONE << (slot * 8))) | ((local as u32) << (slot * 8));
+		slot_weights.push(weight / total);
+	}
+	Ok((packed, slot_weights))
+}
+
+// the model's bounding mesh and each joint's are rebuilt as boxes around the vertices they cover.
+// a joint covers the vertices it has the most weight on, and its box is in the joint's own space
+fn update_bounds(block: &mut GmdcBlock) -> Result<(), Box<dyn Error>> {
+	let mut positions = Vec::new();
+	let mut joint_positions = vec![Vec::new(); block.joints.len()];
+	for group in block.groups.iter().filter(|group| group.primitive_type == TRIANGLES) {
+		let mesh_group = MeshGroup::from_group(block, group)?;
+		for (v, position) in mesh_group.positions.iter().enumerate() {
+			positions.push(*position);
+			let strongest = mesh_group.joints.get(v).zip(mesh_group.weights.get(v)).and_then(|(joints, weights)| {
+				(0..4).filter(|i| weights[*i] > 0.0).max_by(|a, b| weights[*a].total_cmp(&weights[*b])).map(|i| joints[i] as usize)
+			});
+			if let Some(joint) = strongest && let Some(joint_positions) = joint_positions.get_mut(joint) {
+				joint_positions.push(block.model.transforms.get(joint).map_or(*position, |transform| to_joint_space(transform, position)));
+			}
+		}
+	}
+
+	if !block.model.bounding_mesh.vertices.is_empty() && !positions.is_empty() {
+		block.model.bounding_mesh = box_mesh(&positions);
+	}
+	for (joint, positions) in block.joints.iter_mut().zip(&joint_positions) {
+		if !joint.vertices.is_empty() && !positions.is_empty() {
+			*joint = box_mesh(positions);
+		}
+	}
+	Ok(())
+}
+
+fn to_joint_space(transform: &GmdcTransform, position: &[f32; 3]) -> [f32; 3] {
+	let [x, y, z, w] = transform.rotation;
+	let v = [0, 1, 2].map(|i| position[i] - transform.translation[i]);
+	// undoes the joint's rotation by rotating with its conjugate
+	let q = [-x, -y, -z];
+	let cross = |a: [f32; 3], b: [f32; 3]| [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]];
+	let t = cross(q, v).map(|c| c * 2.0);
+	let u = cross(q, t);
+	[0, 1, 2].map(|i| v[i] + w * t[i] + u[i])
+}
+
+fn box_mesh(positions: &[[f32; 3]]) -> GmdcMesh {
+	let mut min = [f32::MAX; 3];
+	let mut max = [f32::MIN; 3];
+	for position in positions {
+		for i in 0..3 {
+			min[i] = min[i].min(position[i]);
+			max[i] = max[i].max(position[i]);
+		}
+	}
+	// corner i takes max on the axes whose bit is set
+	let vertices = (0..8)
+		.map(|i: usize| [0, 1, 2].map(|axis| if i & (1 << axis) != 0 { max[axis] } else { min[axis] }))
+		.collect();
+	let faces = vec![
+		0, 2, 1, 1, 2, 3, // -z
+		4, 5, 6, 5, 7, 6, // +z
+		0, 1, 4, 1, 5, 4, // -y
+		2, 6, 3, 3, 6, 7, // +y
+		0, 4, 2, 2, 4, 6, // -x
+		1, 3, 5, 3, 7, 5  // +x
+	];
+	GmdcMesh { vertices, faces }
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::io::Cursor;
+	use std::path::{ Path, PathBuf };
+	
+	use crate::backup::{ Backup, BackupPolicy };
+	use crate::dbpf::Dbpf;
+	use crate::dbpf::header::Header;
+	use crate::dbpf::resource::DecodedResource;
+	use crate::dbpf::resource_types::gmdc::GmdcBlock;
+	use crate::mesh::{ MeshFormat, MeshGroup };
+	use crate::mesh::export_mesh::export_mesh;
+	use crate::mesh::gltf::{ read_glb, write_glb };
+	use crate::mesh::import_mesh::import_mesh;
+	use crate::test_support::test_dir;
+	use crate::test_support::fixtures::{ mesh_gmdc, mesh_gmdc_resource };
+
+	// writes a package with the mesh fixture, and exports its GMDC as glTF
+	fn exported_mesh(dir: &Path) -> (PathBuf, PathBuf) {
+		let package = dir.join("mesh.package");
+		let mut bytes = Cursor::new(Vec::new());
+		Dbpf::write_resources(vec![mesh_gmdc_resource()], Header::default(), &mut bytes, false).unwrap();
+		fs::write(&package, bytes.into_inner()).unwrap();
+		export_mesh(package.clone(), Some(dir.to_path_buf()), MeshFormat::Gltf).unwrap();
+		(package, dir.join("test_body_tslocator_gmdc.glb"))
+	}
+
+	fn imported_gmdc(package: &Path) -> GmdcBlock {
+		let package = Dbpf::read_from_file(package, "").unwrap();
+		package.resources.into_iter()
+			.find_map(|resource| if let DecodedResource::Gmdc(gmdc) = resource { Some(gmdc.block) } else { None })
+			.unwrap()
+	}
+
+	fn no_backup() -> Backup {
+		Backup { policy: BackupPolicy::None, dir: None }
+	}
+
+	#[test]
+	fn mesh_import_replaces_geometry_and_bounds() {
+		let dir = test_dir("mesh_import");
+		let (package, glb) = exported_mesh(&dir);
+
+		// a new vertex on the joint the group didn't use yet
+		let block = mesh_gmdc();
+		let mut groups = read_glb(&glb).unwrap();
+		let body = &mut groups[0];
+		body.positions.push([2.0, 2.0, 0.0]);
+		body.normals.push([0.0, 0.0, 1.0]);
+		body.uvs.push([1.0, 1.0]);
+		body.joints.push([2, 0, 0, 0]);
+		body.weights.push([1.0, 0.0, 0.0, 0.0]);
+		body.morphs[0].deltas.push([0.0, 0.0, 0.0]);
+		body.triangles.push([1, 3, 2]);
+		write_glb(&glb, &groups, &[], &block.model.transforms).unwrap();
+		import_mesh(package.clone(), glb, &no_backup()).unwrap();
+
+		let imported = imported_gmdc(&package);
+		let body = MeshGroup::from_group(&imported, &imported.groups[0]).unwrap();
+		assert_eq!(body.positions.len(), 4);
+		assert_eq!(body.triangles.len(), 2);
+		assert_eq!(body.joints[3], [2, 0, 0, 0]);
+		assert_eq!(imported.groups[0].used_joints, [0, 1, 2]);
+		assert_eq!(imported.groups[1].faces, [0, 1]);
+
+		// boxes around all the vertices, and around each joint's in its own space
+		let bounds = |vertices: &[[f32; 3]]| vertices.iter().fold(([f32::MAX; 3], [f32::MIN; 3]), |(min, max), v| {
+			(std::array::from_fn(|i| min[i].min(v[i])), std::array::from_fn(|i| max[i].max(v[i])))
+		});
+		assert_eq!(imported.model.bounding_mesh.vertices.len(), 8);
+		assert_eq!(bounds(&imported.model.bounding_mesh.vertices), ([0.0, 0.0, 0.0], [2.0, 2.0, 0.0]));
+		assert_eq!(bounds(&imported.joints[0].vertices), ([0.0, 0.0, 0.0], [1.0, 0.0, 0.0]));
+		assert!(imported.joints[1].vertices.is_empty());
+		assert_eq!(bounds(&imported.joints[2].vertices), ([1.0, 1.0, 0.0], [1.0, 1.0, 0.0]));
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn mesh_import_refuses_more_bones_than_a_group_can_index() {
+		let dir = test_dir("mesh_import_bones");
+		let (package, glb) = exported_mesh(&dir);
+
+		let group = MeshGroup {
+			name: "body".to_string(),
+			positions: (0..300).map(|i| [i as f32, 0.0, 0.0]).collect(),
+			normals: Vec::new(),
+			uvs: Vec::new(),
+			joints: (0..300).map(|i| [i, 0, 0, 0]).collect(),
+			weights: vec![[1.0, 0.0, 0.0, 0.0]; 300],
+			morphs: Vec::new(),
+			triangles: vec![[0, 1, 2]]
+		};
+		write_glb(&glb, &[group], &[], &mesh_gmdc().model.transforms).unwrap();
+		let err = import_mesh(package, glb, &no_backup()).err().unwrap();
+		assert!(err.to_string().contains("bones"), "{err}");
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
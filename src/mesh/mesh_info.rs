@@ -0,0 +1,227 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::dbpf::Dbpf;
+use crate::dbpf::resource::DecodedResource;
+use crate::dbpf::resource_index::ResourceIndex;
+use crate::dbpf::resource_types::gmdc::{ Gmdc, GmdcGroup, ElementIdentity };
+use crate::dbpf::resource_types::shpe::Material;
+use crate::mesh::{ MeshGroup, LinkAttribute, TRIANGLES, shpe_materials };
+
+#[derive(Serialize)]
+pub struct MeshInfo {
+	pub id: String,
+	pub name: String,
+	pub version: u32,
+	pub joints: usize,
+	pub vertices: usize,
+	pub faces: usize,
+	pub groups: Vec<GroupInfo>,
+	// SHPE subsets without a group of the same name
+	pub unmatched_subsets: Vec<String>,
+	pub warnings: Vec<String>
+}
+
+#[derive(Serialize)]
+pub struct GroupInfo {
+	pub name: String,
+	pub vertices: usize,
+	pub faces: usize,
+	pub uv_sets: usize,
+	pub has_normals: bool,
+	pub unweighted_vertices: usize,
+	// model joints the group's vertices are assigned to
+	pub bones: Vec<u32>,
+	pub morphs: Vec<String>,
+	pub has_material: bool
+}
+
+pub fn mesh_info(file: PathBuf, json: bool) -> Result<(), Box<dyn Error>> {
+	let package = Dbpf::read_from_file(&file, "")?;
+	let index = ResourceIndex::new(&package.resources);
+	let materials = shpe_materials(&index);
+
+	let mut infos = Vec::new();
+	for resource in &package.resources {
+		let DecodedResource::Gmdc(gmdc) = resource else { continue };
+		let gmdc_materials = materials.get(&gmdc.id).map(|m| m.as_slice());
+		infos.push(gmdc_info(gmdc, gmdc_materials));
+	}
+
+	if infos.is_empty() {
+		return Err(format!("{} does not contain any GMDC.", file.to_string_lossy()).into());
+	}
+
+	if json {
+		println!("{}", serde_json::to_string_pretty(&infos)?);
+	} else {
+		println!("{}", file.to_string_lossy());
+		for info in &infos {
+			println!();
+			print_info(info);
+		}
+	}
+
+	Ok(())
+}
+
+pub fn gmdc_info(gmdc: &Gmdc, materials: Option<&[Material]>) -> MeshInfo {
+	let block = &gmdc.block;
+	let num_joints = block.model.transforms.len();
+	let mut warnings = Vec::new();
+	let mut groups = Vec::new();
+
+	for group in &block.groups {
+		if group.primitive_type != TRIANGLES {
+			warnings.push(format!("\"{}\" has primitive type {} and is not drawn as triangles", group.name, group.primitive_type));
+			continue;
+		}
+		match group_info(gmdc, group, materials) {
+			Ok(info) => {
+				if !info.has_normals {
+					warnings.push(format!("\"{}\" has no normals", info.name));
+				}
+				if info.unweighted_vertices > 0 {
+					warnings.push(format!("\"{}\" has {} vertices not assigned to any bone", info.name, info.unweighted_vertices));
+				}
+				let missing_bones = info.bones.iter().filter(|bone| **bone as usize >= num_joints).collect::<Vec<&u32>>();
+				if !missing_bones.is_empty() {
+					warnings.push(format!("\"{}\" uses bones {missing_bones:?}, but the mesh only has {num_joints} joints", info.name));
+				}
+				if materials.is_some() && !info.has_material {
+					warnings.push(format!("\"{}\" has no SHPE material with the same subset name", info.name));
+				}
+				groups.push(info);
+			}
+			Err(err) => warnings.push(format!("\"{}\" is broken: {err}", group.name))
+		}
+	}
+
+	let unmatched_subsets = materials.unwrap_or_default().iter()
+		.map(|material| material.subset.to_string())
+		.filter(|subset| !block.groups.iter().any(|group| group.name.to_string().eq_ignore_ascii_case(subset)))
+		.collect::<Vec<String>>();
+	for subset in &unmatched_subsets {
+		warnings.push(format!("SHPE subset \"{subset}\" has no group in the GMDC"));
+	}
+	if materials.is_none() {
+		warnings.push("no SHPE in the package uses this GMDC".to_string());
+	}
+
+	MeshInfo {
+		id: gmdc.id.to_string(),
+		name: block.file_name.to_string(),
+		version: block.version,
+		joints: num_joints,
+		vertices: groups.iter().map(|group| group.vertices).sum(),
+		faces: groups.iter().map(|group| group.faces).sum(),
+		groups,
+		unmatched_subsets,
+		warnings
+	}
+}
+
+pub fn group_info(gmdc: &Gmdc, group: &GmdcGroup, materials: Option<&[Material]>) -> Result<GroupInfo, Box<dyn Error>> {
+	let block = &gmdc.block;
+	let mesh_group = MeshGroup::from_group(block, group)?;
+	let link = &block.links[group.link as usize];
+	let uv_sets = LinkAttribute::find_all(block, link, ElementIdentity::UvCoordinates, mesh_group.positions.len())?.len();
+
+	// meshes without joints (like most objects) don't need any weights
+	let unweighted_vertices = if block.model.transforms.is_empty() {
+		0
+	} else if mesh_group.weights.is_empty() {
+		mesh_group.positions.len()
+	} else {
+		mesh_group.weights.iter().filter(|weights| weights.iter().sum::<f32>() <= 0.0).count()
+	};
+	let mut bones = mesh_group.joints.iter().zip(&mesh_group.weights)
+		.flat_map(|(joints, weights)| (0..4).filter(|i| weights[*i] > 0.0).map(|i| joints[i]))
+		.collect::<Vec<u32>>();
+	bones.sort();
+	bones.dedup();
+
+	Ok(GroupInfo {
+		has_material: mesh_group.material(materials.unwrap_or_default()).is_some(),
+		name: mesh_group.name,
+		vertices: mesh_group.positions.len(),
+		faces: mesh_group.triangles.len(),
+		uv_sets,
+		has_normals: !mesh_group.normals.is_empty(),
+		unweighted_vertices,
+		bones,
+		morphs: mesh_group.morphs.into_iter().map(|morph| morph.name).collect()
+	})
+}
+
+fn print_info(info: &MeshInfo) {
+	println!("{} ({}), GMDC version {}, {} joints, {} vertices, {} faces",
+		info.name, info.id, info.version, info.joints, info.vertices, info.faces);
+	println!("{:<24} {:>8} {:>8} {:>4} {:>8} {:>11} {:>7}  MORPHS", "GROUP", "VERTICES", "FACES", "UVS", "NORMALS", "UNWEIGHTED", "BONES");
+	for group in &info.groups {
+		println!("{:<24} {:>8} {:>8} {:>4} {:>8} {:>11} {:>7}  {}",
+			group.name,
+			group.vertices,
+			group.faces,
+			group.uv_sets,
+			if group.has_normals { "yes" } else { "no" },
+			group.unweighted_vertices,
+			format!("{}/{}", group.bones.len(), info.joints),
+			if group.morphs.is_empty() { "-".to_string() } else { group.morphs.join(", ") });
+	}
+	for warning in &info.warnings {
+		println!("WARNING: {warning}");
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::dbpf::resource_types::gmdc::ElementData;
+	use crate::mesh::mesh_info::{ gmdc_info, group_info };
+	use crate::test_support::fixtures::{ mesh_gmdc_decoded, shpe_materials };
+
+	#[test]
+	fn mesh_info_counts_groups() {
+		let materials = shpe_materials();
+		let info = gmdc_info(&mesh_gmdc_decoded(), Some(&materials));
+		assert_eq!((info.joints, info.vertices, info.faces), (3, 3, 1));
+		assert_eq!(info.groups.len(), 1);
+		let body = &info.groups[0];
+		assert_eq!((body.vertices, body.faces, body.uv_sets), (3, 1, 1));
+		assert!(body.has_normals && body.has_material);
+		assert_eq!(body.unweighted_vertices, 0);
+		assert_eq!(body.bones, [0, 1]);
+		assert_eq!(body.morphs, ["fat"]);
+		// the hair subset has no group, and the outline isn't made of triangles
+		assert_eq!(info.unmatched_subsets, ["hair"]);
+		assert_eq!(info.warnings.len(), 2, "{:?}", info.warnings);
+	}
+
+	#[test]
+	fn mesh_info_reports_problems() {
+		let mut gmdc = mesh_gmdc_decoded();
+		// no normals, the first vertex on no bone, and the third on a joint the mesh doesn't have
+		gmdc.block.links[0].elements.retain(|element| *element != 1);
+		gmdc.block.elements[3].data = ElementData::Dword(vec![0xFFFFFFFF, 0xFFFFFF00, 0xFFFFFF01]);
+		gmdc.block.groups[0].used_joints = vec![0, 7];
+
+		let body = group_info(&gmdc, &gmdc.block.groups[0], None).unwrap();
+		assert!(!body.has_normals && !body.has_material);
+		assert_eq!(body.unweighted_vertices, 1);
+		assert_eq!(body.bones, [0, 7]);
+
+		let info = gmdc_info(&gmdc, Some(&[]));
+		let has_warning = |text: &str| info.warnings.iter().any(|warning| warning.contains(text));
+		assert!(has_warning("has no normals"), "{:?}", info.warnings);
+		assert!(has_warning("1 vertices not assigned to any bone"), "{:?}", info.warnings);
+		assert!(has_warning("uses bones [7], but the mesh only has 3 joints"), "{:?}", info.warnings);
+		assert!(has_warning("has no SHPE material"), "{:?}", info.warnings);
+		assert!(info.unmatched_subsets.is_empty());
+
+		let info = gmdc_info(&gmdc, Some(&shpe_materials()[1..]));
+		assert_eq!(info.unmatched_subsets, ["hair"]);
+		assert!(info.warnings.iter().any(|warning| warning.contains("SHPE subset \"hair\" has no group")), "{:?}", info.warnings);
+	}
+}
@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use clap::ValueEnum;
+
+use crate::dbpf::Identifier;
+use crate::dbpf::resource::DecodedResource;
+use crate::dbpf::resource_index::ResourceIndex;
+use crate::dbpf::resource_types::gmdc::{ GmdcBlock, GmdcElement, GmdcGroup, GmdcLink, ElementData, ElementIdentity };
+use crate::dbpf::resource_types::shpe::Material;
+use crate::file_names::sanitize;
+
+pub mod obj;
+pub mod gltf;
+pub mod export_mesh;
+pub mod import_mesh;
+pub mod mesh_info;
+
+// groups drawn as triangle lists; other primitive types aren't used by TS2 meshes
+pub const TRIANGLES: u32 = 2;
+// bone assignment byte for an unused slot
+const NO_BONE: u32 = 0xFF;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MeshFormat {
+	/// Wavefront OBJ with an MTL file for the materials
+	Obj,
+	/// Binary glTF (.glb), also carrying bone weights and morph targets
+	Gltf
+}
+
+impl MeshFormat {
+	pub fn extension(self) -> &'static str {
+		match self {
+			Self::Obj => "obj",
+			Self::Gltf => "glb"
+		}
+	}
+}
+
+pub struct MorphTarget {
+	pub name: String,
+	pub deltas: Vec<[f32; 3]>
+}
+
+// one GMDC group with its vertices gathered from the link's elements, ready to write out
+pub struct MeshGroup {
+	pub name: String,
+	pub positions: Vec<[f32; 3]>,
+	pub normals: Vec<[f32; 3]>,
+	pub uvs: Vec<[f32; 2]>,
+	// joints of the model (not the group) each vertex is assigned to, with their weights
+	pub joints: Vec<[u32; 4]>,
+	pub weights: Vec<[f32; 4]>,
+	pub morphs: Vec<MorphTarget>,
+	pub triangles: Vec<[u32; 3]>
+}
+
+impl MeshGroup {
+	// every triangle list group in the GMDC; anything else is skipped with a warning
+	pub fn from_gmdc(block: &GmdcBlock, warnings: &mut Vec<String>) -> Result<Vec<Self>, Box<dyn Error>> {
+		let mut groups = Vec::new();
+		for group in &block.groups {
+			if group.primitive_type != TRIANGLES {
+				warnings.push(format!("skipped group \"{}\" with primitive type {}", group.name, group.primitive_type));
+				continue;
+			}
+			groups.push(Self::from_group(block, group)
+				.map_err(|err| format!("group \"{}\": {err}", group.name))?);
+		}
+		Ok(groups)
+	}
+
+	pub fn from_group(block: &GmdcBlock, group: &GmdcGroup) -> Result<Self, Box<dyn Error>> {
+		let link = block.links.get(group.link as usize).ok_or(format!("link {} does not exist", group.link))?;
+		let num_vertices = link.referenced_size as usize;
+		let attribute = |identity| LinkAttribute::find(block, link, identity, num_vertices);
+
+		let positions = match attribute(ElementIdentity::Vertices)? {
+			Some(attribute) => attribute.vectors()?,
+			None => return Err("no vertices".into())
+		};
+		let normals = attribute(ElementIdentity::Normals)?.map(|a| a.vectors()).transpose()?.unwrap_or_default();
+		let uvs = attribute(ElementIdentity::UvCoordinates)?.map(|a| a.uvs()).transpose()?.unwrap_or_default();
+
+		let (joints, weights) = match attribute(ElementIdentity::BoneAssignments)? {
+			Some(assignments) => {
+				let weights = attribute(ElementIdentity::BoneWeights)?;
+				let mut joints = Vec::new();
+				let mut all_weights = Vec::new();
+				for v in 0..num_vertices {
+					let packed = assignments.dword(v)?;
+					let explicit = weights.as_ref().map(|w| w.floats(v)).transpose()?.unwrap_or_default();
+					let (vertex_joints, vertex_weights) = unpack_bones(packed, &explicit, &group.used_joints);
+					joints.push(vertex_joints);
+					all_weights.push(vertex_weights);
+				}
+				(joints, all_weights)
+			}
+			None => (Vec::new(), Vec::new())
+		};
+
+		let mut morphs = Vec::new();
+		for attribute in LinkAttribute::find_all(block, link, ElementIdentity::MorphVertexDeltas, num_vertices)? {
+			morphs.push(MorphTarget { name: morph_name(block, attribute.element), deltas: attribute.vectors()? });
+		}
+
+		let mut triangles = Vec::new();
+		for face in group.faces.chunks_exact(3) {
+			if let Some(index) = face.iter().find(|index| **index as usize >= num_vertices) {
+				return Err(format!("face index {index} out of {num_vertices} vertices").into());
+			}
+			triangles.push([face[0], face[1], face[2]]);
+		}
+
+		Ok(Self {
+			name: group.name.to_string(),
+			positions,
+			normals,
+			uvs,
+			joints,
+			weights,
+			morphs,
+			triangles
+		})
+	}
+
+	// the SHPE material for this group, matched on its subset name
+	pub fn material<'a>(&self, materials: &'a [Material]) -> Option<(usize, &'a Material)> {
+		materials.iter().enumerate()
+			.find(|(_, material)| material.subset.to_string().eq_ignore_ascii_case(&self.name))
+	}
+}
+
+// GMDC -> materials of the SHPE that uses it, through the GMND in between
+pub fn shpe_materials(index: &ResourceIndex) -> HashMap<Identifier, Vec<Material>> {
+	let mut materials = HashMap::new();
+	for resource in index.resources() {
+		let DecodedResource::Shpe(shpe) = resource else { continue };
+		let gmnd = shpe.gmnd_ref.as_ref().and_then(|gmnd_ref| index.get(gmnd_ref));
+		if let Some(DecodedResource::Gmnd(gmnd)) = gmnd {
+			materials.entry(gmnd.gmdc_ref.clone()).or_insert(shpe.block.materials.clone());
+		}
+	}
+	materials
+}
+
+// the file name mesh files for a GMDC are written with, without the ##0x...! group prefix
+pub fn mesh_name(block: &GmdcBlock) -> String {
+	let file_name = block.file_name.to_string();
+	sanitize(file_name.rsplit('!').next().unwrap_or_default())
+}
+
+// each delta element is one morph, named after the blend group it's assigned to
+pub fn morph_name(block: &GmdcBlock, element: &GmdcElement) -> String {
+	let repetition = element.repetition as usize;
+	block.model.blend_groups.get(repetition)
+		.map(|(_, element)| element.to_string())
+		.unwrap_or(format!("morph{repetition}"))
+}
+
+// up to four joints per vertex, 0xFF marking unused slots. joint indices are into the group's used joints.
+// the last assigned joint's weight is left out and makes the total up to 1
+fn unpack_bones(packed: u32, explicit: &[f32], used_joints: &[u32]) -> ([u32; 4], [f32; 4]) {
+	let mut joints = [0; 4];
+	let mut weights = [0.0; 4];
+	let mut remaining = 1.0 - explicit.iter().sum::<f32>();
+	for slot in 0..4 {
+		let local = (packed >> (slot * 8)) & 0xFF;
+		if local == NO_BONE {
+			continue;
+		}
+		joints[slot] = used_joints.get(local as usize).copied().unwrap_or(local);
+		weights[slot] = match explicit.get(slot) {
+			Some(weight) => *weight,
+			None => std::mem::replace(&mut remaining, 0.0)
+		};
+	}
+	(joints, weights)
+}
+
+// an element used by a link, with the alias list (if any) that maps the link's vertices to its values
+struct LinkAttribute<'a> {
+	element: &'a GmdcElement,
+	alias: Option<&'a [u32]>,
+	num_vertices: usize
+}
+
+impl<'a> LinkAttribute<'a> {
+	fn find(block: &'a GmdcBlock, link: &'a GmdcLink, identity: ElementIdentity, num_vertices: usize) -> Result<Option<Self>, Box<dyn Error>> {
+		Ok(Self::find_all(block, link, identity, num_vertices)?.into_iter().next())
+	}
+
+	fn find_all(block: &'a GmdcBlock, link: &'a GmdcLink, identity: ElementIdentity, num_vertices: usize) -> Result<Vec<Self>, Box<dyn Error>> {
+		let mut attributes = Vec::new();
+		for (position, element_index) in link.elements.iter().enumerate() {
+			let element = block.elements.get(*element_index as usize).ok_or(format!("element {element_index} does not exist"))?;
+			if element.identity != identity {
+				continue;
+			}
+			let alias = link.aliases.get(position).filter(|alias| !alias.is_empty()).map(|alias| alias.as_slice());
+			attributes.push(Self { element, alias, num_vertices });
+		}
+		Ok(attributes)
+	}
+
+	fn index(&self, vertex: usize) -> Result<usize, Box<dyn Error>> {
+		let index = match self.alias {
+			Some(alias) => *alias.get(vertex).ok_or(format!("vertex {vertex} has no alias in {}", self.element.identity))? as usize,
+			None => vertex
+		};
+		if index >= self.element.data.len() {
+			return Err(format!("vertex {vertex} is out of range of {}", self.element.identity).into());
+		}
+		Ok(index)
+	}
+
+	fn vectors(&self) -> Result<Vec<[f32; 3]>, Box<dyn Error>> {
+		let ElementData::Float3(values) = &self.element.data else {
+			return Err(format!("{} are not 3D vectors", self.element.identity).into());
+		};
+		(0..self.num_vertices).map(|v| Ok(values[self.index(v)?])).collect()
+	}
+
+	fn uvs(&self) -> Result<Vec<[f32; 2]>, Box<dyn Error>> {
+		let ElementData::Float2(values) = &self.element.data else {
+			return Err(format!("{} are not 2D vectors", self.element.identity).into());
+		};
+		(0..self.num_vertices).map(|v| Ok(values[self.index(v)?])).collect()
+	}
+
+	fn floats(&self, vertex: usize) -> Result<Vec<f32>, Box<dyn Error>> {
+		let index = self.index(vertex)?;
+		Ok(match &self.element.data {
+			ElementData::Float1(values) => vec![values[index]],
+			ElementData::Float2(values) => values[index].to_vec(),
+			ElementData::Float3(values) => values[index].to_vec(),
+			ElementData::Dword(_) => return Err(format!("{} are not floats", self.element.identity).into())
+		})
+	}
+
+	fn dword(&self, vertex: usize) -> Result<u32, Box<dyn Error>> {
+		let index = self.index(vertex)?;
+		match &self.element.data {
+			ElementData::Dword(values) => Ok(values[index]),
+			_ => Err(format!("{} are not packed values", self.element.identity).into())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::mesh::MeshGroup;
+	use crate::test_support::fixtures::mesh_gmdc;
+
+	#[test]
+	fn mesh_groups_gather_link_elements() {
+		let mut warnings = Vec::new();
+		let groups = MeshGroup::from_gmdc(&mesh_gmdc(), &mut warnings).unwrap();
+		assert_eq!(groups.len(), 1);
+		assert_eq!(warnings.len(), 1);
+		assert!(warnings[0].contains("outline"), "{warnings:?}");
+
+		let body = &groups[0];
+		assert_eq!(body.name, "body");
+		assert_eq!(body.positions, [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]);
+		assert_eq!(body.normals.len(), 3);
+		assert_eq!(body.uvs, [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]);
+		assert_eq!(body.joints, [[0, 0, 0, 0], [0, 0, 0, 0], [1, 0, 0, 0]]);
+		assert_eq!(body.weights, [[1.0, 0.0, 0.0, 0.0]; 3]);
+		assert_eq!(body.morphs.len(), 1);
+		assert_eq!(body.morphs[0].name, "fat");
+		assert_eq!(body.morphs[0].deltas, [[0.0, 0.5, 0.0], [0.0, 0.5, 0.0], [0.0, 0.0, 0.0]]);
+		assert_eq!(body.triangles, [[0, 1, 2]]);
+	}
+}
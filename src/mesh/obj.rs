@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use crate::backup::write_atomic;
+use crate::dbpf::resource_types::shpe::Material;
+use crate::mesh::MeshGroup;
+
+// writes each group as an OBJ object, with an MTL file next to it naming the SHPE materials.
+// uvs are flipped vertically, since OBJ puts the origin at the bottom left
+pub fn write_obj(path: &Path, groups: &[MeshGroup], materials: &[Material]) -> Result<(), Box<dyn Error>> {
+	let mtl_path = path.with_extension("mtl");
+	let mut obj = String::new();
+	if !materials.is_empty() {
+		writeln!(obj, "mtllib {}", mtl_path.file_name().unwrap_or_default().to_string_lossy())?;
+	}
+
+	// OBJ indices are 1-based and count up across the whole file, separately for each kind
+	let (mut first_position, mut first_uv, mut first_normal) = (1, 1, 1);
+	for group in groups {
+		writeln!(obj, "o {}", group.name)?;
+		for [x, y, z] in &group.positions {
+			writeln!(obj, "v {x} {y} {z}")?;
+		}
+		for [u, v] in &group.uvs {
+			writeln!(obj, "vt {u} {}", 1.0 - v)?;
+		}
+		for [x, y, z] in &group.normals {
+			writeln!(obj, "vn {x} {y} {z}")?;
+		}
+		if let Some((_, material)) = group.material(materials) {
+			writeln!(obj, "usemtl {}", material.subset)?;
+		}
+		for triangle in &group.triangles {
+			let corners = triangle.map(|index| {
+				let position = index + first_position;
+				let uv = if group.uvs.is_empty() { String::new() } else { (index + first_uv).to_string() };
+				if group.normals.is_empty() {
+					if uv.is_empty() { position.to_string() } else { format!("{position}/{uv}") }
+				} else {
+					format!("{position}/{uv}/{}", index + first_normal)
+				}
+			});
+			writeln!(obj, "f {}", corners.join(" "))?;
+		}
+		first_position += group.positions.len() as u32;
+		first_uv += group.uvs.len() as u32;
+		first_normal += group.normals.len() as u32;
+	}
+	write_atomic(path, obj.as_bytes())?;
+
+	if !materials.is_empty() {
+		let mut mtl = String::new();
+		for material in materials {
+			writeln!(mtl, "# {}", material.txmt_name)?;
+			writeln!(mtl, "newmtl {}", material.subset)?;
+			writeln!(mtl, "Kd 1 1 1")?;
+			writeln!(mtl)?;
+		}
+		write_atomic(&mtl_path, mtl.as_bytes())?;
+	}
+
+	Ok(())
+}
+
+// reads each object (or group) in an OBJ file as a mesh group. OBJ indexes positions, uvs and normals
+// separately, so every distinct combination becomes its own vertex; polygons are split into triangle fans
+pub fn read_obj(path: &Path) -> Result<Vec<MeshGroup>, Box<dyn Error>> {
+	let text = fs::read_to_string(path)?;
+	let mut positions: Vec<[f32; 3]> = Vec::new();
+	let mut uvs: Vec<[f32; 2]> = Vec::new();
+	let mut normals: Vec<[f32; 3]> = Vec::new();
+
+	let mut groups: Vec<MeshGroup> = Vec::new();
+	let mut vertices: HashMap<(usize, Option<usize>, Option<usize>), u32> = HashMap::new();
+
+	for (line_number, line) in text.lines().enumerate() {
+		let mut tokens = line.split_whitespace();
+		let Some(keyword) = tokens.next() else { continue };
+		let tokens = tokens.collect::<Vec<&str>>();
+		let error = |message: String| format!("{} line {}: {message}", path.to_string_lossy(), line_number + 1);
+		let floats = || tokens.iter()
+			.map(|token| token.parse::<f32>().map_err(|err| error(format!("invalid number \"{token}\": {err}"))))
+			.collect::<Result<Vec<f32>, String>>();
+
+		match keyword {
+			"v" | "vn" => {
+				let values = floats()?;
+				let [x, y, z] = values[..] else { return Err(error(format!("expected 3 values, got {}", values.len())).into()) };
+				if keyword == "v" { positions.push([x, y, z]) } else { normals.push([x, y, z]) }
+			}
+			"vt" => {
+				let values = floats()?;
+				let (Some(u), Some(v)) = (values.first(), values.get(1)) else { return Err(error("expected 2 values".to_string()).into()) };
+				uvs.push([*u, 1.0 - v]);
+			}
+			"o" | "g" => {
+				let name = tokens.join(" ");
+				if groups.last().is_none_or(|group| group.name != name) {
+					groups.push(empty_group(name));
+					vertices.clear();
+				}
+			}
+			"f" => {
+				if groups.is_empty() {
+					groups.push(empty_group(String::new()));
+				}
+				let group = groups.last_mut().unwrap();
+				let mut corners = Vec::new();
+				for token in &tokens {
+					let mut parts = token.split('/');
+					let position = resolve(parts.next(), positions.len()).map_err(error)?
+						.ok_or(error(format!("face corner \"{token}\" has no position")))?;
+					let uv = resolve(parts.next(), uvs.len()).map_err(error)?;
+					let normal = resolve(parts.next(), normals.len()).map_err(error)?;
+					let index = *vertices.entry((position, uv, normal)).or_insert_with(|| {
+						group.positions.push(positions[position]);
+						group.uvs.extend(uv.map(|uv| uvs[uv]));
+						group.normals.extend(normal.map(|normal| normals[normal]));
+						group.positions.len() as u32 - 1
+					});
+					corners.push(index);
+				}
+				for i in 1..corners.len().saturating_sub(1) {
+					group.triangles.push([corners[0], corners[i], corners[i + 1]]);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	groups.retain(|group| !group.triangles.is_empty());
+	for group in &groups {
+		if (!group.uvs.is_empty() && group.uvs.len() != group.positions.len()) || (!group.normals.is_empty() && group.normals.len() != group.positions.len()) {
+			return Err(format!("\"{}\" only has uvs or normals on some of its faces", group.name).into());
+		}
+	}
+	Ok(groups)
+}
+
+fn empty_group(name: String) -> MeshGroup {
+	MeshGroup {
+		name,
+		positions: Vec::new(),
+		normals: Vec::new(),
+		uvs: Vec::new(),
+		joints: Vec::new(),
+		weights: Vec::new(),
+		morphs: Vec::new(),
+		triangles: Vec::new()
+	}
+}
+
+// turns a 1-based (or negative, counting back from the end) OBJ index into a 0-based one
+fn resolve(part: Option<&str>, len: usize) -> Result<Option<usize>, String> {
+	let Some(part) = part.filter(|part| !part.is_empty()) else { return Ok(None) };
+	let index = part.parse::<i64>().map_err(|err| format!("invalid index \"{part}\": {err}"))?;
+	let resolved = if index < 0 { len as i64 + index } else { index - 1 };
+	if resolved < 0 || resolved >= len as i64 {
+		return Err(format!("index {index} out of range"));
+	}
+	Ok(Some(resolved as usize))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	
+	use crate::mesh::MeshGroup;
+	use crate::mesh::obj::{ read_obj, write_obj };
+	use crate::test_support::test_dir;
+	use crate::test_support::fixtures::{ mesh_gmdc, shpe_materials };
+
+	#[test]
+	fn obj_export_writes_groups_and_materials() {
+		let groups = MeshGroup::from_gmdc(&mesh_gmdc(), &mut Vec::new()).unwrap();
+		let dir = test_dir("obj_export");
+		let path = dir.join("mesh.obj");
+		write_obj(&path, &groups, &shpe_materials()).unwrap();
+
+		let obj = fs::read_to_string(&path).unwrap();
+		let lines = obj.lines().collect::<Vec<&str>>();
+		assert_eq!(lines[0], "mtllib mesh.mtl");
+		assert!(lines.contains(&"o body") && lines.contains(&"usemtl body"), "{obj}");
+		// uvs are flipped
+		assert!(lines.contains(&"vt 1 1") && lines.contains(&"vt 0 0"), "{obj}");
+		assert!(lines.contains(&"f 1/1/1 2/2/2 3/3/3"), "{obj}");
+		let mtl = fs::read_to_string(path.with_extension("mtl")).unwrap();
+		assert!(mtl.contains("newmtl body") && mtl.contains("newmtl hair"), "{mtl}");
+
+		let read = read_obj(&path).unwrap();
+		assert_eq!(read.len(), 1);
+		assert_eq!(read[0].positions, groups[0].positions);
+		assert_eq!(read[0].uvs, groups[0].uvs);
+		assert_eq!(read[0].triangles, groups[0].triangles);
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
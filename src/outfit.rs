@@ -1,8 +1,10 @@
 use std::error::Error;
 
-use crate::dbpf::{ TypeId, PascalString };
+use crate::dbpf::{ Identifier, TypeId, PascalString };
+use crate::error::ClodError;
 
 use crate::dbpf::resource::DecodedResource;
+use crate::dbpf::resource_index::ResourceIndex;
 
 use crate::dbpf::resource_types::gzps::Gzps;
 use crate::dbpf::resource_types::idr::Idr;
@@ -35,127 +37,82 @@ pub struct Outfit {
 
 impl Outfit {
 	pub fn from_resources(gzps: Gzps, resources: &[DecodedResource], ignore_missing: bool) -> Result<Self, Box<dyn Error>> {
+		Self::from_index(gzps, &ResourceIndex::new(resources), ignore_missing)
+	}
+
+	// like from_resources, but reuses an index built once for many GZPS
+	pub fn from_index(gzps: Gzps, index: &ResourceIndex, ignore_missing: bool) -> Result<Self, Box<dyn Error>> {
 		// find 3IDR
-		let idr = resources.iter().find_map(|res| -> Option<Idr> {
-			if let DecodedResource::Idr(idr) = res {
-				if idr.id.group_id == gzps.id.group_id
-					&& idr.id.instance_id == gzps.id.instance_id
-					&& idr.id.resource_id == gzps.id.resource_id {
-						return Some(idr.clone());
-				}
-			}
-			None
-		}).ok_or(format!("Missing 3IDR for {}", gzps.id))?.clone();
+		let idr_id = Identifier { type_id: TypeId::Idr, ..gzps.id.clone() };
+		let idr = match index.get(&idr_id) {
+			Some(DecodedResource::Idr(idr)) => idr.clone(),
+			_ => return Err(ClodError::missing(&gzps.id, TypeId::Idr, Some(&idr_id)).into())
+		};
 
 		// find SHPE
 		let shpe = if let Some(shpe_ref) = &idr.shpe_ref {
-			resources.iter().find_map(|res| -> Option<Shpe> {
-				if let DecodedResource::Shpe(shpe) = res {
-					if shpe.id == *shpe_ref {
-						return Some(shpe.clone());
-					}
-				}
-				None
-			})
+			match index.get(shpe_ref) {
+				Some(DecodedResource::Shpe(shpe)) => Some(shpe.clone()),
+				_ => None
+			}
 		} else {
-			return Err("3IDR does not define SHPE".into())
+			return Err(ClodError::missing(&idr.id, TypeId::Shpe, None).into())
 		};
 
 		if !ignore_missing && shpe.is_none() {
-			return Err(format!("Missing {}", idr.shpe_ref.unwrap()).into());
+			return Err(ClodError::missing(&idr.id, TypeId::Shpe, idr.shpe_ref.as_ref()).into());
 		}
 
 		// find CRES
 		let cres = if let Some(cres_ref) = &idr.cres_ref {
-			resources.iter().find_map(|res| -> Option<Cres> {
-				if let DecodedResource::Cres(cres) = res {
-					if cres.id == *cres_ref {
-						return Some(cres.clone());
-					}
-				}
-				None
-			})
+			match index.get(cres_ref) {
+				Some(DecodedResource::Cres(cres)) => Some(cres.clone()),
+				_ => None
+			}
 		} else {
-			return Err("3IDR does not define CRES".into())
+			return Err(ClodError::missing(&idr.id, TypeId::Cres, None).into())
 		};
 
 		if !ignore_missing && cres.is_none() {
-			return Err(format!("Missing {}", idr.cres_ref.unwrap()).into());
+			return Err(ClodError::missing(&idr.id, TypeId::Cres, idr.cres_ref.as_ref()).into());
 		}
 
 		// find GMND
-		let gmnd = if let Some(shpe) = &shpe {
-			resources.iter().find_map(|res| -> Option<Gmnd> {
-				if let DecodedResource::Gmnd(gmnd) = res {
-					if let Some(gmnd_ref) = &shpe.gmnd_ref {
-						if gmnd.id == *gmnd_ref {
-							return Some(gmnd.clone());
-						}
-					}
-				}
-				None
-			})
-		} else {
-			None
+		let gmnd = match shpe.as_ref().and_then(|shpe| shpe.gmnd_ref.as_ref()).and_then(|gmnd_ref| index.get(gmnd_ref)) {
+			Some(DecodedResource::Gmnd(gmnd)) => Some(gmnd.clone()),
+			_ => None
 		};
 
-		if !ignore_missing && shpe.is_some() && gmnd.is_none() {
-			if let Some(gmnd_ref) = shpe.unwrap().gmnd_ref {
-				return Err(format!("Missing {gmnd_ref}").into());
-			} else {
-				return Err("Missing GMND ref".into());
-			}
+		if let Some(shpe) = &shpe && !ignore_missing && gmnd.is_none() {
+			return Err(ClodError::missing(&shpe.id, TypeId::Gmnd, shpe.gmnd_ref.as_ref()).into());
 		}
 
 		// find GMDC
-		let gmdc = if let Some(gmnd) = &gmnd {
-			resources.iter().find_map(|res| -> Option<Gmdc> {
-				if let DecodedResource::Gmdc(gmdc) = res {
-					if gmdc.id == gmnd.gmdc_ref {
-						return Some(gmdc.clone());
-					}
-				}
-				None
-			})
-		} else {
-			None
+		let gmdc = match gmnd.as_ref().and_then(|gmnd| index.get(&gmnd.gmdc_ref)) {
+			Some(DecodedResource::Gmdc(gmdc)) => Some(gmdc.clone()),
+			_ => None
 		};
 
-		if !ignore_missing && gmnd.is_some() && gmdc.is_none() {
-			return Err(format!("Missing {}", gmnd.unwrap().gmdc_ref).into());
+		if let Some(gmnd) = &gmnd && !ignore_missing && gmdc.is_none() {
+			return Err(ClodError::missing(&gmnd.id, TypeId::Gmdc, Some(&gmnd.gmdc_ref)).into());
 		}
 
 		// find TXMTs
 		let mut txmts = Vec::new();
 		for txmt_ref in &idr.txmt_refs {
-			if let Some(txmt) =
-				resources.iter().find_map(|res| -> Option<Txmt> {
-					if let DecodedResource::Txmt(txmt) = res {
-						if txmt.id == *txmt_ref {
-							return Some(txmt.clone());
-						}
-					}
-					None
-				}) {
-					txmts.push(txmt);
-				} else if !ignore_missing {
-					return Err(format!("Missing {}", txmt_ref).into());
-				}
+			match index.get(txmt_ref) {
+				Some(DecodedResource::Txmt(txmt)) => txmts.push(txmt.clone()),
+				_ if !ignore_missing => return Err(ClodError::missing(&idr.id, TypeId::Txmt, Some(txmt_ref)).into()),
+				_ => {}
+			}
 		}
 
 		// find TXTRs
 		let mut txtrs = Vec::new();
-		let all_txtrs: Vec<Txtr> = resources
-			.iter()
-			.filter_map(|r| if let DecodedResource::Txtr(txtr) = r { Some(txtr.clone()) } else { None })
-			.collect();
 		for txmt in &txmts {
-			let txtr_names: Vec<String> = txmt.txtr_names.iter().map(|s| format!("{}_txtr", s)).collect();
-			for txtr in &all_txtrs {
-				if txtr_names.contains(&txtr.name.to_string()) {
-					txtrs.push(txtr.clone());
-				}
-			}
+			let txtr_names = txmt.txtr_names.iter().map(|name| format!("{name}_txtr"));
+			txtrs.extend(index.by_names(txtr_names).into_iter()
+				.filter_map(|r| if let DecodedResource::Txtr(txtr) = r { Some(txtr.clone()) } else { None }));
 		}
 
 		Ok(Self {
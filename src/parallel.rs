@@ -0,0 +1,88 @@
+use std::error::Error;
+use std::sync::Mutex;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::thread;
+
+use crate::error::sendable;
+
+// number of worker threads; 0 means one per available core
+static THREADS: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_threads(threads: usize) {
+	THREADS.store(threads, Ordering::Relaxed);
+}
+
+pub fn threads() -> usize {
+	match THREADS.load(Ordering::Relaxed) {
+		0 => thread::available_parallelism().map_or(1, |n| n.get()),
+		threads => threads
+	}
+}
+
+// runs f on every item across the worker threads, returning the results in the same order as the items
+pub fn par_map<T: Send, R: Send>(items: impl IntoIterator<Item = T>, f: impl Fn(T) -> R + Sync) -> Vec<R> {
+	let items = items.into_iter().collect::<Vec<T>>();
+	let num_items = items.len();
+	let num_threads = threads().min(num_items);
+	if num_threads <= 1 {
+		return items.into_iter().map(f).collect();
+	}
+
+	let queue = Mutex::new(items.into_iter().enumerate());
+	let mut results = thread::scope(|scope| {
+		let workers = (0..num_threads)
+			.map(|_| scope.spawn(|| {
+				let mut results = Vec::new();
+				loop {
+					let next = queue.lock().unwrap_or_else(|err| err.into_inner()).next();
+					match next {
+						Some((i, item)) => results.push((i, f(item))),
+						None => return results
+					}
+				}
+			}))
+			.collect::<Vec<_>>();
+		workers.into_iter()
+			.flat_map(|worker| worker.join().unwrap_or_else(|err| std::panic::resume_unwind(err)))
+			.collect::<Vec<(usize, R)>>()
+	});
+	results.sort_by_key(|(i, _)| *i);
+	results.into_iter().map(|(_, result)| result).collect()
+}
+
+// like par_map, but fails with the error of the first item (in item order) that failed
+pub fn try_par_map<T: Send, R: Send>(items: impl IntoIterator<Item = T>, f: impl Fn(T) -> Result<R, Box<dyn Error>> + Sync) -> Result<Vec<R>, Box<dyn Error>> {
+	par_map(items, |item| f(item).map_err(sendable))
+		.into_iter()
+		.map(|result| result.map_err(|err| -> Box<dyn Error> { err }))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+	
+	use crate::dbpf::Dbpf;
+	use crate::dbpf::header::Header;
+	use crate::dbpf::index::DbpfIndex;
+	use crate::error::ClodError;
+	use crate::parallel::set_threads;
+	use crate::test_support::fixtures::{ synthetic_resources, txmt_resource };
+
+	#[test]
+	fn decode_errors_survive_the_worker_threads() {
+		let mut broken = txmt_resource();
+		broken.data.truncate(broken.data.len() - 10);
+		let mut resources = synthetic_resources();
+		resources.push(broken.clone());
+
+		let mut package = Cursor::new(Vec::new());
+		Dbpf::write_resources(resources, Header::default(), &mut package, false).unwrap();
+		set_threads(2);
+		let err = DbpfIndex::new(Cursor::new(package.get_ref().as_slice())).unwrap().decode_all("test", false).err().unwrap();
+		match err.downcast_ref::<ClodError>() {
+			Some(ClodError::Decode { id, offset: Some(_), position: Some(_), .. }) => assert_eq!(*id, broken.id),
+			_ => panic!("no located decode error: {err}")
+		}
+	}
+}
@@ -1,2 +1,48 @@
+use std::error::Error;
+use std::path::Path;
+
+use crate::dbpf::Dbpf;
+
 pub mod recolor_object;
 pub mod recolor_outfit;
+
+// in lenient mode, resources that fail to decode are collected into warnings instead of failing
+fn read_package(path: &Path, lenient: bool, warnings: &mut Vec<String>) -> Result<Dbpf, Box<dyn Error>> {
+	let package = if lenient {
+		Dbpf::read_from_file_lenient(path, "")?
+	} else {
+		Dbpf::read_from_file(path, "")?
+	};
+	warnings.extend(package.warnings.iter().cloned());
+	Ok(package)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use crate::dbpf::resource::DecodedResource;
+	use crate::recolor::read_package;
+	use crate::test_support::{ test_dir, write_package };
+	use crate::test_support::fixtures::{ gmdc_resource, gzps_resource, txmt_resource };
+
+	#[test]
+	fn lenient_reads_keep_corrupt_resources() {
+		let dir = test_dir("lenient");
+		let file = dir.join("corrupt.package");
+		let mut gmdc = gmdc_resource();
+		gmdc.data.truncate(gmdc.data.len() / 2);
+		write_package(&file, vec![(gzps_resource(), false), (gmdc.clone(), true), (txmt_resource(), false)]);
+
+		let mut warnings = Vec::new();
+		assert!(read_package(&file, false, &mut warnings).is_err());
+		assert!(warnings.is_empty());
+
+		let package = read_package(&file, true, &mut warnings).unwrap();
+		assert_eq!(warnings.len(), 1);
+		assert!(warnings[0].contains(&gmdc.id.to_string()), "{}", warnings[0]);
+		assert!(matches!(&package.resources[1], DecodedResource::Other(other) if other.data == gmdc.data));
+		assert!(matches!(package.resources[0], DecodedResource::Gzps(_)) && matches!(package.resources[2], DecodedResource::Txmt(_)));
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
@@ -5,10 +5,14 @@ use rand::Rng;
 
 use crate::dbpf::{ Dbpf, PascalString, SevenBitString };
 use crate::dbpf::resource::DecodedResource;
+use crate::dbpf::resource_index::ResourceIndex;
 use crate::dbpf::resource_types::mmat::Mmat;
 use crate::dbpf::resource_types::txmt::Txmt;
 use crate::dbpf::resource_types::txtr::Txtr;
 use crate::crc::{ hash_crc24, hash_crc32 };
+use crate::error::report_warnings;
+
+use super::read_package;
 
 #[derive(Clone)]
 pub struct ObjectRecolor {
@@ -34,17 +38,31 @@ impl ObjectRecolor {
 			let txtr_name_txtr = format!("{txtr_name}_txtr");
 			txtr.name = SevenBitString::new(&txtr_name_txtr);
 			txtr.block.file_name = SevenBitString::new(&txtr_name_txtr);
+			txtr.block.file_name_repeat = Some(SevenBitString::new(&txtr_name));
 			txtr.id.resource_id = hash_crc32(&txtr_name_txtr);
 			txtr.id.instance_id = hash_crc24(&txtr_name_txtr);
-			if let Some(txtr_ref) = self.txmt.block.properties.iter_mut().find(|p| p.name.to_string() == "stdMatBaseTextureName") {
-				txtr_ref.value = SevenBitString::new(&format!("##0x{:08x}!{}", txtr.id.group_id, txtr_name));
+			if self.txmt.block.properties.iter().any(|p| p.name.to_string() == "stdMatBaseTextureName") {
+				self.txmt.block.set_property("stdMatBaseTextureName", SevenBitString::new(&format!("##0x{:08x}!{}", txtr.id.group_id, txtr_name)));
 			}
 		}
 	}
 }
 
-pub fn recolor_object(file: PathBuf, title: Option<String>, number: Option<usize>, subset: Option<String>) -> Result<(), Box<dyn Error>> {
-	let package = Dbpf::read_from_file(&file, "")?;
+#[derive(Clone, Default)]
+pub struct ObjectRecolorOptions {
+	pub file: PathBuf,
+	pub title: Option<String>,
+	pub number: Option<usize>,
+	// only recolor this subset, rather than all of them
+	pub subset: Option<String>,
+	// keep going when resources fail to decode, and report them at the end
+	pub lenient: bool
+}
+
+pub fn recolor_object(options: ObjectRecolorOptions) -> Result<(), Box<dyn Error>> {
+	let ObjectRecolorOptions { file, title, number, subset, lenient } = options;
+	let mut warnings = Vec::new();
+	let package = read_package(&file, lenient, &mut warnings)?;
 
 	let default_mmats = package.resources.iter().filter_map(|res| {
 		if let DecodedResource::Mmat(mmat) = res {
@@ -62,11 +80,17 @@ pub fn recolor_object(file: PathBuf, title: Option<String>, number: Option<usize
 	let title = title.unwrap_or(file.file_stem().unwrap().to_string_lossy().to_string());
 
 	let resources = make_recolors(&default_colors, number, &title);
-	save_recolors(&file, resources, &title)
+	save_recolors(&file, resources, &title)?;
+
+	report_warnings(&warnings);
+
+	Ok(())
 }
 
-pub fn clone_recolor(file: PathBuf, title: Option<String>, number: Option<usize>, subset: Option<String>) -> Result<(), Box<dyn Error>> {
-	let package = Dbpf::read_from_file(&file, "")?;
+pub fn clone_recolor(options: ObjectRecolorOptions) -> Result<(), Box<dyn Error>> {
+	let ObjectRecolorOptions { file, title, number, subset, lenient } = options;
+	let mut warnings = Vec::new();
+	let package = read_package(&file, lenient, &mut warnings)?;
 
 	let mut subsets = Vec::new();
 	let mut mmats = Vec::new();
@@ -86,31 +110,24 @@ pub fn clone_recolor(file: PathBuf, title: Option<String>, number: Option<usize>
 	let title = title.unwrap_or(file.file_stem().unwrap().to_string_lossy().to_string());
 
 	let resources = make_recolors(&base_colors, number, &title);
-	save_recolors(&file, resources, &title)
+	save_recolors(&file, resources, &title)?;
+
+	report_warnings(&warnings);
+
+	Ok(())
 }
 
 fn get_recolors(package: &Dbpf, mmats: &[Mmat]) -> Vec<ObjectRecolor> {
+	let index = ResourceIndex::new(&package.resources);
 	mmats.iter().filter_map(|mmat| {
-		let txmt_name = format!("{}_txmt", mmat.name).to_lowercase();
-		if let Some(txmt) = package.resources.iter().find_map(|res| {
-				if let DecodedResource::Txmt(txmt) = res {
-					if txmt.block.material_definition.to_string().to_lowercase() == txmt_name ||
-						format!("##0x{:08x}!{}", txmt.id.group_id, txmt.block.material_definition).to_lowercase() == txmt_name {
-							return Some(txmt);
-					}
-				}
-				None
+		let txmt_name = format!("{}_txmt", mmat.name);
+		if let Some(txmt) = index.by_name(&txmt_name).into_iter().find_map(|res| {
+				if let DecodedResource::Txmt(txmt) = res { Some(txmt) } else { None }
 			}) {
 				let txtr = if let Some(txtr_ref) = txmt.block.properties.iter().find(|p| p.name.to_string() == "stdMatBaseTextureName") {
-					let txtr_name = format!("{}_txtr", txtr_ref.value).to_lowercase();
-					package.resources.iter().find_map(|res| {
-						if let DecodedResource::Txtr(txtr) = res {
-							if txtr.name.to_string().to_lowercase() == txtr_name ||
-								format!("##0x{:08x}!{}", txtr.id.group_id, txtr.name).to_lowercase() == txtr_name {
-									return Some(txtr.clone());
-							}
-						}
-						None
+					let txtr_name = format!("{}_txtr", txtr_ref.value);
+					index.by_name(&txtr_name).into_iter().find_map(|res| {
+						if let DecodedResource::Txtr(txtr) = res { Some(txtr.clone()) } else { None }
 					})
 				} else {
 					None
@@ -152,9 +169,9 @@ fn make_recolors(base_colors: &[ObjectRecolor], number: usize, title: &str) -> V
 			new_color.rename(&title.replace(' ', ".").replace('_', ".").replace('-', "."), &format!("{:08x}", guid));
 
 			if let Some(txtr_ref_og) = color.txmt.block.properties.iter().find(|p| p.name.to_string() == "stdMatBaseTextureName") {
-				if let Some(txtr_ref_new) = new_color.txmt.block.properties.iter_mut().find(|p| p.name.to_string() == "stdMatBaseTextureName") {
+				if let Some(txtr_ref_new) = new_color.txmt.block.properties.iter().find(|p| p.name.to_string() == "stdMatBaseTextureName") {
 					if let Some(used_txtr) = txtrs_used.get(&txtr_ref_og.value) {
-						txtr_ref_new.value = used_txtr.clone();
+						new_color.txmt.block.set_property("stdMatBaseTextureName", used_txtr.clone());
 						new_color.txtr = None;
 					} else {
 						txtrs_used.insert(txtr_ref_og.value.clone(), txtr_ref_new.value.clone());
@@ -175,5 +192,5 @@ fn make_recolors(base_colors: &[ObjectRecolor], number: usize, title: &str) -> V
 fn save_recolors(file: &PathBuf, resources: Vec<DecodedResource>, title: &str) -> Result<(), Box<dyn Error>> {
 	let mut package = Dbpf::new(resources)?;
 	package.is_compressed = true;
-	package.write_to_file(&file.with_file_name(format!("{title}_RECS.package")))
+	package.write_to_file(&file.with_file_name(format!("{title}_RECS.package")), false)
 }
@@ -11,6 +11,9 @@ use crate::dbpf::resource_types::idr::Idr;
 use crate::dbpf::resource_types::txmt::Txmt;
 use crate::dbpf::resource_types::txtr::{ Txtr, TxtrPurpose };
 use crate::crc::hash_crc32;
+use crate::error::report_warnings;
+
+use super::read_package;
 
 #[derive(Clone)]
 pub struct OutfitRecolor {
@@ -42,15 +45,45 @@ impl OutfitRecolor {
 	}
 
 	pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
-		self.to_package()?.write_to_file(path)
+		self.to_package()?.write_to_file(path, false)
 	}
 }
 
-pub fn recolor_outfit_from_template(files: Vec<PathBuf>, title: Option<String>, number: Option<usize>, repo: bool) -> Result<(), Box<dyn Error>> {
+#[derive(Clone, Default)]
+pub struct TemplateRecolorOptions {
+	// first file is the main template; any others are repositoried to its textures if repo is set
+	pub files: Vec<PathBuf>,
+	pub title: Option<String>,
+	pub number: Option<usize>,
+	pub repo: bool,
+	// keep going when resources fail to decode, and report them at the end
+	pub lenient: bool
+}
+
+#[derive(Clone, Default)]
+pub struct MeshRecolorOptions {
+	// first file is the mesh; any others are repositoried to its textures
+	pub files: Vec<PathBuf>,
+	pub title: Option<String>,
+	pub number: Option<usize>,
+	// "top", "bottom", or "body"
+	pub part: String,
+	// eg. "am", "ef", or "cu"
+	pub age_gender: String,
+	// eg. "everyday" or "everyday_formal"
+	pub category: Option<String>,
+	pub shoe: Option<String>,
+	// keep going when resources fail to decode, and report them at the end
+	pub lenient: bool
+}
+
+pub fn recolor_outfit_from_template(options: TemplateRecolorOptions) -> Result<(), Box<dyn Error>> {
+	let TemplateRecolorOptions { files, title, number, repo, lenient } = options;
+	let mut warnings = Vec::new();
 	let mut main_refs = Vec::new();
 	let title = title.unwrap_or("OutfitRecolor".to_string());
 	for file in files {
-		let package = Dbpf::read_from_file(&file, "")?;
+		let package = read_package(&file, lenient, &mut warnings)?;
 		let template = create_outfit_template(&package)?;
 		let is_main = main_refs.is_empty();
 		for i in 0..number.unwrap_or(1) {
@@ -73,6 +106,8 @@ pub fn recolor_outfit_from_template(files: Vec<PathBuf>, title: Option<String>,
 		}
 	}
 
+	report_warnings(&warnings);
+
 	Ok(())
 }
 
@@ -140,11 +175,13 @@ fn create_outfit_template(package: &Dbpf) -> Result<OutfitRecolor, Box<dyn Error
 	})
 }
 
-pub fn recolor_outfit_from_mesh(files: Vec<PathBuf>, title: Option<String>, number: Option<usize>, part: String, age_gender: String, category: Option<String>, shoe: Option<String>) -> Result<(), Box<dyn Error>> {
-	let file = &files[0];
+pub fn recolor_outfit_from_mesh(options: MeshRecolorOptions) -> Result<(), Box<dyn Error>> {
+	let MeshRecolorOptions { files, title, number, part, age_gender, category, shoe, lenient } = options;
+	let mut warnings = Vec::new();
+	let file = files.first().ok_or("No package files given")?;
 	let repo_files = &files[1..];
 
-	let package = Dbpf::read_from_file(file, "")?;
+	let package = read_package(file, lenient, &mut warnings)?;
 
 	let cres_id = package.resources.iter().find_map(|r| {
 		if let DecodedResource::Cres(cres) = r {
@@ -210,7 +247,7 @@ pub fn recolor_outfit_from_mesh(files: Vec<PathBuf>, title: Option<String>, numb
 		};
 		let mut repo_ids: Vec<Identifier> = Vec::new();
 		if let Some(repo_file) = repo_files.get(i) {
-			let recolor_package = Dbpf::read_from_file(&repo_file, "")?;
+			let recolor_package = read_package(repo_file, lenient, &mut warnings)?;
 			repo_ids = recolor_package.resources.iter().find_map(|r| {
 				if let DecodedResource::Idr(idr) = r {
 					Some(idr.txmt_refs.clone())
@@ -224,6 +261,8 @@ pub fn recolor_outfit_from_mesh(files: Vec<PathBuf>, title: Option<String>, numb
 		recolor.save(&path)?;
 	}
 
+	report_warnings(&warnings);
+
 	Ok(())
 }
 
@@ -277,6 +316,7 @@ fn create_outfit_recolor_from_mesh(spec: &OutfitSpec, cres_id: &Identifier, shpe
 
 	let idr = Idr {
 		id: Identifier::new(u32::from(TypeId::Idr), spec.guid, 0, 1),
+		version: 2,
 		cres_ref: Some(cres_id.clone()),
 		shpe_ref: Some(shpe_id.clone()),
 		txmt_refs,
@@ -0,0 +1,262 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::path::{ Path, PathBuf };
+
+use clap::ValueEnum;
+
+use crate::backup::write_atomic_with;
+use crate::dbpf::{ Dbpf, Identifier, TypeId };
+use crate::dbpf::resource::{ Resource, DecodedResource };
+use crate::dbpf::resource_index::ResourceIndex;
+use crate::file_names::{ sanitize, unique_name };
+use crate::outfit::Outfit;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SplitMode {
+	/// One package per GZPS, with everything it references
+	Outfit,
+	/// One package per MMAT, with its TXMT and TXTRs
+	Object,
+	/// One package per resource type
+	Type
+}
+
+pub fn split_package(file: PathBuf, output: Option<PathBuf>, mode: SplitMode) -> Result<(), Box<dyn Error>> {
+	let output_dir = output.unwrap_or(file.with_extension(""));
+	fs::create_dir_all(&output_dir)?;
+
+	let stem = file.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+
+	match mode {
+		SplitMode::Outfit => split_by_outfit(&file, &output_dir, &stem),
+		SplitMode::Object => split_by_object(&file, &output_dir, &stem),
+		SplitMode::Type => split_by_type(&file, &output_dir, &stem)
+	}
+}
+
+fn split_by_outfit(file: &Path, output_dir: &Path, stem: &str) -> Result<(), Box<dyn Error>> {
+	let package = Dbpf::read_from_file(file, stem)?;
+	let resources = &package.resources;
+	let index = ResourceIndex::new(resources);
+
+	let mut used_ids: HashSet<Identifier> = HashSet::new();
+	let mut used_names: HashSet<String> = HashSet::new();
+
+	for resource in resources {
+		let DecodedResource::Gzps(gzps) = resource else { continue };
+
+		let outfit = match Outfit::from_index(gzps.clone(), &index, true) {
+			Ok(outfit) => outfit,
+			Err(err) => {
+				println!("WARNING: skipping \"{}\": {err}", gzps.name);
+				continue;
+			}
+		};
+
+		let mut outfit_resources = outfit.get_resources();
+
+		// pick up the BINX and STR# that belong with the GZPS as well
+		let binx_id = Identifier { type_id: TypeId::Binx, ..gzps.id.clone() };
+		let extra_ids = [Some(binx_id), outfit.idr.str_ref.clone()];
+		for extra_id in extra_ids.iter().flatten() {
+			if let Some(extra) = index.get(extra_id) {
+				outfit_resources.push(extra.clone());
+			}
+		}
+
+		used_ids.extend(outfit_resources.iter().map(|r| r.get_id()));
+
+		let name = unique_name(&mut used_names, &sanitize(&gzps.name.to_string()));
+		let path = output_dir.join(format!("{name}.package"));
+		Dbpf::write_package_file(&outfit_resources, &path, package.is_compressed)?;
+		println!("Wrote {} ({} resources)", path.to_string_lossy(), outfit_resources.len());
+	}
+
+	write_remaining(&package, &used_ids, output_dir, stem)
+}
+
+fn split_by_object(file: &Path, output_dir: &Path, stem: &str) -> Result<(), Box<dyn Error>> {
+	let package = Dbpf::read_from_file(file, stem)?;
+	let resources = &package.resources;
+	let index = ResourceIndex::new(resources);
+
+	let mut used_ids: HashSet<Identifier> = HashSet::new();
+	let mut used_names: HashSet<String> = HashSet::new();
+
+	for resource in resources {
+		let DecodedResource::Mmat(mmat) = resource else { continue };
+		let mmat_name = mmat.name.to_string();
+
+		// the MMAT names its TXMT, with or without the group prefix, and the TXMT names its TXTRs
+		let txmt_name = format!("{mmat_name}_txmt");
+		let txmt_names = [format!("##0x{:08x}!{txmt_name}", mmat.id.group_id), txmt_name];
+		let txmts = index.by_names(&txmt_names).into_iter()
+			.filter_map(|r| if let DecodedResource::Txmt(txmt) = r { Some(txmt) } else { None })
+			.collect::<Vec<_>>();
+		if txmts.is_empty() {
+			println!("WARNING: no TXMT found for \"{mmat_name}\"");
+		}
+
+		let mut object_resources = vec![resource.clone()];
+		for txmt in txmts {
+			object_resources.push(DecodedResource::Txmt(txmt.clone()));
+			let txtr_names = txmt.txtr_names.iter().map(|name| format!("{name}_txtr"));
+			object_resources.extend(index.by_names(txtr_names).into_iter()
+				.filter(|r| matches!(r, DecodedResource::Txtr(_)))
+				.cloned());
+		}
+
+		used_ids.extend(object_resources.iter().map(|r| r.get_id()));
+
+		// names usually start with a group prefix like ##0x1c050000!
+		let short_name = mmat_name.rsplit('!').next().unwrap_or(&mmat_name);
+		let name = unique_name(&mut used_names, &sanitize(short_name));
+		let path = output_dir.join(format!("{name}.package"));
+		Dbpf::write_package_file(&object_resources, &path, package.is_compressed)?;
+		println!("Wrote {} ({} resources)", path.to_string_lossy(), object_resources.len());
+	}
+
+	write_remaining(&package, &used_ids, output_dir, stem)
+}
+
+// anything not belonging to an outfit or object still needs to go somewhere
+fn write_remaining(package: &Dbpf, used_ids: &HashSet<Identifier>, output_dir: &Path, stem: &str) -> Result<(), Box<dyn Error>> {
+	let remaining = package.resources.iter()
+		.filter(|r| !used_ids.contains(&r.get_id()))
+		.cloned()
+		.collect::<Vec<DecodedResource>>();
+	if !remaining.is_empty() {
+		let path = output_dir.join(format!("{stem}_remaining.package"));
+		Dbpf::write_package_file(&remaining, &path, package.is_compressed)?;
+		println!("Wrote {} ({} resources)", path.to_string_lossy(), remaining.len());
+	}
+
+	Ok(())
+}
+
+fn split_by_type(file: &Path, output_dir: &Path, stem: &str) -> Result<(), Box<dyn Error>> {
+	let bytes = fs::read(file)?;
+	let (resources, header, is_compressed) = Dbpf::read_resources(&bytes)?;
+
+	let mut type_ids: Vec<TypeId> = Vec::new();
+	for resource in &resources {
+		if !type_ids.contains(&resource.id.type_id) {
+			type_ids.push(resource.id.type_id);
+		}
+	}
+
+	for type_id in type_ids {
+		let type_resources = resources.iter()
+			.filter(|r| r.id.type_id == type_id)
+			.cloned()
+			.collect::<Vec<Resource>>();
+		let num_resources = type_resources.len();
+
+		let type_name = match type_id {
+			TypeId::Other(inner) => format!("{inner:08X}"),
+			type_id => sanitize(&type_id.to_string())
+		};
+		let path = output_dir.join(format!("{stem}_{type_name}.package"));
+
+		write_atomic_with(&path, |file| Dbpf::write_resources(type_resources, header.clone(), file, is_compressed))?;
+		println!("Wrote {} ({num_resources} resources)", path.to_string_lossy());
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashSet;
+	use std::fs;
+	use std::path::{ Path, PathBuf };
+
+	use crate::dbpf::{ Dbpf, Identifier, PascalString, SevenBitString };
+	use crate::dbpf::resource::{ Resource, DecodedResource };
+	use crate::split::{ SplitMode, split_package };
+	use crate::test_support::test_dir;
+	use crate::test_support::fixtures::*;
+
+	fn decode(resource: Resource) -> DecodedResource {
+		DecodedResource::new(&resource, "test").unwrap()
+	}
+
+	// a whole GZPS -> 3IDR -> SHPE -> GMND -> GMDC and TXMT -> TXTR chain with its BINX and STR#,
+	// an MMAT naming the same TXMT, and a resource that belongs to neither
+	fn linked_resources() -> Vec<DecodedResource> {
+		let DecodedResource::Shpe(shpe) = decode(shpe_resource()) else { unreachable!() };
+		let DecodedResource::Gmnd(mut gmnd) = decode(gmnd_resource()) else { unreachable!() };
+		gmnd.id = shpe.gmnd_ref.clone().unwrap();
+		let DecodedResource::Gmdc(mut gmdc) = decode(gmdc_resource()) else { unreachable!() };
+		gmdc.id = gmnd.gmdc_ref.clone();
+		let cres = decode(cres_resource());
+		let txmt = decode(txmt_resource());
+		let DecodedResource::Txtr(mut txtr) = decode(txtr_resource()) else { unreachable!() };
+		txtr.block.file_name = SevenBitString::new("##0x1c050000!test_base_txtr");
+		txtr.name = txtr.block.file_name.clone();
+		let strings = decode(text_list_resource());
+
+		let DecodedResource::Idr(mut idr) = decode(idr_resource()) else { unreachable!() };
+		idr.cres_ref = Some(cres.get_id());
+		idr.shpe_ref = Some(shpe.id.clone());
+		idr.txmt_refs = vec![txmt.get_id()];
+		idr.str_ref = Some(strings.get_id());
+		idr.gzps_ref = None;
+
+		let DecodedResource::Mmat(mut mmat) = decode(mmat_resource()) else { unreachable!() };
+		mmat.name = PascalString::new("test");
+
+		vec![
+			decode(gzps_resource()),
+			DecodedResource::Idr(idr),
+			DecodedResource::Shpe(shpe),
+			DecodedResource::Gmnd(gmnd),
+			DecodedResource::Gmdc(gmdc),
+			cres,
+			txmt,
+			DecodedResource::Txtr(txtr),
+			decode(binx_resource()),
+			strings,
+			DecodedResource::Mmat(mmat),
+			decode(other_resource())
+		]
+	}
+
+	fn ids(path: &Path) -> HashSet<Identifier> {
+		Dbpf::read_from_file(path, "test").unwrap().resources.iter().map(|r| r.get_id()).collect()
+	}
+
+	fn split(name: &str, mode: SplitMode) -> (Vec<DecodedResource>, PathBuf) {
+		let dir = test_dir(name);
+		let resources = linked_resources();
+		let file = dir.join("split.package");
+		Dbpf::write_package_file(&resources, &file, false).unwrap();
+		split_package(file, Some(dir.join("out")), mode).unwrap();
+		(resources, dir)
+	}
+
+	#[test]
+	fn outfits_are_split_with_their_whole_chain() {
+		let (resources, dir) = split("split_outfit", SplitMode::Outfit);
+		let all_ids = resources.iter().map(|r| r.get_id()).collect::<HashSet<_>>();
+		let leftover_ids = [&resources[10], &resources[11]].map(|r| r.get_id());
+
+		let outfit_ids = all_ids.iter().filter(|id| !leftover_ids.contains(id)).cloned().collect::<HashSet<_>>();
+		assert_eq!(ids(&dir.join("out/afTopTest.package")), outfit_ids);
+		assert_eq!(ids(&dir.join("out/split_remaining.package")), HashSet::from(leftover_ids));
+		fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn objects_are_split_with_their_materials_and_textures() {
+		let (resources, dir) = split("split_object", SplitMode::Object);
+		let object_ids = [&resources[10], &resources[6], &resources[7]].map(|r| r.get_id());
+		assert_eq!(ids(&dir.join("out/test.package")), HashSet::from(object_ids.clone()));
+
+		let remaining = ids(&dir.join("out/split_remaining.package"));
+		assert_eq!(remaining.len(), resources.len() - object_ids.len());
+		assert!(object_ids.iter().all(|id| !remaining.contains(id)));
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
@@ -0,0 +1,429 @@
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use crate::dbpf::{ Identifier, TypeId };
+use crate::dbpf::resource::{ Resource, DecodedResource };
+use crate::dbpf::resource_types::cpf::PropertyValue;
+use crate::dbpf::resource_types::gmdc::{ Gmdc, GmdcBlock };
+use crate::dbpf::resource_types::shpe::Material;
+use crate::test_support::{ Bytes, assert_round_trip, cpf, rcol, rcol_blocks, resource, string };
+
+pub fn gmdc_resource() -> Resource {
+	let block = Bytes::new()
+		.block_header("cGeometryDataContainer", TypeId::Gmdc, 4)
+		.sg_resource("test_body_tslocator_gmdc")
+		.u32(5)
+		// vertices, normals, uvs, bone assignments and a morph delta, with u16 references
+		.u32(1).u32(0x5B830781).u32(0).u32(2).u32(0).u32(36)
+		.f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0)
+		.u32(0)
+		.u32(1).u32(0x3B83078B).u32(0).u32(2).u32(1).u32(36)
+		.f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(1.0)
+		.u32(0)
+		.u32(1).u32(0xBB8307AB).u32(0).u32(1).u32(2).u32(24)
+		.f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(1.0)
+		.u32(0)
+		.u32(1).u32(0xFBD70111).u32(0).u32(4).u32(0).u32(12)
+		.u32(0).u32(0).u32(0x01)
+		.u32(0)
+		.u32(1).u32(0x5CF2CFE1).u32(1).u32(2).u32(3).u32(12)
+		.f32(0.0).f32(0.5).f32(0.0)
+		.u32(3).u16(0).u16(0).u16(0)
+		.u32(1)
+		.u32(4).u16(0).u16(1).u16(2).u16(3)
+		.u32(3).u32(4)
+		.u32(0).u32(0).u32(0)
+		.u32(1)
+		.u32(2).u32(0).string("body")
+		.u32(3).u16(0).u16(1).u16(2)
+		.u32(0xffffffff)
+		.u32(2).u16(0).u16(1)
+		.u32(2)
+		.f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(0.0)
+		.f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(1.0).f32(0.0)
+		.u32(1).string("body").string("fat")
+		.u32(3).u32(3)
+		.f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0)
+		.u16(0).u16(1).u16(2)
+		.u32(2)
+		.u32(3).u32(3)
+		.f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0)
+		.u16(0).u16(1).u16(2)
+		.u32(0)
+		.finish();
+	resource(TypeId::Gmdc, rcol(&[], TypeId::Gmdc, &block))
+}
+
+pub fn mesh_gmdc_resource() -> Resource {
+	let block = Bytes::new()
+		.block_header("cGeometryDataContainer", TypeId::Gmdc, 4)
+		.sg_resource("test_body_tslocator_gmdc")
+		.u32(5)
+		// vertices, normals, uvs, bone assignments and a morph delta for every vertex
+		.u32(1).u32(0x5B830781).u32(0).u32(2).u32(0).u32(36)
+		.f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0)
+		.u32(0)
+		.u32(1).u32(0x3B83078B).u32(0).u32(2).u32(1).u32(36)
+		.f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(1.0)
+		.u32(0)
+		.u32(1).u32(0xBB8307AB).u32(0).u32(1).u32(2).u32(24)
+		.f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(1.0)
+		.u32(0)
+		.u32(1).u32(0xFBD70111).u32(0).u32(4).u32(0).u32(12)
+		.u32(0xFFFFFF00).u32(0xFFFFFF00).u32(0xFFFFFF01)
+		.u32(0)
+		.u32(1).u32(0x5CF2CFE1).u32(0).u32(2).u32(3).u32(36)
+		.f32(0.0).f32(0.5).f32(0.0).f32(0.0).f32(0.5).f32(0.0).f32(0.0).f32(0.0).f32(0.0)
+		.u32(3).u16(0).u16(1).u16(2)
+		.u32(2)
+		.u32(5).u16(0).u16(1).u16(2).u16(3).u16(4)
+		.u32(3).u32(4)
+		.u32(0).u32(0).u32(0)
+		.u32(1).u16(0)
+		.u32(3).u32(1)
+		.u32(0).u32(0).u32(0)
+		.u32(2)
+		.u32(2).u32(0).string("body")
+		.u32(3).u16(0).u16(1).u16(2)
+		.u32(0xffffffff)
+		.u32(2).u16(0).u16(1)
+		// drawn as lines, so it isn't exported
+		.u32(1).u32(1).string("outline")
+		.u32(2).u16(0).u16(1)
+		.u32(0xffffffff)
+		.u32(0)
+		.u32(3)
+		.f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(0.0)
+		// a quarter turn around z
+		.f32(0.0).f32(0.0).f32(FRAC_1_SQRT_2).f32(FRAC_1_SQRT_2).f32(0.0).f32(1.0).f32(0.0)
+		.f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(1.0).f32(1.0).f32(0.0)
+		.u32(1).string("body").string("fat")
+		.u32(3).u32(3)
+		.f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0)
+		.u16(0).u16(1).u16(2)
+		.u32(3)
+		.u32(3).u32(3)
+		.f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0)
+		.u16(0).u16(1).u16(2)
+		.u32(0)
+		// joint 2 isn't used by any vertex yet
+		.u32(3).u32(3)
+		.f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0)
+		.u16(0).u16(1).u16(2)
+		.finish();
+	resource(TypeId::Gmdc, rcol(&[], TypeId::Gmdc, &block))
+}
+
+// version 3 has u32 indices and no joints per group
+pub fn gmdc_v3_resource() -> Resource {
+	let block = Bytes::new()
+		.block_header("cGeometryDataContainer", TypeId::Gmdc, 3)
+		.sg_resource("test_gmdc")
+		.u32(1)
+		.u32(1).u32(0x5B830781).u32(0).u32(2).u32(0).u32(36)
+		.f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0).f32(0.0).f32(0.0).f32(1.0).f32(0.0)
+		.u32(1).u32(7)
+		.u32(1)
+		.u32(1).u32(0)
+		.u32(3).u32(1)
+		.u32(0).u32(0).u32(0)
+		.u32(1)
+		.u32(2).u32(0).string("test")
+		.u32(3).u32(0).u32(1).u32(2)
+		.u32(0xffffffff)
+		.u32(0)
+		.u32(0)
+		.u32(0)
+		.u32(0)
+		.finish();
+	resource(TypeId::Gmdc, rcol(&[], TypeId::Gmdc, &block))
+}
+
+pub fn gmnd_resource() -> Resource {
+	let block = Bytes::new()
+		.block_header("cGeometryNode", TypeId::Gmnd, 11)
+		.object_graph("test_tslocator_gmnd")
+		.sg_resource("test_tslocator_gmnd")
+		.u16(2)
+		.u16(1)
+		.u8(1)
+		.u32(0)
+		.finish();
+	let gmdc_ref = Identifier::new(u32::from(TypeId::Gmdc), 0x1C050000, 0x11111111, 0x22222222);
+	resource(TypeId::Gmnd, rcol(&[gmdc_ref], TypeId::Gmnd, &block))
+}
+
+pub fn shpe_resource() -> Resource {
+	let block = Bytes::new()
+		.block_header("cShape", TypeId::Shpe, 8)
+		.sg_resource("test_shpe")
+		.node("cReferentNode", 1)
+		.object_graph("test_shpe")
+		// lod values
+		.u32(1).u32(0)
+		// gmnd items
+		.u32(1).u32(0).u8(1).string("##0x1c050000!test_tslocator_gmnd")
+		// materials
+		.u32(2)
+		.string("body").string("##0x1c050000!test_body_txmt").u32(0).u8(0).u32(0)
+		.string("hair").string("##0x1c050000!test_hair_txmt").u32(3).u8(1).u32(7)
+		.finish();
+	resource(TypeId::Shpe, rcol(&[], TypeId::Shpe, &block))
+}
+
+pub fn cres_resource() -> Resource {
+	let resource_node = Bytes::new()
+		.block_header("cResourceNode", TypeId::Cres, 7)
+		.u8(1)
+		.sg_resource("test_cres")
+		.node("cCompositionTreeNode", 11)
+		.object_graph("test_cres")
+		.u32(1).u8(1).u8(0).u32(1)
+		.u8(0)
+		.u32(0)
+		.finish();
+	let shape_ref = Bytes::new()
+		.block_header("cShapeRefNode", TypeId::ShapeRef, 21)
+		.node("cRenderableNode", 5)
+		.node("cBoundedNode", 5)
+		.transform_node(0, "test_shape", &[2], 0x7FFFFFFF)
+		.u16(1).u32(0)
+		.string("Practical")
+		.u32(0).u8(1)
+		.u32(1).u8(1).u8(0).u32(0)
+		.u32(0)
+		.u32(1).u32(1).string("fat")
+		.u32(4).f32(0.5)
+		.u32(0)
+		.finish();
+	let root = Bytes::new().transform_node(u32::from(TypeId::Transform), "auskel_root", &[3], 0).finish();
+	let slot = Bytes::new().transform_node(u32::from(TypeId::Transform), "r_hand_slot", &[], 0x7FFFFFFF).finish();
+	let data_list = Bytes::new()
+		.block_header("cDataListExtension", TypeId::DataList, 1)
+		.node("cExtension", 3)
+		.u8(7)
+		.u32(3)
+		.u8(2).string("slot").u32(1)
+		.u8(6).string("bone").string("r_hand")
+		.u8(7).string("offsets").u32(2)
+			.u8(5).string("translation").f32(0.0).f32(0.1).f32(0.0)
+			.u8(8).string("rotation").f32(0.0).f32(0.0).f32(0.0).f32(1.0)
+		.finish();
+	let bone_data = Bytes::new()
+		.block_header("cBoneDataExtension", TypeId::BoneData, 5)
+		.node("cExtension", 4)
+		.u8(7)
+		.string("bone")
+		.u32(2)
+		.u8(3).string("scale").f32(1.0)
+		.u8(9).string("data").u32(2).u8(0xAB).u8(0xCD)
+		.u32(1).u32(0).f32(0.5)
+		.f32(0.0).f32(0.0).f32(0.0).f32(1.0)
+		.finish();
+	let shpe_ref = Identifier::new(u32::from(TypeId::Shpe), 0x1C050000, 0x33333333, 0x44444444);
+	resource(TypeId::Cres, rcol_blocks(&[shpe_ref], &[
+		(TypeId::Cres, resource_node),
+		(TypeId::ShapeRef, shape_ref),
+		(TypeId::Transform, root),
+		(TypeId::Transform, slot),
+		(TypeId::DataList, data_list),
+		(TypeId::BoneData, bone_data)
+	]))
+}
+
+pub fn txmt_resource() -> Resource {
+	let block = Bytes::new()
+		.block_header("cMaterialDefinition", TypeId::Txmt, 11)
+		.sg_resource("##0x1c050000!test_txmt")
+		.string("test")
+		.string("SimStandardMaterial")
+		.u32(3)
+		.string("stdMatBaseTextureName").string("##0x1c050000!test_base")
+		.string("stdMatNormalMapTextureName").string("##0x1c050000!test_normal")
+		.string("stdMatAlphaBlendMode").string("none")
+		// texture list doesn't have to mirror the properties
+		.u32(1)
+		.string("##0x1c050000!test_base")
+		.finish();
+	resource(TypeId::Txmt, rcol(&[], TypeId::Txmt, &block))
+}
+
+pub fn txtr_resource() -> Resource {
+	let block = Bytes::new()
+		.block_header("cImageData", TypeId::Txtr, 9)
+		.sg_resource("##0x1c050000!test_txtr")
+		.u32(4).u32(4).u32(4).u32(2).f32(2.0)
+		.u32(2).u32(0x12)
+		.string("test repeat")
+		.u32(2)
+		.u8(0).u32(8).raw(&[1, 2, 3, 4, 5, 6, 7, 8])
+		.u8(1).string("test_lifo")
+		.u32(0x12345678).u32(0x41200000)
+		.u32(1)
+		.u8(0).u32(2).raw(&[9, 10])
+		.u32(0xffffffff).u32(0x3F800000)
+		.finish();
+	resource(TypeId::Txtr, rcol(&[], TypeId::Txtr, &block))
+}
+
+pub fn txtr_v7_resource() -> Resource {
+	let block = Bytes::new()
+		.block_header("cImageData", TypeId::Txtr, 7)
+		.sg_resource("test_txtr")
+		.u32(2).u32(2).u32(1).u32(2).f32(3.0)
+		.u32(1).u32(0)
+		.u8(0).u32(4).raw(&[1, 2, 3, 4])
+		.u8(0).u32(1).raw(&[5])
+		.u32(0x87654321)
+		.finish();
+	resource(TypeId::Txtr, rcol(&[], TypeId::Txtr, &block))
+}
+
+pub fn mmat_resource() -> Resource {
+	resource(TypeId::Mmat, cpf(2, vec![
+		("flags", PropertyValue::Uint(0)),
+		("name", string("test_mmat")),
+		("copyright", string("test")),
+		("creator", string("00000000-0000-0000-0000-000000000000")),
+		("type", string("Material")),
+		("objectGUID", PropertyValue::Uint(0x12345678)),
+		("modelName", string("test_cres")),
+		("materialStateFlags", PropertyValue::Uint(0)),
+		("objectStateIndex", PropertyValue::Int(-1)),
+		("family", string("a0000000-0000-0000-0000-000000000000")),
+		("subsetName", string("body")),
+		("defaultMaterial", PropertyValue::Bool(true))
+	]))
+}
+
+pub fn gzps_resource() -> Resource {
+	resource(TypeId::Gzps, cpf(2, vec![
+		("version", PropertyValue::Uint(6)),
+		("product", PropertyValue::Uint(1)),
+		("age", PropertyValue::Uint(72)),
+		("gender", PropertyValue::Uint(1)),
+		("species", PropertyValue::Uint(1)),
+		("outfit", PropertyValue::Uint(4)),
+		("parts", PropertyValue::Uint(4)),
+		("flags", PropertyValue::Uint(8)),
+		("name", string("afTopTest")),
+		("creator", string("00000000-0000-0000-0000-000000000000")),
+		("family", string("a0000000-0000-0000-0000-000000000000")),
+		("genetic", PropertyValue::Float(0.5)),
+		("priority", PropertyValue::Int(-2)),
+		("type", string("skin")),
+		("skintone", string("00000000-0000-0000-0000-000000000000")),
+		("hairtone", string("00000003-0000-0000-0000-000000000000")),
+		("category", PropertyValue::Uint(39)),
+		("shoe", PropertyValue::Uint(2)),
+		("fitness", PropertyValue::Uint(0)),
+		("resourcekeyidx", PropertyValue::Uint(0)),
+		("shapekeyidx", PropertyValue::Uint(1)),
+		("numoverrides", PropertyValue::Uint(1)),
+		("override0shape", PropertyValue::Uint(0)),
+		("override0subset", string("top")),
+		("override0resourcekeyidx", PropertyValue::Uint(2))
+	]))
+}
+
+pub fn idr_resource() -> Resource {
+	let mut bytes = Bytes::new();
+	bytes.u32(0xDEADBEEF).u32(2).u32(5);
+	for (type_id, instance_id) in [(TypeId::Cres, 1), (TypeId::Shpe, 2), (TypeId::Txmt, 3), (TypeId::Txmt, 4), (TypeId::Gzps, 5)] {
+		bytes.u32(u32::from(type_id)).u32(0x1C050000).u32(instance_id).u32(0xABCD0000 + instance_id);
+	}
+	resource(TypeId::Idr, bytes.finish())
+}
+
+pub fn idr_v1_resource() -> Resource {
+	let mut bytes = Bytes::new();
+	bytes.u32(0xDEADBEEF).u32(1).u32(2);
+	for (type_id, instance_id) in [(TypeId::Shpe, 2), (TypeId::Txmt, 3)] {
+		bytes.u32(u32::from(type_id)).u32(0x1C050000).u32(instance_id);
+	}
+	resource(TypeId::Idr, bytes.finish())
+}
+
+pub fn binx_resource() -> Resource {
+	resource(TypeId::Binx, cpf(0, vec![
+		("iconidx", PropertyValue::Uint(3)),
+		("stringsetidx", PropertyValue::Uint(4)),
+		("binidx", PropertyValue::Uint(5)),
+		("objectidx", PropertyValue::Uint(6)),
+		("creatorid", string("00000000-0000-0000-0000-000000000000")),
+		("sortindex", PropertyValue::Int(-3)),
+		("stringindex", PropertyValue::Uint(2))
+	]))
+}
+
+pub fn xtol_resource() -> Resource {
+	resource(TypeId::Xtol, cpf(2, vec![
+		("version", PropertyValue::Uint(2)),
+		("product", PropertyValue::Uint(1)),
+		("type", string("facemakeup")),
+		("subtype", PropertyValue::Uint(5)),
+		("name", string("test_makeup")),
+		("creator", string("00000000-0000-0000-0000-000000000000")),
+		("family", string("a0000000-0000-0000-0000-000000000000")),
+		("age", PropertyValue::Uint(72)),
+		("gender", PropertyValue::Uint(3)),
+		("species", PropertyValue::Uint(1)),
+		("category", PropertyValue::Uint(39)),
+		("skintone", string("00000000-0000-0000-0000-000000000000")),
+		("hairtone", string("00000000-0000-0000-0000-000000000000")),
+		("genetic", PropertyValue::Float(0.0)),
+		("flags", PropertyValue::Uint(0)),
+		("bin", PropertyValue::Uint(4)),
+		("layer", PropertyValue::Uint(40)),
+		("materialkeyidx", PropertyValue::Uint(1))
+	]))
+}
+
+pub fn text_list_resource() -> Resource {
+	let mut key_name = [0u8; 64];
+	key_name[..9].copy_from_slice(b"test_strs");
+	let data = Bytes::new()
+		.raw(&key_name)
+		.u16(0xfffd)
+		.u16(2)
+		.u8(1).raw(b"Test\0Description\0")
+		.u8(2).raw(b"\0\0")
+		.finish();
+	resource(TypeId::TextList, data)
+}
+
+pub fn other_resource() -> Resource {
+	resource(TypeId::Other(0x12345678), vec![0xde, 0xad, 0xbe, 0xef])
+}
+
+pub fn synthetic_resources() -> Vec<Resource> {
+	vec![
+		gmdc_resource(),
+		gmnd_resource(),
+		shpe_resource(),
+		cres_resource(),
+		mmat_resource(),
+		txmt_resource(),
+		txtr_resource(),
+		txtr_v7_resource(),
+		gzps_resource(),
+		idr_resource(),
+		binx_resource(),
+		xtol_resource(),
+		text_list_resource(),
+		other_resource()
+	]
+}
+
+pub fn mesh_gmdc_decoded() -> Gmdc {
+	let DecodedResource::Gmdc(gmdc) = assert_round_trip(&mesh_gmdc_resource()) else { panic!("not a GMDC") };
+	gmdc
+}
+
+pub fn mesh_gmdc() -> GmdcBlock {
+	mesh_gmdc_decoded().block
+}
+
+pub fn shpe_materials() -> Vec<Material> {
+	let DecodedResource::Shpe(shpe) = assert_round_trip(&shpe_resource()) else { panic!("not a SHPE") };
+	shpe.block.materials
+}
@@ -0,0 +1,199 @@
+// shared helpers for the tests next to each module
+use std::fs;
+use std::io::{ Cursor, Write };
+use std::path::{ Path, PathBuf };
+
+use binrw::BinWrite;
+
+use crate::dbpf::{ Identifier, TypeId, SevenBitString, PascalString };
+use crate::dbpf::header::Header;
+use crate::dbpf::resource::{ Resource, DecodedResource };
+use crate::dbpf::resource_types::cpf::{ Cpf, CpfType, PropertyValue };
+use crate::dbpf::writer::PackageWriter;
+
+pub mod fixtures;
+
+// decodes the resource, writes it back out and fails with a field-level report if the bytes differ
+pub fn assert_round_trip(resource: &Resource) -> DecodedResource {
+	let decoded = DecodedResource::new(resource, "test")
+		.unwrap_or_else(|err| panic!("{} failed to decode: {err}", resource.id));
+	let bytes = decoded.to_bytes()
+		.unwrap_or_else(|err| panic!("{} failed to encode: {err}", resource.id));
+	if bytes != resource.data {
+		panic!("{} does not round-trip:\n{}", resource.id, diff_report(resource, &bytes));
+	}
+	decoded
+}
+
+pub fn diff_report(resource: &Resource, bytes: &[u8]) -> String {
+	let mut report = Vec::new();
+
+	let first_difference = resource.data.iter().zip(bytes).position(|(a, b)| a != b)
+		.unwrap_or(resource.data.len().min(bytes.len()));
+	report.push(format!("  original is {} bytes, rewritten is {} bytes, first difference at offset 0x{first_difference:x}",
+		resource.data.len(), bytes.len()));
+
+	let rewritten = Resource { id: resource.id.clone(), data: bytes.to_vec() };
+	match (DecodedResource::new(resource, "test"), DecodedResource::new(&rewritten, "test")) {
+		(Ok(decoded), Ok(redecoded)) => {
+			let before = format!("{decoded:#?}");
+			let after = format!("{redecoded:#?}");
+			let field_diffs = diff_lines(&before, &after);
+			if field_diffs.is_empty() {
+				report.push("  decoded fields are identical; the decoder is not capturing the differing bytes".to_string());
+			}
+			report.extend(field_diffs);
+		}
+		(Err(err), _) => report.push(format!("  original bytes fail to decode: {err}")),
+		(_, Err(err)) => report.push(format!("  rewritten bytes fail to decode: {err}"))
+	}
+
+	report.join("\n")
+}
+
+pub fn diff_lines(before: &str, after: &str) -> Vec<String> {
+	let before = before.lines().collect::<Vec<&str>>();
+	let after = after.lines().collect::<Vec<&str>>();
+	let mut diffs = Vec::new();
+	for i in 0..before.len().max(after.len()) {
+		match (before.get(i), after.get(i)) {
+			(Some(a), Some(b)) if a == b => {}
+			(a, b) => diffs.push(format!("  - {}\n  + {}", a.unwrap_or(&"").trim(), b.unwrap_or(&"").trim()))
+		}
+	}
+	diffs
+}
+
+// little-endian byte builder for synthetic resources, independent of the decoders' own writers
+pub struct Bytes(Cursor<Vec<u8>>);
+
+impl Bytes {
+	pub fn new() -> Self {
+		Self(Cursor::new(Vec::new()))
+	}
+
+	pub fn u8(&mut self, value: u8) -> &mut Self {
+		value.write_le(&mut self.0).unwrap();
+		self
+	}
+
+	pub fn u16(&mut self, value: u16) -> &mut Self {
+		value.write_le(&mut self.0).unwrap();
+		self
+	}
+
+	pub fn u32(&mut self, value: u32) -> &mut Self {
+		value.write_le(&mut self.0).unwrap();
+		self
+	}
+
+	pub fn f32(&mut self, value: f32) -> &mut Self {
+		value.write_le(&mut self.0).unwrap();
+		self
+	}
+
+	pub fn raw(&mut self, data: &[u8]) -> &mut Self {
+		self.0.write_all(data).unwrap();
+		self
+	}
+
+	pub fn string(&mut self, value: &str) -> &mut Self {
+		SevenBitString::new(value).write(&mut self.0).unwrap();
+		self
+	}
+
+	pub fn block_header(&mut self, name: &str, type_id: TypeId, version: u32) -> &mut Self {
+		PascalString::new(name).write::<u8>(&mut self.0).unwrap();
+		self.u32(u32::from(type_id)).u32(version)
+	}
+
+	pub fn node(&mut self, name: &str, version: u32) -> &mut Self {
+		self.string(name).u32(0).u32(version)
+	}
+
+	pub fn sg_resource(&mut self, file_name: &str) -> &mut Self {
+		self.node("cSGResource", 2).string(file_name)
+	}
+
+	pub fn object_graph(&mut self, file_name: &str) -> &mut Self {
+		self.node("cObjectGraphNode", 4)
+			.u32(2)
+			.u8(1).u8(0).u32(0)
+			.u8(1).u8(1).u32(1)
+			.string(file_name)
+	}
+
+	pub fn transform_node(&mut self, block_id: u32, file_name: &str, children: &[u32], joint_id: u32) -> &mut Self {
+		self.string("cTransformNode").u32(block_id).u32(7)
+			.node("cCompositionTreeNode", 11)
+			.object_graph(file_name)
+			.u32(children.len() as u32);
+		for child in children {
+			self.u8(1).u8(0).u32(*child);
+		}
+		self.f32(0.0).f32(1.5).f32(0.0)
+			.f32(0.0).f32(0.0).f32(0.0).f32(1.0)
+			.u32(joint_id)
+	}
+
+	pub fn finish(&mut self) -> Vec<u8> {
+		self.0.get_ref().clone()
+	}
+}
+
+pub fn rcol(links: &[Identifier], block_id: TypeId, block: &[u8]) -> Vec<u8> {
+	rcol_blocks(links, &[(block_id, block.to_vec())])
+}
+
+pub fn rcol_blocks(links: &[Identifier], blocks: &[(TypeId, Vec<u8>)]) -> Vec<u8> {
+	let mut bytes = Bytes::new();
+	bytes.u32(0xFFFF0001).u32(links.len() as u32);
+	for link in links {
+		bytes.u32(link.group_id).u32(link.instance_id).u32(link.resource_id).u32(u32::from(link.type_id));
+	}
+	bytes.u32(blocks.len() as u32);
+	for (block_id, _) in blocks {
+		bytes.u32(u32::from(*block_id));
+	}
+	for (_, block) in blocks {
+		bytes.raw(block);
+	}
+	bytes.finish()
+}
+
+pub fn cpf(version: u16, props: Vec<(&str, PropertyValue)>) -> Vec<u8> {
+	let cpf = Cpf {
+		cpf_type: CpfType::Normal,
+		version: Some(version),
+		props: props.into_iter().map(|(name, value)| (name.to_string(), value)).collect()
+	};
+	let mut cur = Cursor::new(Vec::new());
+	cpf.write(&mut cur).unwrap();
+	cur.into_inner()
+}
+
+pub fn string(value: &str) -> PropertyValue {
+	PropertyValue::String(PascalString::new(value))
+}
+
+pub fn resource(type_id: TypeId, data: Vec<u8>) -> Resource {
+	Resource {
+		id: Identifier::new(u32::from(type_id), 0x1C050000, 0x12345678, 0x9ABCDEF0),
+		data
+	}
+}
+
+// writes the resources into a package, compressing the ones marked
+pub fn write_package(path: &Path, resources: Vec<(Resource, bool)>) {
+	let mut package_writer = PackageWriter::new(fs::File::create(path).unwrap(), Header::default()).unwrap();
+	for (resource, compress) in resources {
+		package_writer.add(resource, compress).unwrap();
+	}
+	package_writer.finish().unwrap();
+}
+
+pub fn test_dir(name: &str) -> PathBuf {
+	let dir = std::env::temp_dir().join(format!("clod_test_{name}_{}", std::process::id()));
+	fs::create_dir_all(&dir).unwrap();
+	dir
+}
@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::io::Cursor;
+use std::path::{ Path, PathBuf };
+
+use serde::{ Serialize, Deserialize };
+
+use crate::backup::write_atomic_with;
+use crate::dbpf::{ Identifier, TypeId };
+use crate::dbpf::format::PackageFormat;
+use crate::dbpf::header::Header;
+use crate::dbpf::index::DbpfIndex;
+use crate::dbpf::resource::Resource;
+use crate::dbpf::resource_types::cpf::{ Cpf, CpfType };
+use crate::dbpf::writer::PackageWriter;
+use crate::error::{ ClodError, report_warnings };
+
+const MANIFEST_NAME: &str = "manifest.json";
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+	major_version: u32,
+	minor_version: u32,
+	index_major_version: u32,
+	index_minor_version: u32,
+	date_created: u32,
+	date_modified: u32,
+	// where the DIR was in the index, among the resources
+	#[serde(default)]
+	dir_position: usize,
+	resources: Vec<ManifestEntry>
+}
+
+#[derive(Serialize, Deserialize)]
+struct ManifestEntry {
+	file: String,
+	type_id: String,
+	group_id: String,
+	resource_id: String,
+	instance_id: String,
+	compressed: bool,
+	// binary CPF resources exported as XML, to be converted back when packing
+	#[serde(default)]
+	cpf_as_xml: bool
+}
+
+impl ManifestEntry {
+	fn id(&self) -> Result<Identifier, Box<dyn Error>> {
+		Ok(Identifier::new(
+			parse_hex(&self.type_id)?,
+			parse_hex(&self.group_id)?,
+			parse_hex(&self.resource_id)?,
+			parse_hex(&self.instance_id)?))
+	}
+}
+
+fn parse_hex(s: &str) -> Result<u32, Box<dyn Error>> {
+	Ok(u32::from_str_radix(s.trim_start_matches("0x"), 16)?)
+}
+
+fn is_cpf_type(type_id: TypeId) -> bool {
+	matches!(type_id, TypeId::Gzps | TypeId::Xtol | TypeId::Binx | TypeId::Mmat)
+}
+
+fn type_folder(type_id: TypeId) -> String {
+	match type_id {
+		TypeId::Other(inner) => format!("{inner:08X}"),
+		type_id => type_id.to_string()
+	}
+}
+
+pub fn unpack_package(file: PathBuf, output: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+	let output_dir = output.unwrap_or(file.with_extension(""));
+
+	let mut index = DbpfIndex::open(&file)?;
+	let resources = index.read_all()?;
+
+	let mut entries = Vec::new();
+	let mut warnings = Vec::new();
+	for resource in resources {
+		let id = &resource.id;
+		let compressed = index.dir.as_ref().is_some_and(|dir| dir.uncompressed_size(id).is_some());
+
+		// binary CPFs are written out as XML so they can be edited by hand.
+		// ones that can't be read are written out as they are
+		let mut data = resource.data.clone();
+		let mut cpf_as_xml = false;
+		let mut is_cpf = is_cpf_type(id.type_id);
+		if is_cpf {
+			match Cpf::read(&resource.data) {
+				Ok(cpf) => if let CpfType::Normal = cpf.cpf_type {
+					let mut cur = Cursor::new(Vec::new());
+					cpf.write_xml("cGZPropertySetString", &mut cur)?;
+					// anything XML can't hold, like strings that aren't UTF-8, is kept binary so packing gives the same bytes
+					if from_xml(&cur.get_ref()[..]).is_ok_and(|binary| binary == resource.data) {
+						data = cur.into_inner();
+						cpf_as_xml = true;
+					} else {
+						warnings.push(format!("{id} does not survive conversion to XML and was unpacked as binary"));
+					}
+				}
+				Err(err) => {
+					warnings.push(format!("{id} is not a valid CPF and was unpacked as binary: {err}"));
+					is_cpf = false;
+				}
+			}
+		}
+
+		let extension = if is_cpf { "cpf" } else { "bin" };
+		let relative_path = format!("{}/{:08X}-{:08X}-{:08X}.{extension}",
+			type_folder(id.type_id), id.group_id, id.resource_id, id.instance_id);
+		let path = output_dir.join(&relative_path);
+		fs::create_dir_all(path.parent().unwrap_or(&output_dir))?;
+		fs::write(&path, &data)?;
+
+		entries.push(ManifestEntry {
+			file: relative_path,
+			type_id: format!("0x{:08X}", u32::from(id.type_id)),
+			group_id: format!("0x{:08X}", id.group_id),
+			resource_id: format!("0x{:08X}", id.resource_id),
+			instance_id: format!("0x{:08X}", id.instance_id),
+			compressed,
+			cpf_as_xml
+		});
+	}
+
+	let header = &index.header;
+	let dir_position = index.all_entries().iter().position(|e| e.id.type_id == TypeId::Dir);
+	let manifest = Manifest {
+		major_version: header.major_version,
+		minor_version: header.minor_version,
+		index_major_version: header.index_major_version,
+		index_minor_version: header.index_minor_version,
+		date_created: header.date_created,
+		date_modified: header.date_modified,
+		dir_position: dir_position.unwrap_or(0),
+		resources: entries
+	};
+	fs::write(output_dir.join(MANIFEST_NAME), serde_json::to_string_pretty(&manifest)?)?;
+
+	println!("Unpacked {} resources to {}", manifest.resources.len(), output_dir.to_string_lossy());
+	report_warnings(&warnings);
+
+	Ok(())
+}
+
+pub fn pack_package(input: PathBuf, output: Option<PathBuf>, format: Option<PackageFormat>) -> Result<(), Box<dyn Error>> {
+	let output_path = output.unwrap_or(input.with_extension("package"));
+
+	let manifest_path = input.join(MANIFEST_NAME);
+	let manifest: Manifest = serde_json::from_str(&fs::read_to_string(&manifest_path)
+		.map_err(|err| ClodError::io(&manifest_path, err))?)?;
+
+	let header = Header {
+		major_version: manifest.major_version,
+		minor_version: manifest.minor_version,
+		index_major_version: manifest.index_major_version,
+		index_minor_version: manifest.index_minor_version,
+		date_created: manifest.date_created,
+		date_modified: manifest.date_modified,
+		..Header::default()
+	};
+
+	let compressed_ids = manifest.resources.iter()
+		.filter(|entry| entry.compressed)
+		.map(|entry| entry.id())
+		.collect::<Result<HashSet<Identifier>, Box<dyn Error>>>()?;
+
+	// resource files are read and compressed a batch at a time as they're written
+	write_atomic_with(&output_path, |file| {
+		let resources = manifest.resources.iter().map(|entry| -> Result<Resource, Box<dyn Error>> {
+			let data = read_resource_file(&input.join(&entry.file), entry.cpf_as_xml)?;
+			Ok(Resource { id: entry.id()?, data })
+		});
+		let mut package_writer = PackageWriter::new(file, header)?
+			.with_format(format)
+			.with_dir_position(manifest.dir_position);
+		package_writer.add_all(resources, |resource| compressed_ids.contains(&resource.id))?;
+		package_writer.finish()?;
+		Ok(())
+	})?;
+
+	println!("Packed {} resources into {}", manifest.resources.len(), output_path.to_string_lossy());
+
+	Ok(())
+}
+
+fn read_resource_file(path: &Path, cpf_as_xml: bool) -> Result<Vec<u8>, Box<dyn Error>> {
+	let data = fs::read(path).map_err(|err| ClodError::io(path, err))?;
+	if !cpf_as_xml {
+		return Ok(data);
+	}
+	from_xml(&data)
+}
+
+fn from_xml(data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+	let mut cpf = Cpf::read(data)?;
+	cpf.cpf_type = CpfType::Normal;
+	let mut cur = Cursor::new(Vec::new());
+	cpf.write(&mut cur)?;
+	Ok(cur.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use crate::dbpf::{ Identifier, TypeId };
+	use crate::dbpf::header::Header;
+	use crate::dbpf::resource::Resource;
+	use crate::dbpf::resource_types::cpf::PropertyValue;
+	use crate::dbpf::writer::PackageWriter;
+	use crate::test_support::{ cpf, resource, test_dir };
+	use crate::test_support::fixtures::{ binx_resource, gzps_resource };
+	use crate::unpacker::{ pack_package, unpack_package };
+
+	#[test]
+	fn unpacking_and_packing_gives_back_the_same_package() {
+		let dir = test_dir("unpack");
+		let floats = Resource {
+			id: Identifier::new(u32::from(TypeId::Gzps), 0x1C050000, 0, 1),
+			data: cpf(3, vec![
+				("genetic", PropertyValue::Float(0.1)),
+				("small", PropertyValue::Float(1.0e-7)),
+				("negative", PropertyValue::Float(-0.0))
+			])
+		};
+		let other = resource(TypeId::Other(0x12345678), b"repeated ".repeat(64));
+		let resources = [(gzps_resource(), true), (floats, false), (other, true), (binx_resource(), false)];
+
+		let original = dir.join("original.package");
+		let mut package_writer = PackageWriter::new(fs::File::create(&original).unwrap(), Header::default())
+			.unwrap()
+			.with_dir_position(2);
+		for (resource, compress) in resources {
+			package_writer.add(resource, compress).unwrap();
+		}
+		package_writer.finish().unwrap();
+
+		let unpacked = dir.join("unpacked");
+		let packed = dir.join("packed.package");
+		unpack_package(original.clone(), Some(unpacked.clone())).unwrap();
+		let xml = fs::read_to_string(unpacked.join("GZPS/1C050000-00000000-00000001.cpf")).unwrap();
+		assert!(xml.contains("cGZPropertySetString"), "{xml}");
+		pack_package(unpacked, Some(packed.clone()), None).unwrap();
+
+		assert!(fs::read(&packed).unwrap() == fs::read(&original).unwrap());
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}